@@ -0,0 +1,3 @@
+const api = "/api/v1/users";
+fetch('/api/v1/orders?limit=10').then((res) => res.json());
+const cdn = "//cdn.elsewhere.example/lib.js";
@@ -1,4 +1,4 @@
-pub const TOP_100_PORTS: &[u16] = [
+pub const TOP_100_PORTS: &[u16] = &[
     80, 23, 443, 21, 22, 25, 3389, 110, 445, 139, 143, 53, 135, 3306, 8080, 1723, 111, 995, 993,
     5900, 1025, 587, 8888, 199, 1720, 465, 548, 113, 81, 6001, 10000, 514, 5060, 179, 1026, 2000,
     8443, 8000, 32768, 554, 26, 1433, 49152, 2001, 515, 8008, 49154, 1027, 5666, 646, 5000, 5631,
@@ -6,3 +6,10 @@ pub const TOP_100_PORTS: &[u16] = [
     543, 544, 5101, 144, 7, 389, 8009, 3128, 444, 9999, 5009, 7070, 5190, 3000, 5432, 1900, 3986,
     13, 1029, 9, 5051, 6646, 49157, 1028, 873, 1755, 2717, 4899, 9100, 119, 37,
 ];
+
+pub const HTTP_PORTS: &[u16] = &[
+    80, 81, 443, 591, 2000, 3000, 5000, 7001, 8000, 8008, 8080, 8081, 8088, 8443, 8888, 9000, 9090,
+    9443, 10000,
+];
+
+pub const HTTPS_PORTS: &[u16] = &[443, 8443, 9443, 10000];
@@ -1,7 +1,25 @@
 use ansi_term::Colour::{ Red, Blue, Yellow, Green };
+use serde::Serialize;
 
 pub struct Output;
 
+/// How module results (open ports, discovered paths, ...) are printed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+impl Format {
+    pub fn from_name(name: &str) -> Option<Format> {
+        match name {
+            "text" => Some(Format::Text),
+            "json" => Some(Format::Json),
+            _ => None,
+        }
+    }
+}
+
 impl Output {
     pub fn error(msg: &str) {
         println!("{} - {}", Red.bold().paint("[-]"), msg);
@@ -18,5 +36,15 @@ impl Output {
     pub fn success(msg: &str) {
         println!("{} - {}", Green.bold().paint("[+]"), msg);
     }
-}
 
+    /// Prints a result either as a human readable line or as one JSON object per line.
+    pub fn result<T: Serialize>(format: Format, msg: &str, item: &T) {
+        match format {
+            Format::Text => Output::success(msg),
+            Format::Json => match serde_json::to_string(item) {
+                Ok(json) => println!("{}", json),
+                Err(err) => Output::error(&format!("serializing result: {}", err)),
+            },
+        }
+    }
+}
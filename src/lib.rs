@@ -0,0 +1,98 @@
+//! Reconnaissance toolkit: port scanning, service and web fingerprinting, and spiders crawling
+//! targets for CVEs, pages and endpoints.
+//!
+//! The modules are internal, the items callers need are all exported at the root.
+//!
+//! Scanning a host for its open ports and the services behind them:
+//!
+//! ```no_run
+//! use vxsuite::{ detect_service, scan_ports, Domain, ScanSettings };
+//!
+//! let settings = ScanSettings::default();
+//! let domain = scan_ports(Domain { domain: "192.168.10.1".to_string(), open_port: Vec::new() }, &settings);
+//! for port in &domain.open_port {
+//!     if let Some(service) = detect_service(&domain.domain, port.port, &settings) {
+//!         println!("{} {}", service.port, service.name);
+//!     }
+//! }
+//! ```
+//!
+//! Or reacting to every open port as soon as it is found:
+//!
+//! ```no_run
+//! use futures::StreamExt;
+//! use vxsuite::{ detect_service, scan_ports_stream, ScanSettings };
+//!
+//! # async fn scan() {
+//! let settings = ScanSettings::default();
+//! let mut ports = scan_ports_stream("192.168.10.1", &settings);
+//! while let Some(port) = ports.next().await {
+//!     let settings = settings.clone();
+//!     tokio::task::spawn_blocking(move || detect_service("192.168.10.1", port.port, &settings));
+//! }
+//! # }
+//! ```
+//!
+//! Crawling with one of the bundled spiders, or any type implementing [`Spider`]:
+//!
+//! ```no_run
+//! use std::sync::Arc;
+//! use std::time::Duration;
+//! use vxsuite::{ Crawler, CveDetails, HttpSettings };
+//!
+//! # async fn crawl() -> Result<(), vxsuite::Error> {
+//! let spider = CveDetails::new(&HttpSettings::default()).with_min_score(9.0);
+//! Crawler::new(Duration::from_millis(200), 2, 500).run(Arc::new(spider)).await
+//! # }
+//! ```
+//!
+//! [`Crawler::stream`] hands the items over instead, along with the progress of the crawl:
+//!
+//! ```no_run
+//! use futures::StreamExt;
+//! use std::sync::Arc;
+//! use std::time::Duration;
+//! use vxsuite::{ CrawlEvent, Crawler, CveDetails, HttpSettings };
+//!
+//! # async fn crawl() -> Result<(), vxsuite::Error> {
+//! let spider = Arc::new(CveDetails::new(&HttpSettings::default()));
+//! let mut events = Crawler::new(Duration::from_millis(200), 2, 500).stream(spider).await?;
+//! while let Some(event) = events.next().await {
+//!     match event {
+//!         CrawlEvent::Item(cve) => println!("{} {}", cve.name, cve.best_score()),
+//!         CrawlEvent::Failed { url, error, .. } => eprintln!("{} : {}", url, error),
+//!         CrawlEvent::Scraped { .. } => {}
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+pub(crate) mod commons;
+pub(crate) mod module;
+
+pub use crate::commons::output::{ Format, Output };
+pub use crate::module::cache::HttpCache;
+pub use crate::module::check::{ check, CheckReport, Fixture };
+pub use crate::module::config::{ Config, Profile, Settings };
+pub use crate::module::cpe::Cpe;
+pub use crate::module::crawler::{ CrawlEvent, Crawler, Cve, CveDetails, GitHubSpider, HttpSettings, QuotesSpider, Spider };
+pub use crate::module::cvedb::{ CveQuery, CveStore };
+pub use crate::module::cvss::{ Cvss, Severity };
+pub use crate::module::dirb::{ DirBuster, DirbOptions };
+pub use crate::module::error::Error;
+pub use crate::module::fetch::FetchMode;
+pub use crate::module::fingerprint::{ HttpFingerprint, HttpFingerprinter };
+pub use crate::module::headers::HEADER_PROFILES;
+pub use crate::module::nvd::import_feed;
+pub use crate::module::proxy::ProxyList;
+pub use crate::module::render::Action;
+pub use crate::module::request::Request;
+pub use crate::module::scanner::{
+    detect_service, scan_port, scan_ports, scan_ports_stream, Connector, Domain, Port, ScanSettings, Service,
+};
+pub use crate::module::screenshot::take_screenshots;
+pub use crate::module::session::{ Login, Response, Session };
+pub use crate::module::sitemap::{ Endpoint, SiteMapper };
+pub use crate::module::tls::{ inspect_tls, TlsProbe, TlsReport };
+pub use crate::module::webdriver::{ Browser, WebDriverPool, WebDriverSettings };
@@ -0,0 +1,236 @@
+use crate::module::error::Error;
+use crate::module::request::Request;
+use crate::module::session::Response;
+use reqwest::{ header::{ HeaderMap, HeaderName, HeaderValue }, StatusCode };
+use ring::digest;
+use serde::{ Deserialize, Serialize };
+use std::fs;
+use std::path::PathBuf;
+use std::time::{ Duration, SystemTime, UNIX_EPOCH };
+use url::Url;
+
+/// A response as written to the cache directory.
+#[derive(Debug, Serialize, Deserialize)]
+struct Entry {
+    method: String,
+    request_url: String,
+    url: String,
+    status: u16,
+    headers: Vec<(String, String)>,
+    /// Base64, the body of most pages being text but not all.
+    body: String,
+    /// Seconds since the epoch.
+    fetched_at: u64,
+}
+
+/// On-disk cache of the responses of a spider, keyed by method, URL and body.
+#[derive(Debug, Clone)]
+pub struct HttpCache {
+    dir: PathBuf,
+    expiry: Option<Duration>,
+    offline: bool,
+}
+
+impl HttpCache {
+    pub fn new(dir: &str) -> Result<Self, Error> {
+        fs::create_dir_all(dir).map_err(|err| Error::Cache(format!("{} : {}", dir, err)))?;
+
+        Ok(HttpCache {
+            dir: PathBuf::from(dir),
+            expiry: None,
+            offline: false,
+        })
+    }
+
+    /// Responses older than `expiry` are fetched again, cached ones never expire otherwise.
+    pub fn with_expiry(mut self, expiry: Duration) -> Self {
+        self.expiry = Some(expiry);
+        self
+    }
+
+    /// Only serves cached responses, however old, and fails on the others instead of fetching them.
+    pub fn with_offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    /// A cache with the same settings in the `name` directory of this one, for responses of another kind.
+    pub fn subdirectory(&self, name: &str) -> Self {
+        HttpCache { dir: self.dir.join(name), ..self.clone() }
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.offline
+    }
+
+    /// The cached response to `request`, unless missing, unreadable or expired.
+    pub fn get(&self, request: &Request) -> Option<Response> {
+        let path = self.path(request);
+        let entry: Entry = match fs::read(&path).map(|data| serde_json::from_slice(&data)) {
+            Ok(Ok(entry)) => entry,
+            Ok(Err(err)) => {
+                log::warn!("cache: {} : {}", path.display(), err);
+                return None;
+            }
+            Err(_) => return None,
+        };
+
+        let age = Duration::from_secs(now().saturating_sub(entry.fetched_at));
+        if !self.offline && self.expiry.is_some_and(|expiry| age > expiry) {
+            return None;
+        }
+
+        let mut headers = HeaderMap::new();
+        for (name, value) in &entry.headers {
+            if let (Ok(name), Ok(value)) = (HeaderName::from_bytes(name.as_bytes()), HeaderValue::from_str(value)) {
+                headers.append(name, value);
+            }
+        }
+
+        log::debug!("cache: hit {} {}", entry.method, entry.request_url);
+        Some(Response {
+            url: Url::parse(&entry.url).ok()?,
+            status: StatusCode::from_u16(entry.status).ok()?,
+            headers,
+            body: base64::decode(&entry.body).ok()?,
+        })
+    }
+
+    pub fn put(&self, request: &Request, response: &Response) -> Result<(), Error> {
+        let entry = Entry {
+            method: request.method.to_string(),
+            request_url: request.url.clone(),
+            url: response.url.to_string(),
+            status: response.status.as_u16(),
+            headers: response
+                .headers
+                .iter()
+                .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+                .collect(),
+            body: base64::encode(&response.body),
+            fetched_at: now(),
+        };
+
+        let path = self.path(request);
+        fs::create_dir_all(&self.dir).map_err(|err| Error::Cache(format!("{} : {}", self.dir.display(), err)))?;
+        let data = serde_json::to_vec_pretty(&entry).map_err(|err| Error::Cache(err.to_string()))?;
+        fs::write(&path, data).map_err(|err| Error::Cache(format!("{} : {}", path.display(), err)))
+    }
+
+    fn path(&self, request: &Request) -> PathBuf {
+        let mut context = digest::Context::new(&digest::SHA256);
+        context.update(format!("{} {}\n", request.method, request.url).as_bytes());
+        if let Some(body) = &request.body {
+            context.update(body);
+        }
+
+        let name = context
+            .finish()
+            .as_ref()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<String>();
+        self.dir.join(format!("{}.json", name))
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header;
+
+    fn cache(name: &str) -> HttpCache {
+        let dir = std::env::temp_dir().join(format!("vxsuite-cache-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        HttpCache::new(dir.to_str().unwrap()).unwrap()
+    }
+
+    fn response(body: &[u8]) -> Response {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/octet-stream"));
+        headers.append(header::SET_COOKIE, HeaderValue::from_static("a=1"));
+        headers.append(header::SET_COOKIE, HeaderValue::from_static("b=2"));
+
+        Response {
+            url: Url::parse("http://target/final").unwrap(),
+            status: StatusCode::OK,
+            headers,
+            body: body.to_vec(),
+        }
+    }
+
+    /// Pretends the entry of `request` was fetched `age` ago.
+    fn age(cache: &HttpCache, request: &Request, age: Duration) {
+        let path = cache.path(request);
+        let mut entry: Entry = serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+        entry.fetched_at -= age.as_secs();
+        fs::write(&path, serde_json::to_vec(&entry).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn responses_round_trip() {
+        let cache = cache("round-trip");
+        let request = Request::get("http://target/start");
+        assert!(cache.get(&request).is_none());
+
+        cache.put(&request, &response(&[0, 159, 146, 150])).unwrap();
+        let cached = cache.get(&request).unwrap();
+
+        assert_eq!(cached.url.as_str(), "http://target/final");
+        assert_eq!(cached.status, StatusCode::OK);
+        assert_eq!(cached.body, vec![0, 159, 146, 150]);
+        assert_eq!(cached.headers.get_all(header::SET_COOKIE).iter().count(), 2);
+        assert_eq!(cached.content_type().as_deref(), Some("application/octet-stream"));
+
+        let _ = fs::remove_dir_all(&cache.dir);
+    }
+
+    #[test]
+    fn entries_are_keyed_by_method_url_and_body() {
+        let cache = cache("keys");
+        let fields = |value: &str| vec![("q".to_string(), value.to_string())];
+        let search = Request::post("http://target/search").with_form(&fields("rust")).unwrap();
+        cache.put(&search, &response(b"rust results")).unwrap();
+
+        assert_eq!(cache.get(&search).unwrap().body, b"rust results");
+        assert!(cache.get(&Request::get("http://target/search")).is_none());
+        assert!(cache.get(&Request::post("http://target/search").with_form(&fields("go")).unwrap()).is_none());
+        assert!(cache.subdirectory("rendered").get(&search).is_none());
+
+        let _ = fs::remove_dir_all(&cache.dir);
+    }
+
+    #[test]
+    fn expired_entries_are_only_served_offline() {
+        let cache = cache("expiry").with_expiry(Duration::from_secs(3600));
+        let request = Request::get("http://target/");
+        cache.put(&request, &response(b"page")).unwrap();
+
+        age(&cache, &request, Duration::from_secs(600));
+        assert!(cache.get(&request).is_some());
+
+        age(&cache, &request, Duration::from_secs(7200));
+        assert!(cache.get(&request).is_none());
+        assert!(cache.clone().with_offline(true).get(&request).is_some());
+
+        let _ = fs::remove_dir_all(&cache.dir);
+    }
+
+    #[test]
+    fn unreadable_entries_are_misses() {
+        let cache = cache("corrupt");
+        let request = Request::get("http://target/");
+        fs::write(cache.path(&request), b"{ not json").unwrap();
+
+        assert!(cache.get(&request).is_none());
+
+        let _ = fs::remove_dir_all(&cache.dir);
+    }
+}
@@ -0,0 +1,256 @@
+use crate::module::cache::HttpCache;
+use crate::module::crawler::Spider;
+use crate::module::error::Error;
+use crate::module::request::{ Request, DEFAULT_HANDLER };
+use crate::module::session::Response;
+use reqwest::{ header::{ self, HeaderMap, HeaderValue }, Method, StatusCode };
+use serde::{ Deserialize, Serialize };
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{ Path, PathBuf };
+use url::Url;
+
+/// A spider request run against recorded pages, with what its parsing should yield.
+///
+/// ```json
+/// {
+///   "request": { "url": "https://quotes.toscrape.com/js", "handler": "parse" },
+///   "pages": [{ "url": "https://quotes.toscrape.com/js", "file": "page.html" }],
+///   "expect": { "items": 2, "contains": [{ "author": "Albert Einstein" }], "follows": [{ "url": "..." }] }
+/// }
+/// ```
+#[derive(Debug, Deserialize)]
+pub struct Fixture {
+    pub request: FixtureRequest,
+    pub pages: Vec<FixturePage>,
+    pub expect: Expectations,
+    /// Directory of the fixture file, the page files being relative to it.
+    #[serde(skip)]
+    dir: PathBuf,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FixtureRequest {
+    pub url: String,
+    #[serde(default)]
+    pub handler: Option<String>,
+    #[serde(default)]
+    pub meta: HashMap<String, Value>,
+}
+
+/// A recorded response, served in place of the live site.
+#[derive(Debug, Deserialize)]
+pub struct FixturePage {
+    pub url: String,
+    #[serde(default)]
+    pub method: Option<String>,
+    #[serde(default)]
+    pub body: Option<String>,
+    #[serde(default)]
+    pub status: Option<u16>,
+    #[serde(default)]
+    pub content_type: Option<String>,
+    pub file: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct Expectations {
+    /// Exact number of items.
+    #[serde(default)]
+    pub items: Option<usize>,
+    /// Fields that some item must have, every listed one with the same value.
+    #[serde(default)]
+    pub contains: Vec<Value>,
+    /// Exact number of follow-up requests.
+    #[serde(default)]
+    pub requests: Option<usize>,
+    #[serde(default)]
+    pub follows: Vec<ExpectedRequest>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExpectedRequest {
+    pub url: String,
+    #[serde(default)]
+    pub handler: Option<String>,
+    /// Fields of the meta the request must carry, e.g. the list row of a detail page.
+    #[serde(default)]
+    pub meta: Option<Value>,
+}
+
+/// What a spider made of a fixture, and how it differs from the expectations.
+#[derive(Debug, Serialize)]
+pub struct CheckReport {
+    pub fixture: String,
+    pub items: Vec<Value>,
+    pub requests: Vec<Value>,
+    pub failures: Vec<String>,
+}
+
+impl CheckReport {
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "{} : {} ({} items, {} follow-up requests)",
+            self.fixture,
+            if self.passed() { "ok" } else { "FAILED" },
+            self.items.len(),
+            self.requests.len(),
+        )
+    }
+}
+
+impl Fixture {
+    pub fn load(path: &Path) -> Result<Fixture, Error> {
+        let data = fs::read(path).map_err(|err| Error::Internal(format!("{} : {}", path.display(), err)))?;
+        let mut fixture: Fixture = serde_json::from_slice(&data)
+            .map_err(|err| Error::Internal(format!("{} : {}", path.display(), err)))?;
+
+        fixture.dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        Ok(fixture)
+    }
+
+    /// The fixture files of `path`, itself if it is one, its `.json` files if it is a directory.
+    pub fn find(path: &Path) -> Result<Vec<PathBuf>, Error> {
+        if !path.is_dir() {
+            return Ok(vec![path.to_path_buf()]);
+        }
+
+        let mut paths = fs::read_dir(path)
+            .map_err(|err| Error::Internal(format!("{} : {}", path.display(), err)))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|extension| extension == "json"))
+            .collect::<Vec<PathBuf>>();
+        paths.sort();
+        Ok(paths)
+    }
+
+    pub fn request(&self) -> Request {
+        let mut request = Request::get(&self.request.url)
+            .with_handler(self.request.handler.as_deref().unwrap_or(DEFAULT_HANDLER));
+        request.meta = self.request.meta.clone();
+        request
+    }
+
+    /// An offline cache in `dir` holding the pages, to be given to the spider under check.
+    pub fn cache(&self, dir: &Path) -> Result<HttpCache, Error> {
+        let _ = fs::remove_dir_all(dir);
+        let cache = HttpCache::new(&dir.to_string_lossy())?.with_offline(true);
+
+        for page in &self.pages {
+            let path = self.dir.join(&page.file);
+            let body = fs::read(&path).map_err(|err| Error::Internal(format!("{} : {}", path.display(), err)))?;
+
+            let method = page.method.as_deref().unwrap_or("GET");
+            let mut request = Request::new(
+                Method::from_bytes(method.as_bytes()).map_err(|err| Error::Internal(format!("{} : {}", method, err)))?,
+                &page.url,
+            );
+            request.body = page.body.as_ref().map(|body| body.as_bytes().to_vec());
+
+            let content_type = page.content_type.as_deref().unwrap_or("text/html");
+            let mut headers = HeaderMap::new();
+            headers.insert(
+                header::CONTENT_TYPE,
+                HeaderValue::from_str(content_type).map_err(|err| Error::Internal(format!("{} : {}", content_type, err)))?,
+            );
+
+            let response = Response {
+                url: Url::parse(&page.url).map_err(|err| Error::Internal(format!("{} : {}", page.url, err)))?,
+                status: StatusCode::from_u16(page.status.unwrap_or(200))
+                    .map_err(|err| Error::Internal(format!("{} : {}", page.url, err)))?,
+                headers,
+                body,
+            };
+            cache.put(&request, &response)?;
+        }
+
+        Ok(cache)
+    }
+}
+
+/// Runs `spider` on the request of `fixture`, whose pages it must be reading from the cache.
+pub async fn check<T: Serialize>(spider: &dyn Spider<Item = T>, fixture: &Fixture, name: &str) -> CheckReport {
+    let mut report = CheckReport {
+        fixture: name.to_string(),
+        items: Vec::new(),
+        requests: Vec::new(),
+        failures: Vec::new(),
+    };
+
+    let (items, requests) = match spider.scrapy(fixture.request()).await {
+        Ok(res) => res,
+        Err(err) => {
+            report.failures.push(format!("scrapy failed: {}", err));
+            return report;
+        }
+    };
+
+    report.items = items
+        .iter()
+        .map(|item| serde_json::to_value(item).unwrap_or(Value::Null))
+        .collect();
+    report.requests = requests
+        .iter()
+        .map(|request| {
+            serde_json::json!({
+                "method": request.method.as_str(),
+                "url": request.url,
+                "handler": request.handler,
+                "meta": request.meta,
+            })
+        })
+        .collect();
+
+    let expect = &fixture.expect;
+    if let Some(count) = expect.items.filter(|count| *count != items.len()) {
+        report.failures.push(format!("expected {} items, got {}", count, items.len()));
+    }
+    for expected in &expect.contains {
+        if !report.items.iter().any(|item| contains(item, expected)) {
+            report.failures.push(format!("no item with {}", expected));
+        }
+    }
+
+    if let Some(count) = expect.requests.filter(|count| *count != requests.len()) {
+        report.failures.push(format!("expected {} follow-up requests, got {}", count, requests.len()));
+    }
+    for expected in &expect.follows {
+        let found = requests.iter().zip(&report.requests).any(|(request, json)| {
+            request.url == expected.url
+                && expected.handler.as_ref().is_none_or(|handler| *handler == request.handler)
+                && expected.meta.as_ref().is_none_or(|expected| contains(&json["meta"], expected))
+        });
+        if !found {
+            report.failures.push(format!(
+                "no follow-up request to {} [{}]",
+                expected.url,
+                expected.handler.as_deref().unwrap_or("any handler"),
+            ));
+        }
+    }
+
+    report
+}
+
+/// Whether `actual` holds every field of `expected`: objects by key, arrays element by element.
+fn contains(actual: &Value, expected: &Value) -> bool {
+    match (actual, expected) {
+        (Value::Object(actual), Value::Object(expected)) => expected
+            .iter()
+            .all(|(key, value)| actual.get(key).is_some_and(|actual| contains(actual, value))),
+        (Value::Array(actual), Value::Array(expected)) => expected
+            .iter()
+            .all(|value| actual.iter().any(|actual| contains(actual, value))),
+        // f32 fields come out of serde_json widened, 7.8 as 7.800000190734863.
+        (Value::Number(actual), Value::Number(expected)) => match (actual.as_f64(), expected.as_f64()) {
+            (Some(actual), Some(expected)) => (actual - expected).abs() < 1e-4,
+            _ => actual == expected,
+        },
+        _ => actual == expected,
+    }
+}
@@ -0,0 +1,388 @@
+use crate::commons::output::{ Format, Output };
+use crate::module::crawler::HttpSettings;
+use crate::module::error::Error;
+use crate::module::headers::{ HeaderRotation, Rotation };
+use crate::module::proxy::ProxyList;
+use crate::module::scanner::{ Connector, ScanSettings };
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+
+/// Read from the working directory when no `--config` is given.
+pub const CONFIG_FILE: &str = "vxsuite.toml";
+
+/// One layer of settings, each unset field falling through to the layers below.
+///
+/// In `vxsuite.toml` the `[defaults]` table and every `[profiles.NAME]` table are profiles.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    /// Seconds an HTTP request may take.
+    pub timeout: Option<u64>,
+    /// Seconds a TCP connection of the scanner may take.
+    pub connect_timeout: Option<u64>,
+    /// Requests crawled at the same time by a spider.
+    pub concurrency: Option<usize>,
+    /// Connections opened at the same time by dirb and the port scanner.
+    pub threads: Option<usize>,
+    /// Milliseconds a spider waits between two requests.
+    pub delay_ms: Option<u64>,
+    /// Identifies the requests instead of the user agents of the header profiles.
+    pub user_agent: Option<String>,
+    /// Browsers the requests pass for, e.g. `["chrome", "firefox"]` or `["all"]`.
+    pub header_profiles: Option<Vec<String>>,
+    /// `fixed`, `request` or `host`.
+    pub rotate_headers: Option<String>,
+    pub proxy: Option<Vec<String>>,
+    /// File of proxies, one per line.
+    pub proxy_list: Option<String>,
+    /// `text` or `json`.
+    pub format: Option<String>,
+}
+
+impl Profile {
+    /// Slow and quiet: one request at a time, spaced out, patient with the target.
+    pub fn stealth() -> Self {
+        Profile {
+            timeout: Some(15),
+            connect_timeout: Some(5),
+            concurrency: Some(1),
+            threads: Some(2),
+            delay_ms: Some(2_000),
+            ..Profile::default()
+        }
+    }
+
+    /// As fast as the target answers, giving up early on the slow ones.
+    pub fn aggressive() -> Self {
+        Profile {
+            timeout: Some(4),
+            connect_timeout: Some(1),
+            concurrency: Some(16),
+            threads: Some(64),
+            delay_ms: Some(0),
+            ..Profile::default()
+        }
+    }
+
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "stealth" => Some(Profile::stealth()),
+            "aggressive" => Some(Profile::aggressive()),
+            _ => None,
+        }
+    }
+
+    /// This profile with the fields set in `over` replaced.
+    pub fn merge(self, over: Profile) -> Self {
+        Profile {
+            timeout: over.timeout.or(self.timeout),
+            connect_timeout: over.connect_timeout.or(self.connect_timeout),
+            concurrency: over.concurrency.or(self.concurrency),
+            threads: over.threads.or(self.threads),
+            delay_ms: over.delay_ms.or(self.delay_ms),
+            user_agent: over.user_agent.or(self.user_agent),
+            header_profiles: over.header_profiles.or(self.header_profiles),
+            rotate_headers: over.rotate_headers.or(self.rotate_headers),
+            proxy: over.proxy.or(self.proxy),
+            proxy_list: over.proxy_list.or(self.proxy_list),
+            format: over.format.or(self.format),
+        }
+    }
+
+    /// The `VXSUITE_TIMEOUT`, `VXSUITE_CONNECT_TIMEOUT`, `VXSUITE_CONCURRENCY`, `VXSUITE_THREADS`,
+    /// `VXSUITE_DELAY_MS`, `VXSUITE_USER_AGENT`, `VXSUITE_HEADER_PROFILES` and `VXSUITE_PROXY` (comma separated),
+    /// `VXSUITE_ROTATE_HEADERS`, `VXSUITE_PROXY_LIST` and `VXSUITE_FORMAT` environment variables.
+    pub fn from_env() -> Result<Self, Error> {
+        Ok(Profile {
+            timeout: env_number("VXSUITE_TIMEOUT")?,
+            connect_timeout: env_number("VXSUITE_CONNECT_TIMEOUT")?,
+            concurrency: env_number("VXSUITE_CONCURRENCY")?,
+            threads: env_number("VXSUITE_THREADS")?,
+            delay_ms: env_number("VXSUITE_DELAY_MS")?,
+            user_agent: env_var("VXSUITE_USER_AGENT"),
+            header_profiles: env_list("VXSUITE_HEADER_PROFILES"),
+            rotate_headers: env_var("VXSUITE_ROTATE_HEADERS"),
+            proxy: env_list("VXSUITE_PROXY"),
+            proxy_list: env_var("VXSUITE_PROXY_LIST"),
+            format: env_var("VXSUITE_FORMAT"),
+        })
+    }
+}
+
+fn env_var(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|value| !value.trim().is_empty())
+}
+
+fn env_list(name: &str) -> Option<Vec<String>> {
+    env_var(name).map(|list| list.split(',').map(|item| item.trim().to_string()).filter(|item| !item.is_empty()).collect())
+}
+
+fn env_number<T: std::str::FromStr>(name: &str) -> Result<Option<T>, Error> {
+    match env_var(name) {
+        Some(value) => match value.trim().parse() {
+            Ok(number) => Ok(Some(number)),
+            Err(_) => Err(Error::Config(format!("{} : {} is not a number", name, value))),
+        },
+        None => Ok(None),
+    }
+}
+
+/// The `vxsuite.toml` file.
+///
+/// ```toml
+/// profile = "stealth"
+///
+/// [defaults]
+/// timeout = 10
+/// user_agent = "vxsuite (pentest for ACME, contact@example.com)"
+///
+/// [profiles.stealth]
+/// proxy = ["socks5h://127.0.0.1:9050"]
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Profile applied when none is asked for.
+    pub profile: Option<String>,
+    #[serde(default)]
+    pub defaults: Profile,
+    /// Profiles of the file, `stealth` and `aggressive` completing the built-in ones of the same name.
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+impl Config {
+    /// Reads `path`, or `vxsuite.toml` when present without a path.
+    pub fn load(path: Option<&str>) -> Result<Self, Error> {
+        let path = match path {
+            Some(path) => path,
+            None if Path::new(CONFIG_FILE).is_file() => CONFIG_FILE,
+            None => return Ok(Config::default()),
+        };
+
+        let content = std::fs::read_to_string(path).map_err(|err| Error::Config(format!("{} : {}", path, err)))?;
+        toml::from_str(&content).map_err(|err| Error::Config(format!("{} : {}", path, err)))
+    }
+
+    pub fn profile(&self, name: &str) -> Result<Profile, Error> {
+        match (Profile::builtin(name), self.profiles.get(name)) {
+            (Some(builtin), Some(profile)) => Ok(builtin.merge(profile.clone())),
+            (Some(builtin), None) => Ok(builtin),
+            (None, Some(profile)) => Ok(profile.clone()),
+            (None, None) => Err(Error::Config(format!("{} : unknown profile", name))),
+        }
+    }
+}
+
+/// The settings the commands run with.
+#[derive(Debug, Clone)]
+pub struct Settings {
+    pub timeout: Duration,
+    pub connect_timeout: Duration,
+    pub concurrency: usize,
+    pub threads: usize,
+    pub delay: Duration,
+    pub user_agent: Option<String>,
+    pub headers: HeaderRotation,
+    pub proxies: Option<ProxyList>,
+    pub format: Format,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            timeout: Duration::from_secs(6),
+            connect_timeout: Duration::from_secs(3),
+            concurrency: 2,
+            threads: 20,
+            delay: Duration::from_millis(200),
+            user_agent: None,
+            headers: HeaderRotation::default(),
+            proxies: None,
+            format: Format::Text,
+        }
+    }
+}
+
+impl Settings {
+    /// Layers, last winning: the defaults, the `[defaults]` of `config`, the profile, `env` then `cli`.
+    ///
+    /// The profile is `profile`, else `VXSUITE_PROFILE`, else the one `config` names.
+    pub fn resolve(config: &Config, profile: Option<&str>, env: Profile, cli: Profile) -> Result<Self, Error> {
+        let mut layers = config.defaults.clone();
+        let profile = profile.map(|name| name.to_string()).or_else(|| env_var("VXSUITE_PROFILE")).or_else(|| config.profile.clone());
+        if let Some(name) = profile {
+            layers = layers.merge(config.profile(&name)?);
+        }
+        let layers = layers.merge(env).merge(cli);
+
+        let defaults = Settings::default();
+        let mut proxies = layers.proxy.unwrap_or_default();
+        if let Some(path) = &layers.proxy_list {
+            proxies.extend(ProxyList::read(path)?);
+        }
+        let rotation = match &layers.rotate_headers {
+            Some(name) => Rotation::from_name(name)
+                .ok_or_else(|| Error::Config(format!("{} : expected the fixed, request or host rotation", name)))?,
+            None => Rotation::Fixed,
+        };
+        let mut headers = HeaderRotation::new(&layers.header_profiles.unwrap_or_default(), rotation)?;
+        if let Some(user_agent) = &layers.user_agent {
+            headers = headers.with_user_agent(user_agent)?;
+        }
+        let concurrency = layers.concurrency.unwrap_or(defaults.concurrency);
+        if concurrency == 0 {
+            return Err(Error::Config("concurrency : at least one request must be in flight".to_string()));
+        }
+        let threads = layers.threads.unwrap_or(defaults.threads);
+        if threads == 0 {
+            return Err(Error::Config("threads : at least one connection must be open".to_string()));
+        }
+        let format = match &layers.format {
+            Some(name) => Format::from_name(name)
+                .ok_or_else(|| Error::Config(format!("{} : expected the text or json format", name)))?,
+            None => defaults.format,
+        };
+
+        Ok(Settings {
+            timeout: layers.timeout.map(Duration::from_secs).unwrap_or(defaults.timeout),
+            connect_timeout: layers.connect_timeout.map(Duration::from_secs).unwrap_or(defaults.connect_timeout),
+            concurrency,
+            threads,
+            delay: layers.delay_ms.map(Duration::from_millis).unwrap_or(defaults.delay),
+            user_agent: layers.user_agent,
+            headers,
+            proxies: if proxies.is_empty() { None } else { Some(ProxyList::parse(proxies)?) },
+            format,
+        })
+    }
+
+    pub fn http(&self) -> HttpSettings {
+        HttpSettings {
+            timeout: self.timeout,
+            headers: self.headers.clone(),
+            proxies: self.proxies.clone(),
+        }
+    }
+
+    /// How the port scans reach their targets, only a SOCKS5 proxy relaying raw TCP connections.
+    pub fn scan(&self) -> ScanSettings {
+        let connector = Connector::through(self.proxies.as_ref()).unwrap_or_else(|| {
+            Output::warning("no SOCKS5 proxy given, port scans connect directly");
+            Connector::Direct
+        });
+
+        ScanSettings { timeout: self.connect_timeout, threads: self.threads, connector }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(toml: &str) -> Config {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn merged_fields_replace_only_when_set() {
+        let base = Profile { timeout: Some(10), user_agent: Some("base".to_string()), ..Profile::default() };
+        let over = Profile { timeout: Some(20), proxy: Some(vec!["socks5://127.0.0.1:9050".to_string()]), ..Profile::default() };
+
+        let merged = base.merge(over);
+        assert_eq!(merged.timeout, Some(20));
+        assert_eq!(merged.user_agent.as_deref(), Some("base"));
+        assert_eq!(merged.proxy, Some(vec!["socks5://127.0.0.1:9050".to_string()]));
+        assert_eq!(merged.threads, None);
+    }
+
+    #[test]
+    fn file_profiles_complete_the_builtin_ones() {
+        let config = parse(
+            r#"
+            [profiles.stealth]
+            delay_ms = 5000
+
+            [profiles.ci]
+            threads = 4
+            "#,
+        );
+
+        let stealth = config.profile("stealth").unwrap();
+        assert_eq!((stealth.concurrency, stealth.delay_ms), (Some(1), Some(5000)));
+        assert_eq!(config.profile("aggressive").unwrap().threads, Some(64));
+        assert_eq!(config.profile("ci").unwrap().threads, Some(4));
+        assert!(matches!(config.profile("missing"), Err(Error::Config(_))));
+        assert!(Profile::builtin("ci").is_none());
+    }
+
+    #[test]
+    fn environment_variables_form_a_profile() {
+        std::env::set_var("VXSUITE_CONCURRENCY", " 8 ");
+        std::env::set_var("VXSUITE_PROXY", "socks5://127.0.0.1:9050, ,http://127.0.0.1:8080");
+        std::env::set_var("VXSUITE_USER_AGENT", "  ");
+        let profile = Profile::from_env().unwrap();
+        assert_eq!(profile.concurrency, Some(8));
+        assert_eq!(profile.proxy, Some(vec!["socks5://127.0.0.1:9050".to_string(), "http://127.0.0.1:8080".to_string()]));
+        assert_eq!(profile.user_agent, None);
+
+        std::env::set_var("VXSUITE_DELAY_MS", "soon");
+        assert!(matches!(Profile::from_env(), Err(Error::Config(message)) if message.contains("VXSUITE_DELAY_MS")));
+
+        for name in ["VXSUITE_CONCURRENCY", "VXSUITE_PROXY", "VXSUITE_USER_AGENT", "VXSUITE_DELAY_MS"] {
+            std::env::remove_var(name);
+        }
+    }
+
+    #[test]
+    fn later_layers_win() {
+        let config = parse(
+            r#"
+            profile = "stealth"
+
+            [defaults]
+            timeout = 10
+            connect_timeout = 2
+            threads = 30
+            delay_ms = 100
+            format = "json"
+
+            [profiles.stealth]
+            connect_timeout = 4
+            "#,
+        );
+        let env = Profile { threads: Some(40), delay_ms: Some(50), ..Profile::default() };
+        let cli = Profile { delay_ms: Some(10), ..Profile::default() };
+
+        let settings = Settings::resolve(&config, Some("stealth"), env, cli).unwrap();
+        // The built-in stealth profile over the file defaults, itself completed by the file.
+        assert_eq!(settings.timeout, Duration::from_secs(15));
+        assert_eq!(settings.connect_timeout, Duration::from_secs(4));
+        assert_eq!(settings.concurrency, 1);
+        // The environment over the profile, the command line over the environment.
+        assert_eq!(settings.threads, 40);
+        assert_eq!(settings.delay, Duration::from_millis(10));
+        assert_eq!(settings.format, Format::Json);
+
+        let unknown = Settings::resolve(&Config::default(), Some("ci"), Profile::default(), Profile::default());
+        assert!(matches!(unknown, Err(Error::Config(_))));
+
+        let aggressive = Settings::resolve(&parse(""), Some("aggressive"), Profile::default(), Profile::default()).unwrap();
+        assert_eq!(aggressive.timeout, Duration::from_secs(4));
+        assert_eq!(aggressive.format, Format::Text);
+        assert!(aggressive.proxies.is_none());
+    }
+
+    #[test]
+    fn no_request_or_connection_at_all_is_refused() {
+        for (cli, option) in [
+            (Profile { concurrency: Some(0), ..Profile::default() }, "concurrency"),
+            (Profile { threads: Some(0), ..Profile::default() }, "threads"),
+        ] {
+            let settings = Settings::resolve(&Config::default(), Some("aggressive"), Profile::default(), cli);
+            assert!(matches!(settings, Err(Error::Config(message)) if message.starts_with(option)));
+        }
+    }
+}
@@ -0,0 +1,764 @@
+use crate::module::crawler::CpeMatch;
+use crate::module::error::Error;
+use crate::module::scanner::Service;
+use regex::Regex;
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+/// One attribute of a CPE name.
+///
+/// Values are kept in their well-formed name (WFN) form: lower case, every literal character
+/// other than letters, digits and `_` quoted with a backslash, unquoted `*` and `?` being
+/// wildcards.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Component {
+    Any,
+    NotApplicable,
+    Value(String),
+}
+
+/// Outcome of comparing a source attribute or name with a target one, as in NISTIR 7696.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Relation {
+    Disjoint,
+    Subset,
+    Superset,
+    Equal,
+    Undefined,
+}
+
+impl Component {
+    /// A value with no wildcard, every special character taken literally.
+    pub fn literal(value: &str) -> Component {
+        let mut wfn = String::new();
+        for c in value.to_lowercase().chars() {
+            push_literal(&mut wfn, c);
+        }
+        Component::Value(wfn)
+    }
+
+    fn from_formatted(value: &str) -> Component {
+        match value {
+            "*" => return Component::Any,
+            "-" => return Component::NotApplicable,
+            _ => {}
+        }
+
+        let mut wfn = String::new();
+        let mut chars = value.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => {
+                    if let Some(quoted) = chars.next() {
+                        push_literal(&mut wfn, quoted.to_ascii_lowercase());
+                    }
+                }
+                '*' | '?' => wfn.push(c),
+                _ => push_literal(&mut wfn, c.to_ascii_lowercase()),
+            }
+        }
+
+        Component::Value(wfn)
+    }
+
+    fn from_uri(value: &str) -> Result<Component, Error> {
+        match value {
+            "" => return Ok(Component::Any),
+            "-" => return Ok(Component::NotApplicable),
+            _ => {}
+        }
+
+        let mut wfn = String::new();
+        let bytes = value.as_bytes();
+        let mut index = 0;
+        while index < bytes.len() {
+            if bytes[index] == b'%' {
+                let code = value
+                    .get(index + 1..index + 3)
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                    .ok_or_else(|| Error::Cpe(format!("invalid percent encoding in {}", value)))?;
+                match code {
+                    0x01 => wfn.push('?'),
+                    0x02 => wfn.push('*'),
+                    _ => push_literal(&mut wfn, (code as char).to_ascii_lowercase()),
+                }
+                index += 3;
+            } else {
+                push_literal(&mut wfn, (bytes[index] as char).to_ascii_lowercase());
+                index += 1;
+            }
+        }
+
+        Ok(Component::Value(wfn))
+    }
+
+    fn to_formatted(&self) -> String {
+        match self {
+            Component::Any => "*".to_string(),
+            Component::NotApplicable => "-".to_string(),
+            Component::Value(wfn) => {
+                let mut formatted = String::new();
+                let mut chars = wfn.chars();
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => match chars.next() {
+                            Some(quoted @ ('.' | '-')) => formatted.push(quoted),
+                            Some(quoted) => {
+                                formatted.push('\\');
+                                formatted.push(quoted);
+                            }
+                            None => {}
+                        },
+                        _ => formatted.push(c),
+                    }
+                }
+                // A lone unquoted hyphen would read as NA.
+                if formatted == "-" {
+                    formatted.insert(0, '\\');
+                }
+                formatted
+            }
+        }
+    }
+
+    fn to_uri(&self) -> String {
+        match self {
+            Component::Any => String::new(),
+            Component::NotApplicable => "-".to_string(),
+            Component::Value(wfn) => {
+                let mut uri = String::new();
+                let mut chars = wfn.chars();
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => match chars.next() {
+                            Some(quoted @ ('.' | '-')) => uri.push(quoted),
+                            Some(quoted) => uri.push_str(&format!("%{:02x}", quoted as u32)),
+                            None => {}
+                        },
+                        '?' => uri.push_str("%01"),
+                        '*' => uri.push_str("%02"),
+                        _ => uri.push(c),
+                    }
+                }
+                if uri == "-" {
+                    uri = "%2d".to_string();
+                }
+                uri
+            }
+        }
+    }
+
+    fn has_wildcards(&self) -> bool {
+        match self {
+            Component::Value(wfn) => wildcard_positions(wfn).next().is_some(),
+            _ => false,
+        }
+    }
+
+    /// The value with quoting removed, wildcards left as they are.
+    pub fn unquoted(&self) -> Option<String> {
+        match self {
+            Component::Value(wfn) => {
+                let mut value = String::new();
+                let mut chars = wfn.chars();
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => value.extend(chars.next()),
+                        _ => value.push(c),
+                    }
+                }
+                Some(value)
+            }
+            _ => None,
+        }
+    }
+
+    /// Compares this source attribute with a target one (NISTIR 7696, table 6-2).
+    pub fn compare(&self, target: &Component) -> Relation {
+        if target.has_wildcards() {
+            return Relation::Undefined;
+        }
+
+        match (self, target) {
+            (Component::Any, Component::Any) => Relation::Equal,
+            (Component::Any, _) => Relation::Superset,
+            (Component::NotApplicable, Component::Any) => Relation::Subset,
+            (Component::NotApplicable, Component::NotApplicable) => Relation::Equal,
+            (Component::NotApplicable, Component::Value(_)) => Relation::Disjoint,
+            (Component::Value(_), Component::Any) => Relation::Subset,
+            (Component::Value(_), Component::NotApplicable) => Relation::Disjoint,
+            (Component::Value(source), Component::Value(value)) => {
+                if source == value {
+                    Relation::Equal
+                } else if self.has_wildcards() && wildcard_regex(source).is_match(value) {
+                    Relation::Superset
+                } else {
+                    Relation::Disjoint
+                }
+            }
+        }
+    }
+}
+
+fn push_literal(wfn: &mut String, c: char) {
+    if !c.is_ascii_alphanumeric() && c != '_' {
+        wfn.push('\\');
+    }
+    wfn.push(c);
+}
+
+/// Positions of the unquoted `*` and `?` of a WFN value.
+fn wildcard_positions(wfn: &str) -> impl Iterator<Item = usize> + '_ {
+    let mut quoted = false;
+    wfn.char_indices().filter_map(move |(index, c)| {
+        let wildcard = !quoted && (c == '*' || c == '?');
+        quoted = !quoted && c == '\\';
+        if wildcard {
+            Some(index)
+        } else {
+            None
+        }
+    })
+}
+
+/// Regex matching the WFN form of the values `source` stands for.
+fn wildcard_regex(source: &str) -> Regex {
+    let mut pattern = String::from("^");
+    let mut chars = source.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            // Wildcards stand for unquoted value characters, quoted ones counting as one.
+            '*' => pattern.push_str(r"(?:\\.|[^\\])*"),
+            '?' => pattern.push_str(r"(?:\\.|[^\\])"),
+            '\\' => {
+                pattern.push_str(r"\\");
+                if let Some(quoted) = chars.next() {
+                    pattern.push_str(&regex::escape(&quoted.to_string()));
+                }
+            }
+            _ => pattern.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    pattern.push('$');
+
+    Regex::new(&pattern).expect("cpe: Compiling wildcard pattern")
+}
+
+/// A CPE 2.3 name, parsed from its formatted string (`cpe:2.3:a:...`) or URI (`cpe:/a:...`) binding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cpe {
+    pub part: Component,
+    pub vendor: Component,
+    pub product: Component,
+    pub version: Component,
+    pub update: Component,
+    pub edition: Component,
+    pub language: Component,
+    pub sw_edition: Component,
+    pub target_sw: Component,
+    pub target_hw: Component,
+    pub other: Component,
+}
+
+impl Cpe {
+    /// An application CPE for `vendor:product:version`, every other attribute left to ANY.
+    pub fn application(vendor: &str, product: &str, version: Option<&str>) -> Cpe {
+        Cpe {
+            part: Component::literal("a"),
+            vendor: Component::literal(vendor),
+            product: Component::literal(product),
+            version: version.map(Component::literal).unwrap_or(Component::Any),
+            update: Component::Any,
+            edition: Component::Any,
+            language: Component::Any,
+            sw_edition: Component::Any,
+            target_sw: Component::Any,
+            target_hw: Component::Any,
+            other: Component::Any,
+        }
+    }
+
+    /// The CPE of a service identified from its banner, if vendor and product are known.
+    pub fn from_service(service: &Service) -> Option<Cpe> {
+        let vendor = service.vendor.as_deref()?;
+        let product = service.product.as_deref()?;
+        let mut cpe = Cpe::application(vendor, product, service.version.as_deref());
+
+        // NVD records OpenSSH portable releases as version 8.2 update p1 rather than 8.2p1.
+        if let (Some(version), "openssh") = (service.version.as_deref(), product) {
+            if let Some(index) = version.find('p') {
+                cpe.version = Component::literal(&version[..index]);
+                cpe.update = Component::literal(&version[index..]);
+            }
+        }
+
+        Some(cpe)
+    }
+
+    /// The 11 attributes in binding order.
+    fn components(&self) -> [&Component; 11] {
+        [
+            &self.part,
+            &self.vendor,
+            &self.product,
+            &self.version,
+            &self.update,
+            &self.edition,
+            &self.language,
+            &self.sw_edition,
+            &self.target_sw,
+            &self.target_hw,
+            &self.other,
+        ]
+    }
+
+    fn from_components(mut components: Vec<Component>) -> Cpe {
+        components.resize(11, Component::Any);
+        let mut components = components.into_iter();
+        let mut next = || components.next().unwrap_or(Component::Any);
+
+        Cpe {
+            part: next(),
+            vendor: next(),
+            product: next(),
+            version: next(),
+            update: next(),
+            edition: next(),
+            language: next(),
+            sw_edition: next(),
+            target_sw: next(),
+            target_hw: next(),
+            other: next(),
+        }
+    }
+
+    pub fn parse_formatted(value: &str) -> Result<Cpe, Error> {
+        let rest = value
+            .strip_prefix("cpe:2.3:")
+            .ok_or_else(|| Error::Cpe(format!("{} is not a CPE 2.3 formatted string", value)))?;
+
+        let mut fields = Vec::new();
+        let mut field = String::new();
+        let mut quoted = false;
+        for c in rest.chars() {
+            if c == ':' && !quoted {
+                fields.push(std::mem::take(&mut field));
+            } else {
+                field.push(c);
+            }
+            quoted = !quoted && c == '\\';
+        }
+        fields.push(field);
+
+        if fields.len() != 11 {
+            return Err(Error::Cpe(format!("{} does not have 11 attributes", value)));
+        }
+
+        let cpe = Cpe::from_components(fields.iter().map(|field| Component::from_formatted(field)).collect());
+        cpe.validate_part(value)?;
+        Ok(cpe)
+    }
+
+    pub fn parse_uri(value: &str) -> Result<Cpe, Error> {
+        let rest = value
+            .strip_prefix("cpe:/")
+            .ok_or_else(|| Error::Cpe(format!("{} is not a CPE URI", value)))?;
+
+        let fields = rest.split(':').collect::<Vec<&str>>();
+        if fields.len() > 7 {
+            return Err(Error::Cpe(format!("{} has too many attributes", value)));
+        }
+
+        let mut components = Vec::new();
+        for (index, field) in fields.iter().enumerate() {
+            // A packed edition carries the four extended attributes: ~edition~sw~target_sw~target_hw~other
+            if index == 5 && field.starts_with('~') {
+                let packed = field[1..].split('~').collect::<Vec<&str>>();
+                if packed.len() != 5 {
+                    return Err(Error::Cpe(format!("invalid packed edition in {}", value)));
+                }
+                components.push(Component::from_uri(packed[0])?);
+                let language = fields.get(6).map(|field| Component::from_uri(field)).transpose()?;
+                components.push(language.unwrap_or(Component::Any));
+                for extended in &packed[1..] {
+                    components.push(Component::from_uri(extended)?);
+                }
+                break;
+            }
+            components.push(Component::from_uri(field)?);
+        }
+
+        let cpe = Cpe::from_components(components);
+        cpe.validate_part(value)?;
+        Ok(cpe)
+    }
+
+    fn validate_part(&self, value: &str) -> Result<(), Error> {
+        match &self.part {
+            Component::Any => Ok(()),
+            Component::Value(part) if ["a", "o", "h"].contains(&part.as_str()) => Ok(()),
+            _ => Err(Error::Cpe(format!("invalid part in {}", value))),
+        }
+    }
+
+    /// The URI binding; attributes the URI form cannot carry are packed into the edition.
+    pub fn to_uri(&self) -> String {
+        let extended = [&self.sw_edition, &self.target_sw, &self.target_hw, &self.other];
+        let edition = if extended.iter().all(|component| **component == Component::Any) {
+            self.edition.to_uri()
+        } else {
+            format!(
+                "~{}~{}~{}~{}~{}",
+                self.edition.to_uri(),
+                self.sw_edition.to_uri(),
+                self.target_sw.to_uri(),
+                self.target_hw.to_uri(),
+                self.other.to_uri(),
+            )
+        };
+
+        let mut fields = vec![
+            self.part.to_uri(),
+            self.vendor.to_uri(),
+            self.product.to_uri(),
+            self.version.to_uri(),
+            self.update.to_uri(),
+            edition,
+            self.language.to_uri(),
+        ];
+        while fields.len() > 1 && fields.last().is_some_and(|field| field.is_empty()) {
+            fields.pop();
+        }
+
+        format!("cpe:/{}", fields.join(":"))
+    }
+
+    /// Attribute-wise relations between this source name and `target`.
+    pub fn compare(&self, target: &Cpe) -> Vec<Relation> {
+        self.components()
+            .iter()
+            .zip(target.components().iter())
+            .map(|(source, target)| source.compare(target))
+            .collect()
+    }
+
+    /// Whether this name matches `target` (CPE_SUPERSET or CPE_EQUAL).
+    pub fn is_superset(&self, target: &Cpe) -> bool {
+        self.compare(target)
+            .iter()
+            .all(|relation| matches!(relation, Relation::Superset | Relation::Equal))
+    }
+
+    pub fn is_subset(&self, target: &Cpe) -> bool {
+        self.compare(target)
+            .iter()
+            .all(|relation| matches!(relation, Relation::Subset | Relation::Equal))
+    }
+
+    pub fn is_equal(&self, target: &Cpe) -> bool {
+        self.compare(target).iter().all(|relation| *relation == Relation::Equal)
+    }
+
+    pub fn is_disjoint(&self, target: &Cpe) -> bool {
+        self.compare(target).contains(&Relation::Disjoint)
+    }
+
+    /// Version used against range bounds, the update appended as NVD does (e.g. 8.2p1).
+    fn range_version(&self) -> Option<String> {
+        if self.version.has_wildcards() {
+            return None;
+        }
+
+        let version = self.version.unquoted()?;
+        match self.update.unquoted() {
+            Some(update) if !self.update.has_wildcards() => Some(format!("{}{}", version, update)),
+            _ => Some(version),
+        }
+    }
+}
+
+impl FromStr for Cpe {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if value.starts_with("cpe:2.3:") {
+            Cpe::parse_formatted(value)
+        } else {
+            Cpe::parse_uri(value)
+        }
+    }
+}
+
+/// The formatted string binding.
+impl fmt::Display for Cpe {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let fields = self
+            .components()
+            .iter()
+            .map(|component| component.to_formatted())
+            .collect::<Vec<String>>();
+
+        write!(f, "cpe:2.3:{}", fields.join(":"))
+    }
+}
+
+impl CpeMatch {
+    fn has_range(&self) -> bool {
+        self.version_start_including.is_some()
+            || self.version_start_excluding.is_some()
+            || self.version_end_including.is_some()
+            || self.version_end_excluding.is_some()
+    }
+
+    /// Whether `target` is one of the platforms this NVD match covers, version range included.
+    pub fn matches(&self, target: &Cpe) -> bool {
+        let criteria = match Cpe::parse_formatted(&self.criteria) {
+            Ok(criteria) => criteria,
+            Err(_) => return false,
+        };
+
+        if !self.has_range() {
+            return criteria.is_superset(target);
+        }
+
+        // The range replaces the version attribute, the update being part of the bounds.
+        let unversioned = Cpe {
+            version: Component::Any,
+            update: Component::Any,
+            ..criteria
+        };
+        if !unversioned.is_superset(target) {
+            return false;
+        }
+
+        let version = match target.range_version() {
+            Some(version) => version,
+            None => return false,
+        };
+        let bound = |bound: &Option<String>, accepted: &[Ordering]| {
+            bound
+                .as_deref()
+                .is_none_or(|bound| accepted.contains(&compare_versions(&version, bound)))
+        };
+
+        bound(&self.version_start_including, &[Ordering::Greater, Ordering::Equal])
+            && bound(&self.version_start_excluding, &[Ordering::Greater])
+            && bound(&self.version_end_including, &[Ordering::Less, Ordering::Equal])
+            && bound(&self.version_end_excluding, &[Ordering::Less])
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum VersionToken {
+    Number(u64),
+    Text(String),
+}
+
+/// Suffixes marking a release that comes before the version it is attached to.
+const PRE_RELEASES: &[&str] = &["dev", "alpha", "a", "beta", "b", "pre", "rc"];
+
+fn version_tokens(version: &str) -> Vec<VersionToken> {
+    let mut tokens = Vec::new();
+
+    for piece in version.split(|c: char| !c.is_ascii_alphanumeric()).filter(|piece| !piece.is_empty()) {
+        let mut current = String::new();
+        for c in piece.chars() {
+            if !current.is_empty() && current.chars().last().map(|last| last.is_ascii_digit()) != Some(c.is_ascii_digit()) {
+                tokens.push(version_token(std::mem::take(&mut current)));
+            }
+            current.push(c.to_ascii_lowercase());
+        }
+        tokens.push(version_token(current));
+    }
+
+    tokens
+}
+
+fn version_token(value: String) -> VersionToken {
+    match value.parse() {
+        Ok(number) => VersionToken::Number(number),
+        Err(_) => VersionToken::Text(value),
+    }
+}
+
+/// Compares dotted versions numerically where possible: 1.10 > 1.9, 8.2p1 > 8.2 > 8.2rc1.
+pub fn compare_versions(left: &str, right: &str) -> Ordering {
+    let left = version_tokens(left);
+    let right = version_tokens(right);
+
+    for index in 0..left.len().max(right.len()) {
+        let ordering = match (left.get(index), right.get(index)) {
+            (Some(VersionToken::Number(left)), Some(VersionToken::Number(right))) => left.cmp(right),
+            (Some(VersionToken::Text(left)), Some(VersionToken::Text(right))) => left.cmp(right),
+            (Some(VersionToken::Number(_)), Some(VersionToken::Text(_))) => Ordering::Greater,
+            (Some(VersionToken::Text(_)), Some(VersionToken::Number(_))) => Ordering::Less,
+            (Some(extra), None) => extra_ordering(extra),
+            (None, Some(extra)) => extra_ordering(extra).reverse(),
+            (None, None) => Ordering::Equal,
+        };
+
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+
+    Ordering::Equal
+}
+
+/// How a version with `extra` as first additional token compares with the shorter one.
+fn extra_ordering(extra: &VersionToken) -> Ordering {
+    match extra {
+        VersionToken::Text(text) if PRE_RELEASES.contains(&text.as_str()) => Ordering::Less,
+        VersionToken::Number(0) => Ordering::Equal,
+        _ => Ordering::Greater,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cpe(value: &str) -> Cpe {
+        value.parse().unwrap()
+    }
+
+    fn range(criteria: &str, start_including: Option<&str>, end_excluding: Option<&str>) -> CpeMatch {
+        CpeMatch {
+            criteria: criteria.to_string(),
+            vulnerable: true,
+            version_start_including: start_including.map(String::from),
+            version_start_excluding: None,
+            version_end_including: None,
+            version_end_excluding: end_excluding.map(String::from),
+        }
+    }
+
+    #[test]
+    fn formatted_string_round_trips() {
+        let value = r"cpe:2.3:a:microsoft:internet_explorer:8.0.6001:beta:*:*:*:*:*:*";
+        let parsed = cpe(value);
+
+        assert_eq!(parsed.vendor, Component::literal("microsoft"));
+        assert_eq!(parsed.version, Component::literal("8.0.6001"));
+        assert_eq!(parsed.edition, Component::Any);
+        assert_eq!(parsed.to_string(), value);
+
+        let quoted = cpe(r"cpe:2.3:a:hp:insight_diagnostics:7.4.0.1570:-:*:*:online:win2003:x64:*");
+        assert_eq!(quoted.update, Component::NotApplicable);
+        assert_eq!(quoted.to_string(), r"cpe:2.3:a:hp:insight_diagnostics:7.4.0.1570:-:*:*:online:win2003:x64:*");
+
+        let escaped = cpe(r"cpe:2.3:a:foo\:bar:baz:1.0:*:*:*:*:*:*:*");
+        assert_eq!(escaped.vendor.unquoted().unwrap(), "foo:bar");
+    }
+
+    #[test]
+    fn invalid_names_are_rejected() {
+        assert!(Cpe::parse_formatted("cpe:2.3:a:vendor:product").is_err());
+        assert!(Cpe::parse_formatted("cpe:2.3:x:vendor:product:*:*:*:*:*:*:*:*").is_err());
+        assert!(Cpe::parse_uri("cpe:/a:vendor:product:1.0:%zz").is_err());
+        assert!(Cpe::parse_uri("cpe:/a:b:c:d:e:f:g:h").is_err());
+    }
+
+    #[test]
+    fn uri_binding_round_trips() {
+        let parsed = cpe("cpe:/a:openbsd:openssh:8.2:p1");
+        assert_eq!(parsed.update, Component::literal("p1"));
+        assert_eq!(parsed.edition, Component::Any);
+        assert_eq!(parsed.to_uri(), "cpe:/a:openbsd:openssh:8.2:p1");
+        assert_eq!(parsed.to_string(), "cpe:2.3:a:openbsd:openssh:8.2:p1:*:*:*:*:*:*");
+
+        let packed = cpe("cpe:/a:hp:insight_diagnostics:7.4.0.1570:-:~~online~win2003~x64~");
+        assert_eq!(packed.sw_edition, Component::literal("online"));
+        assert_eq!(packed.target_hw, Component::literal("x64"));
+        assert_eq!(packed.to_uri(), "cpe:/a:hp:insight_diagnostics:7.4.0.1570:-:~~online~win2003~x64~");
+
+        let encoded = cpe("cpe:/a:foo%21:bar");
+        assert_eq!(encoded.vendor.unquoted().unwrap(), "foo!");
+        assert_eq!(encoded.to_uri(), "cpe:/a:foo%21:bar");
+    }
+
+    #[test]
+    fn attribute_relations_follow_the_matching_table() {
+        let value = Component::literal("openssh");
+
+        assert_eq!(Component::Any.compare(&value), Relation::Superset);
+        assert_eq!(value.compare(&Component::Any), Relation::Subset);
+        assert_eq!(value.compare(&value), Relation::Equal);
+        assert_eq!(Component::NotApplicable.compare(&value), Relation::Disjoint);
+        assert_eq!(value.compare(&Component::literal("openssl")), Relation::Disjoint);
+        assert_eq!(Component::from_formatted("open*").compare(&value), Relation::Superset);
+        assert_eq!(Component::from_formatted("opens?h").compare(&value), Relation::Superset);
+        assert_eq!(value.compare(&Component::from_formatted("open*")), Relation::Undefined);
+    }
+
+    #[test]
+    fn names_compare_attribute_wise() {
+        let criteria = cpe("cpe:2.3:a:openbsd:openssh:*:*:*:*:*:*:*:*");
+        let target = Cpe::application("openbsd", "openssh", Some("8.2"));
+
+        assert!(criteria.is_superset(&target));
+        assert!(target.is_subset(&criteria));
+        assert!(!criteria.is_equal(&target));
+        assert!(target.is_equal(&target.clone()));
+        assert!(cpe("cpe:2.3:a:openbsd:openssh:7.4:*:*:*:*:*:*:*").is_disjoint(&target));
+    }
+
+    #[test]
+    fn cpe_match_checks_version_ranges() {
+        let target = Cpe::application("openbsd", "openssh", Some("8.2"));
+        let criteria = "cpe:2.3:a:openbsd:openssh:*:*:*:*:*:*:*:*";
+
+        assert!(range(criteria, None, None).matches(&target));
+        assert!(range(criteria, Some("8.0"), Some("8.3")).matches(&target));
+        assert!(range(criteria, Some("8.2"), None).matches(&target));
+        assert!(!range(criteria, None, Some("8.2")).matches(&target));
+        assert!(!range(criteria, Some("8.3"), None).matches(&target));
+        assert!(!range("cpe:2.3:a:openbsd:openssl:*:*:*:*:*:*:*:*", None, Some("9.0")).matches(&target));
+        assert!(!range("not a cpe", None, None).matches(&target));
+
+        let exact = range("cpe:2.3:a:openbsd:openssh:8.2:*:*:*:*:*:*:*", None, None);
+        assert!(exact.matches(&target));
+        assert!(!exact.matches(&Cpe::application("openbsd", "openssh", Some("8.3"))));
+    }
+
+    #[test]
+    fn ranges_include_the_update() {
+        let mut target = Cpe::application("openbsd", "openssh", Some("8.2"));
+        target.update = Component::literal("p1");
+        let criteria = "cpe:2.3:a:openbsd:openssh:*:*:*:*:*:*:*:*";
+
+        assert!(range(criteria, None, Some("8.3p1")).matches(&target));
+        assert!(!range(criteria, None, Some("8.2")).matches(&target));
+        assert!(range(criteria, Some("8.2"), None).matches(&target));
+    }
+
+    #[test]
+    fn versions_compare_numerically() {
+        assert_eq!(compare_versions("1.10", "1.9"), Ordering::Greater);
+        assert_eq!(compare_versions("1.0", "1"), Ordering::Equal);
+        assert_eq!(compare_versions("8.2p1", "8.2"), Ordering::Greater);
+        assert_eq!(compare_versions("8.2rc1", "8.2"), Ordering::Less);
+        assert_eq!(compare_versions("2.4.49", "2.4.50"), Ordering::Less);
+        assert_eq!(compare_versions("1.1.1k", "1.1.1j"), Ordering::Greater);
+    }
+
+    #[test]
+    fn services_map_to_application_names() {
+        let service = Service {
+            port: 22,
+            name: "ssh".to_string(),
+            vendor: Some("openbsd".to_string()),
+            product: Some("openssh".to_string()),
+            version: Some("8.2p1".to_string()),
+            cpe: None,
+            banner: "SSH-2.0-OpenSSH_8.2p1".to_string(),
+        };
+
+        let cpe = Cpe::from_service(&service).unwrap();
+        assert_eq!(cpe.to_string(), "cpe:2.3:a:openbsd:openssh:8.2:p1:*:*:*:*:*:*");
+
+        let unknown = Service { vendor: None, ..service };
+        assert!(Cpe::from_service(&unknown).is_none());
+    }
+}
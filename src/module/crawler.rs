@@ -1,65 +1,254 @@
 use async_trait::async_trait;
+use crate::module::cvedb::CveStore;
+use crate::module::cvss::{ Cvss, CvssV2, Severity };
 use crate::module::error::Error;
-use reqwest::{ Client, header };
+use crate::module::fetch::{ FetchMode, Fetcher };
+use crate::module::render::Action;
+use crate::module::request::Request;
+use crate::module::cache::HttpCache;
+use crate::module::headers::HeaderRotation;
+use crate::module::proxy::ProxyList;
+use crate::module::session::{ Response, Session };
+use crate::module::webdriver::WebDriverPool;
+use reqwest::{ Client, ClientBuilder as HttpClientBuilder, header };
 use regex::Regex;
 use serde::{ Deserialize, Serialize };
 use select::{ document::Document, predicate::{ Attr, Class, Name, Predicate } };
-use tokio::sync::Mutex;
-use fantoccini::{ Client as HttpClient, ClientBuilder };
 use std::{
-    collections::HashSet,
+    cmp::Ordering as PriorityOrdering,
+    collections::{ BinaryHeap, HashSet },
     sync::{
-        atomic::{ AtomicUsize, Ordering },
-        Arc,
+        atomic::{ AtomicBool, AtomicUsize, Ordering },
+        Arc, Mutex,
     },
 };
 use std::time::Duration;
 use tokio::{
-    sync::{ mpsc, Barrier },
+    sync::mpsc,
     time::sleep,
 };
-use futures::stream::StreamExt;
+use futures::stream::{ Stream, StreamExt };
 
+/// A crawl of one kind of site: where it starts, how its pages are parsed and what becomes of the items.
 #[async_trait]
 pub trait Spider: Send + Sync {
+    /// What the pages are scraped into.
     type Item;
     fn name(&self) -> String;
     fn start_urls(&self) -> Vec<String>;
-    async fn scrapy(&self, url: String) -> Result<(Vec<Self::Item>, Vec<String>), Error>;
+
+    /// Requests the crawl begins with, GETs of `start_urls` for the default handler unless overridden.
+    fn start_requests(&self) -> Vec<Request> {
+        self.start_urls().into_iter().map(Request::from).collect()
+    }
+
+    /// Runs before the start requests, e.g. to log in to the target or reach the browser.
+    async fn open(&self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Parses the response to `request` with the handler it names.
+    async fn scrapy(&self, request: Request) -> Result<(Vec<Self::Item>, Vec<Request>), Error>;
+    /// Handles a scraped item, e.g. prints or stores it.
     async fn process(&self, item: Self::Item) -> Result<(), Error>;
+
+    /// Runs once the crawl is over and every item processed, e.g. to record how far it got.
+    async fn close(&self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// HTTP client configuration shared by the spiders and the other HTTP modules.
+#[derive(Debug, Clone)]
+pub struct HttpSettings {
+    pub timeout: Duration,
+    pub headers: HeaderRotation,
+    pub proxies: Option<ProxyList>,
+}
+
+impl Default for HttpSettings {
+    fn default() -> Self {
+        HttpSettings {
+            timeout: Duration::from_secs(6),
+            headers: HeaderRotation::default(),
+            proxies: None,
+        }
+    }
+}
+
+impl HttpSettings {
+    pub fn client_builder(&self) -> HttpClientBuilder {
+        let mut builder = Client::builder().timeout(self.timeout).default_headers(self.headers.first());
+
+        if let Some(proxies) = &self.proxies {
+            builder = builder.proxy(proxies.proxy());
+            // A pooled connection would keep going through the proxy it was opened with.
+            if proxies.is_rotating() {
+                builder = builder.pool_max_idle_per_host(0);
+            }
+        }
+
+        builder
+    }
 }
 
 pub struct CveDetails {
-    client: Client,
+    session: Session,
+    store: Option<Arc<CveStore>>,
+    min_score: Option<f32>,
+    since: Option<String>,
+    /// Scheme and host the list and detail pages are fetched from.
+    base_url: String,
+    /// Newest `update_date` saved by this run, recorded as the crawl state once it completes.
+    newest_update: Mutex<Option<String>>,
+    /// Set when a page could not be fetched or a CVE saved, the crawl state then stays where it was.
+    incomplete: AtomicBool,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Cve {
-    name: String,
-    url: String,
-    cwe_id: Option<String>,
-    cwe_url: Option<String>,
-    vulnerability_type: String,
-    publish_date: String,
-    update_date: String,
-    score: f32,
-    access: String,
-    complexity: String,
-    authentication: String,
-    confidentiality: String,
-    integrity: String,
-    availability: String,
+    pub name: String,
+    pub url: String,
+    pub cwe_id: Option<String>,
+    pub cwe_url: Option<String>,
+    pub vulnerability_type: String,
+    pub publish_date: String,
+    pub update_date: String,
+    pub score: f32,
+    pub access: String,
+    pub complexity: String,
+    pub authentication: String,
+    pub confidentiality: String,
+    pub integrity: String,
+    pub availability: String,
+    pub products: Vec<AffectedProduct>,
+    pub summary: Option<String>,
+    pub cvss2_vector: Option<String>,
+    pub cvss3_vector: Option<String>,
+    pub cvss3_score: Option<f32>,
+    pub configurations: Vec<CpeMatch>,
+    pub references: Vec<String>,
+    pub exploit_count: u32,
+    pub exploits: Vec<String>,
+}
+
+/// One `cpeMatch` entry of an NVD configuration, version bounds included.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CpeMatch {
+    pub criteria: String,
+    pub vulnerable: bool,
+    pub version_start_including: Option<String>,
+    pub version_start_excluding: Option<String>,
+    pub version_end_including: Option<String>,
+    pub version_end_excluding: Option<String>,
+}
+
+/// A product version a CVE applies to, `*` standing for every version.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AffectedProduct {
+    pub vendor: String,
+    pub product: String,
+    pub version: String,
+}
+
+impl Cve {
+    /// The CVSS v2 vector, rebuilt from the scraped columns when none was stored.
+    pub fn cvss2(&self) -> Option<CvssV2> {
+        match &self.cvss2_vector {
+            Some(vector) => vector.parse().ok(),
+            None => CvssV2::from_cvedetails(
+                &self.access,
+                &self.complexity,
+                &self.authentication,
+                &self.confidentiality,
+                &self.integrity,
+                &self.availability,
+            ),
+        }
+    }
+
+    pub fn cvss3(&self) -> Option<Cvss> {
+        self.cvss3_vector.as_ref().and_then(|vector| vector.parse().ok())
+    }
+
+    /// The v3 score when known, the v2 one otherwise.
+    pub fn best_score(&self) -> f32 {
+        self.cvss3_score.unwrap_or(self.score)
+    }
+
+    pub fn exploit_available(&self) -> bool {
+        self.exploit_count > 0 || !self.exploits.is_empty()
+    }
+
+    pub fn severity(&self) -> Severity {
+        match self.cvss3_score {
+            Some(score) => Severity::from_score(score as f64),
+            None => Severity::from_v2_score(self.score as f64),
+        }
+    }
+
+    /// One line with the name, the score, the kind of vulnerability and whether it is exploited.
+    pub fn summary(&self) -> String {
+        let mut line = format!(
+            "{} [{:.1} {}] {} {} CWE-{}",
+            self.name,
+            self.best_score(),
+            self.severity(),
+            self.vulnerability_type,
+            self.publish_date,
+            self.cwe_id.as_deref().unwrap_or("?"),
+        );
+        if self.exploit_available() {
+            line.push_str(" (exploit available)");
+        }
+        line
+    }
 }
 
 impl CveDetails {
-    pub fn new() -> Self {
-        let timeout = Duration::from_secs(6);
-        let client = Client::builder()
-            .timeout(timeout)
-            .build()
-            .expect("spiders/cvedetails: Building HTTP client");
+    pub fn new(settings: &HttpSettings) -> Self {
+        let session = Session::new(settings.client_builder())
+            .expect("spiders/cvedetails: Building HTTP client")
+            .with_headers(settings.headers.clone());
+
+        CveDetails {
+            session,
+            store: None,
+            min_score: None,
+            since: None,
+            base_url: BASE_URL.to_string(),
+            newest_update: Mutex::new(None),
+            incomplete: AtomicBool::new(false),
+        }
+    }
+
+    /// Persists every scraped CVE into `store` instead of only printing it.
+    pub fn with_store(mut self, store: Arc<CveStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Drops CVEs scoring below `min_score`.
+    pub fn with_min_score(mut self, min_score: f32) -> Self {
+        self.min_score = Some(min_score);
+        self
+    }
 
-        CveDetails { client }
+    pub fn with_cache(mut self, cache: HttpCache) -> Self {
+        self.session = self.session.with_cache(cache);
+        self
+    }
+
+    /// Only fetches CVEs updated on or after `since` (YYYY-MM-DD), paginating until older ones show up.
+    pub fn with_since(mut self, since: &str) -> Self {
+        self.since = Some(since.to_string());
+        self
+    }
+
+    /// Crawls a copy of the site served at `base_url` instead, e.g. a local mirror.
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
     }
 }
 
@@ -73,19 +262,20 @@ pub struct GitHubItem {
 }
 
 pub struct GitHubSpider {
-    client: Client,
+    session: Session,
     page_regex: Regex,
     expected_number_of_results: usize,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct QuotesItem {
     quote: String,
     author: String,
 }
 
 pub struct QuotesSpider {
-    webdriver_client: Mutex<HttpClient>,
+    session: Session,
+    fetcher: Fetcher,
 }
 
 #[async_trait]
@@ -97,61 +287,144 @@ impl Spider for CveDetails {
     }
 
     fn start_urls(&self) -> Vec<String> {
-        vec!["https://www.cvedetails.com/vulnerability-list/vulnerabilities.html".to_string()]
+        // Incremental runs stop at the first entry older than `since`, which needs the list by update date.
+        let list_url = format!("{}{}?vendor_id=0&product_id=0&page=1", self.base_url, LIST_PATH);
+        match self.since {
+            Some(_) => vec![format!("{}&order={}", list_url, ORDER_BY_UPDATE_DATE)],
+            None => vec![list_url],
+        }
     }
 
-    async fn scrapy(&self, url: String) -> Result<(Vec<Self::Item>, Vec<String>), Error> {
-        log::info!("visiting: {}", url);
+    async fn scrapy(&self, request: Request) -> Result<(Vec<Self::Item>, Vec<Request>), Error> {
+        log::info!("visiting: {}", request.url);
 
-        let http_res = self.client.get(url).send().await?.text().await?;
-        let mut items = Vec::new();
+        if request.handler == DETAIL_HANDLER {
+            let cve: Cve = request
+                .meta("cve")
+                .ok_or_else(|| Error::InvalidSpider("cvedetails: detail request without its cve".to_string()))?;
+            return Ok((vec![self.with_details(&request, cve).await], Vec::new()));
+        }
 
-        let document = Document::from(http_res.as_str());
+        let http_res = self.fetch_page(&request).await?.text();
+        let (cves, next_pages_links) = self.parse_list(&request.url, &http_res);
+
+        // Detail pages go first so that items come out before the next list page.
+        let mut requests = Vec::new();
+        for cve in cves {
+            requests.push(
+                request
+                    .follow(&cve.url)
+                    .with_handler(DETAIL_HANDLER)
+                    .with_priority(1)
+                    .with_meta("cve", &cve)?,
+            );
+        }
+        requests.extend(next_pages_links.iter().map(|link| request.follow(link)));
 
-        let rows = document.select(Attr("id", "vulnslisttable").descendant(Class("srrowns")));
-        for row in rows {
-            let mut columns = row.select(Name("td"));
-            let _ = columns.next();
-            let cve_link = columns.next().unwrap().select(Name("a")).next().unwrap();
-            let cve_name = cve_link.text().trim().to_string();
-            let cve_url = self.url_join(cve_link.attr("href").unwrap());
+        Ok((Vec::new(), requests))
+    }
 
-            let cwe = columns
-                .next()
-                .unwrap()
-                .select(Name("a"))
-                .next()
-                .map(|cwe_link| {
-                    (
-                        cwe_link.text().trim().to_string(),
-                        self.url_join(cwe_link.attr("href").unwrap()),
-                    )
-                });
+    async fn process(&self, item: Self::Item) -> Result<(), Error> {
+        match &self.store {
+            Some(store) => {
+                if let Err(err) = store.save(&item) {
+                    self.incomplete.store(true, Ordering::SeqCst);
+                    return Err(err);
+                }
+                let mut newest_update = self.newest_update.lock().unwrap();
+                if newest_update.as_deref().is_none_or(|newest| newest < item.update_date.as_str()) {
+                    *newest_update = Some(item.update_date.clone());
+                }
+                log::info!("saved: {}", item.name);
+            }
+            None => println!("{:?}", item),
+        }
 
-            let _ = columns.next();
+        Ok(())
+    }
 
-            let vulnerability_type = columns.next().unwrap().text().trim().to_string();
+    /// Advances the crawl state only now, so that an interrupted run is resumed from where the last complete one stopped.
+    async fn close(&self) -> Result<(), Error> {
+        if self.incomplete.load(Ordering::SeqCst) {
+            log::warn!("cvedetails: pages or CVEs were missed, keeping the crawl state for the next run to retry them");
+            return Ok(());
+        }
 
-            let publish_date = columns.next().unwrap().text().trim().to_string();
-            let update_date = columns.next().unwrap().text().trim().to_string();
+        let newest_update = self.newest_update.lock().unwrap().clone();
+        if let (Some(store), Some(newest_update)) = (&self.store, newest_update) {
+            store.record_update(&self.name(), &newest_update)?;
+        }
 
-            let score: f32 = columns
-                .next()
-                .unwrap()
-                .text()
-                .trim()
-                .to_string()
-                .parse()
-                .unwrap();
+        Ok(())
+    }
+}
+
+impl CveDetails {
+    /// Reads the rows of a vulnerability list page and the list pages to visit next.
+    fn parse_list(&self, url: &str, html: &str) -> (Vec<Cve>, Vec<String>) {
+        let mut items = Vec::new();
+        let document = Document::from(html);
+
+        let mut reached_older = false;
+        let rows = document.select(Attr("id", "vulnslisttable").descendant(Class("srrowns")));
+        for row in rows {
+            // #, CVE ID, CWE ID, # of Exploits, Vulnerability Type(s), Publish Date, Update Date, Score,
+            // Gained Access Level, Access, Complexity, Authentication, Conf., Integ., Avail.
+            let columns = row.select(Name("td")).collect::<Vec<_>>();
+            let cve_link = columns.get(1).and_then(|column| column.select(Name("a")).next());
+            let (cve_link, cve_href) = match cve_link.and_then(|link| Some((link, link.attr("href")?))) {
+                Some(cve_link) if columns.len() >= 15 => cve_link,
+                _ => {
+                    log::warn!("{}: skipping a malformed row of {} columns", url, columns.len());
+                    continue;
+                }
+            };
+            let text = |index: usize| columns[index].text().trim().to_string();
 
-            let _ = columns.next();
+            let cve_name = cve_link.text().trim().to_string();
+            let cve_url = self.url_join(cve_href);
 
-            let access = columns.next().unwrap().text().trim().to_string();
-            let complexity = columns.next().unwrap().text().trim().to_string();
-            let authentication = columns.next().unwrap().text().trim().to_string();
-            let confidentiality = columns.next().unwrap().text().trim().to_string();
-            let integrity = columns.next().unwrap().text().trim().to_string();
-            let availability = columns.next().unwrap().text().trim().to_string();
+            let cwe = columns[2]
+                .select(Name("a"))
+                .find_map(|cwe_link| Some((cwe_link.text().trim().to_string(), self.url_join(cwe_link.attr("href")?))));
+
+            let exploit_count = text(3).parse().unwrap_or(0);
+            let vulnerability_type = text(4);
+            let publish_date = text(5);
+            let update_date = text(6);
+            let score = text(7).parse::<f32>().ok();
+
+            if let Some(since) = &self.since {
+                if update_date.as_str() < since.as_str() {
+                    reached_older = true;
+                    continue;
+                }
+            }
+
+            let access = text(9);
+            let complexity = text(10);
+            let authentication = text(11);
+            let confidentiality = text(12);
+            let integrity = text(13);
+            let availability = text(14);
+
+            let cvss2 = CvssV2::from_cvedetails(
+                &access,
+                &complexity,
+                &authentication,
+                &confidentiality,
+                &integrity,
+                &availability,
+            );
+            let score = match (score, &cvss2) {
+                (Some(score), _) => score,
+                (None, Some(cvss2)) => cvss2.base_score() as f32,
+                (None, None) => 0.0,
+            };
+
+            if self.min_score.is_some_and(|min_score| score < min_score) {
+                continue;
+            }
 
             let cve = Cve {
                 name: cve_name,
@@ -168,67 +441,176 @@ impl Spider for CveDetails {
                 confidentiality,
                 integrity,
                 availability,
+                cvss2_vector: cvss2.map(|cvss2| cvss2.to_string()),
+                exploit_count,
+                ..Cve::default()
             };
             items.push(cve);
         }
 
-        let next_pages_links = document
+        let mut next_pages_links = document
             .select(Attr("id", "pagingb").descendant(Name("a")))
             .filter_map(|n| n.attr("href"))
             .map(|url| self.url_join(url))
             .collect::<Vec<String>>();
 
-        Ok((items, next_pages_links))
+        // Incremental runs walk the newest-first list one page at a time to stop at the first older entry.
+        if self.since.is_some() {
+            let next_page = page_number(url) + 1;
+            next_pages_links.retain(|link| !reached_older && page_number(link) == next_page);
+        }
+
+        (items, next_pages_links)
     }
 
-    async fn process(&self, item: Self::Item) -> Result<(), Error> {
-        println!("{:?}", item);
+    /// Fetches `request`, an error status counting as a failure, and marks the run incomplete if it fails.
+    async fn fetch_page(&self, request: &Request) -> Result<Response, Error> {
+        let response = self.session.fetch(request).await.and_then(|response| {
+            if response.status.is_success() {
+                Ok(response)
+            } else {
+                Err(Error::InvalidHttpResponse(format!("{} [{}]", request.url, response.status)))
+            }
+        });
+        if response.is_err() {
+            self.incomplete.store(true, Ordering::SeqCst);
+        }
 
-        Ok(())
+        response
+    }
+
+    /// Fetches the detail page of `cve` and merges it in, keeping the list row alone on failure.
+    async fn with_details(&self, request: &Request, mut cve: Cve) -> Cve {
+        match self.fetch_page(request).await {
+            Ok(res) => self.parse_details(&Document::from(res.text().as_str()), &mut cve),
+            Err(err) => log::warn!("{}: fetching details: {}", cve.name, err),
+        }
+
+        cve
+    }
+
+    fn parse_details(&self, document: &Document, cve: &mut Cve) {
+        if let Some(summary) = document.select(Class("cvedetailssummary")).next() {
+            let text = summary.text();
+            let description = text.split("Publish Date").next().unwrap_or(&text).trim();
+            if !description.is_empty() {
+                cve.summary = Some(description.to_string());
+            }
+        }
+
+        // #, Product Type, Vendor, Product, Version, Update, Edition, Language, details link
+        for row in document.select(Attr("id", "vulnprodstable").descendant(Name("tr"))) {
+            let cells = row.select(Name("td")).map(|cell| cell.text().trim().to_string()).collect::<Vec<String>>();
+            if cells.len() < 5 {
+                continue;
+            }
+
+            let name = |value: &str| value.to_lowercase().replace(' ', "_");
+            let product = AffectedProduct {
+                vendor: name(&cells[2]),
+                product: name(&cells[3]),
+                version: match cells[4].as_str() {
+                    "" | "-" => "*".to_string(),
+                    version => version.to_string(),
+                },
+            };
+
+            let known = cve.products.iter().any(|known| {
+                known.vendor == product.vendor && known.product == product.product && known.version == product.version
+            });
+            if !known {
+                cve.products.push(product);
+            }
+        }
+
+        for link in document.select(Attr("id", "vulnrefstable").descendant(Name("a"))) {
+            if let Some(href) = link.attr("href") {
+                let href = self.url_join(href);
+                if !cve.references.contains(&href) {
+                    cve.references.push(href);
+                }
+            }
+        }
+
+        let exploit_links = document
+            .select(Attr("id", "metasploitmodstable").descendant(Name("a")))
+            .filter_map(|link| link.attr("href"))
+            .map(|href| self.url_join(href))
+            .chain(cve.references.iter().filter(|href| is_exploit_reference(href)).cloned())
+            .collect::<Vec<String>>();
+        for href in exploit_links {
+            if !cve.exploits.contains(&href) {
+                cve.exploits.push(href);
+            }
+        }
     }
-}
 
-impl CveDetails {
     fn url_join(&self, url: &str) -> String {
         let url = url.trim();
 
         if url.starts_with("//www.cvedetails.com") {
-            return format!("https:{}", url);
-        } else if url.starts_with("/") {
-            return format!("https://www.cvedetails.com{}", url);
+            format!("https:{}", url)
+        } else if url.starts_with('/') {
+            format!("{}{}", self.base_url, url)
+        } else {
+            url.to_string()
         }
-
-        return url.to_string();
     }
 }
 
+/// Where cvedetails is served, and its vulnerability list with the `order` value for the most recently updated first.
+const BASE_URL: &str = "https://www.cvedetails.com";
+const LIST_PATH: &str = "/vulnerability-list.php";
+const ORDER_BY_UPDATE_DATE: u8 = 2;
+
+/// Handler of the cvedetails requests for a CVE page, carrying the list row as `cve` meta.
+const DETAIL_HANDLER: &str = "detail";
+
+/// Hosts publishing exploit code rather than advisories.
+const EXPLOIT_HOSTS: &[&str] = &["exploit-db.com", "packetstormsecurity.com", "rapid7.com/db/modules"];
+
+fn is_exploit_reference(url: &str) -> bool {
+    EXPLOIT_HOSTS.iter().any(|host| url.contains(host))
+}
+
+/// The `page` query parameter of a cvedetails list URL, the first page having none.
+fn page_number(url: &str) -> usize {
+    reqwest::Url::parse(url)
+        .ok()
+        .and_then(|url| {
+            url.query_pairs()
+                .find(|(name, _)| name == "page")
+                .and_then(|(_, page)| page.parse().ok())
+        })
+        .unwrap_or(1)
+}
+
 impl GitHubSpider {
-    pub fn new() -> Self {
-        let timeout = Duration::from_secs(6);
+    pub fn new(settings: &HttpSettings) -> Self {
         let mut headers = header::HeaderMap::new();
         headers.insert(
             "Accept",
             header::HeaderValue::from_static("application/vnd.github.v3+json"),
         );
 
-        let client = Client::builder()
-            .timeout(timeout)
-            .default_headers(headers)
-            .user_agent(
-                "Mozilla/5.0 (Windows NT 6.1; Win64; x64; rv:47.0) Gecko/20100101 Firefox/47.0",
-            )
-            .build()
-            .expect("spiders/github: Building HTTP client");
+        // The API answers the same whoever asks, the headers of the first profile are enough.
+        let client = settings.client_builder().default_headers(headers);
+        let session = Session::new(client).expect("spiders/github: Building HTTP client");
 
         let page_regex =
             Regex::new(".*page=([0-9]*).*").expect("spiders/github: Compiling page regex");
 
         GitHubSpider {
-            client,
+            session,
             page_regex,
             expected_number_of_results: 100,
         }
     }
+
+    pub fn with_cache(mut self, cache: HttpCache) -> Self {
+        self.session = self.session.with_cache(cache);
+        self
+    }
 }
 
 
@@ -244,11 +626,12 @@ impl Spider for GitHubSpider {
         vec!["https://api.github.com/orgs/google/public_members?per_page=100&page=1".to_string()]
     }
 
-    async fn scrapy(&self, url: String) -> Result<(Vec<GitHubItem>, Vec<String>), Error> {
-        let items: Vec<GitHubItem> = self.client.get(&url).send().await?.json().await?;
+    async fn scrapy(&self, request: Request) -> Result<(Vec<GitHubItem>, Vec<Request>), Error> {
+        let url = &request.url;
+        let items: Vec<GitHubItem> = self.session.fetch(&request).await?.json()?;
 
         let next_pages_links = if items.len() == self.expected_number_of_results {
-            let captures = self.page_regex.captures(&url).unwrap();
+            let captures = self.page_regex.captures(url).unwrap();
             let old_page_number = captures.get(1).unwrap().as_str().to_string();
             let mut new_page_number = old_page_number
                 .parse::<usize>()
@@ -259,7 +642,7 @@ impl Spider for GitHubSpider {
                 format!("&page={}", old_page_number).as_str(),
                 format!("&page={}", new_page_number).as_str(),
             );
-            vec![next_url]
+            vec![request.follow(&next_url)]
         } else {
             Vec::new()
         };
@@ -275,21 +658,44 @@ impl Spider for GitHubSpider {
 }
 
 impl QuotesSpider {
-    pub async fn new() -> Result<Self, Error> {
-        let mut caps = serde_json::map::Map::new();
-        let chrome_opts = serde_json::json!({ "args": ["--headless", "--disable-gpu"] });
-        caps.insert("goog:chromeOptions".to_string(), chrome_opts);
-        let webdriver_client = ClientBuilder::rustls()
-            .capabilities(caps)
-            .connect("http://localhost:4444")
-            .await?;
-
-        Ok(QuotesSpider {
-            webdriver_client: Mutex::new(webdriver_client),
-        })
+    /// Renders the pages with `webdriver` when their quotes are not in the static HTML.
+    pub fn new(settings: &HttpSettings, webdriver: Arc<WebDriverPool>) -> Self {
+        let session = Session::new(settings.client_builder())
+            .expect("spiders/quotes: Building HTTP client")
+            .with_headers(settings.headers.clone());
+        let fetcher = Fetcher::new(FetchMode::Auto)
+            .with_webdriver(webdriver)
+            .with_expected(&[".quote".to_string()])
+            .expect("spiders/quotes: Parsing quote selector")
+            .with_actions(vec![Action::WaitFor { selector: ".quote".to_string(), timeout_ms: 10_000 }]);
+
+        QuotesSpider { session, fetcher }
     }
-}
 
+    pub fn with_fetch_mode(mut self, mode: FetchMode) -> Self {
+        self.fetcher = self.fetcher.with_mode(mode);
+        self
+    }
+
+    /// Renders the pages whose static HTML lacks one of `selectors` in auto mode, instead of those without quotes.
+    pub fn with_expected(mut self, selectors: &[String]) -> Result<Self, Error> {
+        self.fetcher = self.fetcher.with_expected(selectors)?;
+        Ok(self)
+    }
+
+    /// Runs `actions` on every rendered page once loaded, instead of waiting for the quotes to show up.
+    pub fn with_actions(mut self, actions: Vec<Action>) -> Self {
+        self.fetcher = self.fetcher.with_actions(actions);
+        self
+    }
+
+    /// Serves the pages from `cache`, and records the new ones there.
+    pub fn with_cache(mut self, cache: HttpCache) -> Self {
+        self.fetcher = self.fetcher.with_cache(&cache);
+        self.session = self.session.with_cache(cache);
+        self
+    }
+}
 
 #[async_trait]
 impl Spider for QuotesSpider {
@@ -303,13 +709,13 @@ impl Spider for QuotesSpider {
         vec!["https://quotes.toscrape.com/js".to_string()]
     }
 
-    async fn scrapy(&self, url: String) -> Result<(Vec<Self::Item>, Vec<String>), Error> {
+    async fn open(&self) -> Result<(), Error> {
+        self.fetcher.open().await
+    }
+
+    async fn scrapy(&self, request: Request) -> Result<(Vec<Self::Item>, Vec<Request>), Error> {
         let mut items = Vec::new();
-        let html = {
-            let webdriver = self.webdriver_client.lock().await;
-            webdriver.goto(&url).await?;
-            webdriver.source().await?
-        };
+        let html = self.fetcher.fetch(&self.session, &request).await?.text();
 
         let document = Document::from(html.as_str());
 
@@ -342,8 +748,8 @@ impl Spider for QuotesSpider {
                     .descendant(Name("a")),
             )
             .filter_map(|n| n.attr("href"))
-            .map(|url| self.url_join(url))
-            .collect::<Vec<String>>();
+            .map(|url| request.follow(&self.url_join(url)))
+            .collect::<Vec<Request>>();
 
         Ok((items, next_pages_link))
     }
@@ -359,14 +765,54 @@ impl QuotesSpider {
     fn url_join(&self, url: &str) -> String {
         let url = url.trim();
 
-        if url.starts_with("/") {
-            return format!("https://quotes.toscrape.com{}", url);
+        if url.starts_with('/') {
+            format!("https://quotes.toscrape.com{}", url)
+        } else {
+            url.to_string()
         }
+    }
+}
+
+/// A request waiting for a scraper, ordered by priority then by arrival.
+struct Queued {
+    sequence: u64,
+    request: Request,
+}
+
+impl PartialEq for Queued {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == PriorityOrdering::Equal
+    }
+}
+
+impl Eq for Queued {}
+
+impl PartialOrd for Queued {
+    fn partial_cmp(&self, other: &Self) -> Option<PriorityOrdering> {
+        Some(self.cmp(other))
+    }
+}
 
-        return url.to_string();
+impl Ord for Queued {
+    fn cmp(&self, other: &Self) -> PriorityOrdering {
+        self.request
+            .priority
+            .cmp(&other.request.priority)
+            .then_with(|| other.sequence.cmp(&self.sequence))
     }
 }
 
+/// What happens during a crawl, in the order it happens.
+#[derive(Debug)]
+pub enum CrawlEvent<T> {
+    /// `url` was scraped by `handler` into `items` items and `requests` follow-up requests.
+    Scraped { url: String, handler: String, items: usize, requests: usize },
+    /// `url` could not be fetched or scraped.
+    Failed { url: String, handler: String, error: Error },
+    Item(T),
+}
+
+/// Runs spiders, fetching their requests concurrently and processing their items as they come.
 pub struct Crawler {
     delay: Duration,
     concurrency_count: usize,
@@ -374,6 +820,8 @@ pub struct Crawler {
 }
 
 impl Crawler {
+    /// `concurrency_count` requests at once, each scraper waiting `delay` between two of them,
+    /// and `processing_count` items processed at once.
     pub fn new(
         delay: Duration,
         concurrency_count: usize,
@@ -386,134 +834,213 @@ impl Crawler {
         }
     }
 
-    pub async fn run<T: Send + 'static>(&self, spider: Arc<dyn Spider<Item = T>>) {
-        let mut visited_urls = HashSet::<String>::new();
+    /// Crawls with `spider` until no request is left, failing only if it cannot open.
+    pub async fn run<T: Send + 'static>(&self, spider: Arc<dyn Spider<Item = T>>) -> Result<(), Error> {
+        self.stream(spider.clone())
+            .await?
+            .for_each_concurrent(self.processing_count, |event| async {
+                if let CrawlEvent::Item(item) = event {
+                    if let Err(err) = spider.process(item).await {
+                        log::error!("{}: processing: {}", spider.name(), err);
+                    }
+                }
+            })
+            .await;
+
+        spider.close().await
+    }
+
+    /// Opens `spider` and crawls in the background, reporting what happens as it does.
+    ///
+    /// The items are the caller's to handle, `Spider::process` is not called. Dropping the stream
+    /// stops the crawl once the requests in flight are done. Fails without a single request in flight.
+    pub async fn stream<T: Send + 'static>(
+        &self,
+        spider: Arc<dyn Spider<Item = T>>,
+    ) -> Result<impl Stream<Item = CrawlEvent<T>> + Unpin, Error> {
+        let mut seen_requests = HashSet::<String>::new();
+        let mut pending = BinaryHeap::<Queued>::new();
+        let mut sequence = 0;
         let concurrency_count = self.concurrency_count;
-        let concurrency_queue_capacity = concurrency_count * 400;
-        let processing_count = self.processing_count;
-        let processing_queue_capacity = processing_count * 10;
+        if concurrency_count == 0 {
+            return Err(Error::Config("crawler: at least one request must be in flight".to_string()));
+        }
+        // Kept small so that the backlog waits in `pending`, where priorities apply.
+        let concurrency_queue_capacity = concurrency_count;
+        let new_requests_capacity = concurrency_count * 400;
+        let events_capacity = self.processing_count.max(1) * 10;
         let active_spiders = Arc::new(AtomicUsize::new(0));
 
-        let (urls_to_visit_tx, urls_to_visit_rx) = mpsc::channel(concurrency_queue_capacity);
-        let (items_tx, items_rx) = mpsc::channel(processing_queue_capacity);
-        let (new_urls_tx, mut new_urls_rx) = mpsc::channel(concurrency_queue_capacity);
-        let barrier = Arc::new(Barrier::new(3));
+        log::info!("crawler: running spider {}", spider.name());
 
-        for url in spider.start_urls() {
-            visited_urls.insert(url.clone());
-            let _ = urls_to_visit_tx.send(url).await;
-        }
+        spider.open().await?;
 
-        self.processors(
-            processing_count,
-            spider.clone(),
-            items_rx,
-            barrier.clone(),
-        );
+        let (requests_to_visit_tx, requests_to_visit_rx) = mpsc::channel(concurrency_queue_capacity);
+        let (events_tx, events_rx) = mpsc::channel(events_capacity);
+        let (new_requests_tx, mut new_requests_rx) = mpsc::channel(new_requests_capacity);
+
+        for request in spider.start_requests() {
+            if seen_requests.insert(request.fingerprint()) {
+                sequence += 1;
+                pending.push(Queued { sequence, request });
+            }
+        }
 
         self.scrapers(
             concurrency_count,
-            spider.clone(),
-            urls_to_visit_rx,
-            new_urls_tx.clone(),
-            items_tx,
+            spider,
+            requests_to_visit_rx,
+            new_requests_tx.clone(),
+            events_tx.clone(),
             active_spiders.clone(),
             self.delay,
-            barrier.clone(),
         );
 
-        loop {
-            if let Some((visited_url, new_urls)) = new_urls_rx.try_recv().ok() {
-                visited_urls.insert(visited_url);
+        tokio::spawn(async move {
+            loop {
+                if events_tx.is_closed() {
+                    log::info!("crawler: stream dropped, stopping");
+                    break;
+                }
 
-                for url in new_urls {
-                    if !visited_urls.contains(&url) {
-                        visited_urls.insert(url.clone());
-                        log::debug!("queueing: {}", url);
-                        let _ = urls_to_visit_tx.send(url).await;
+                if let Ok(new_requests) = new_requests_rx.try_recv() {
+                    for request in new_requests {
+                        if seen_requests.insert(request.fingerprint()) {
+                            log::debug!("queueing: {} {}", request.handler, request.url);
+                            sequence += 1;
+                            pending.push(Queued { sequence, request });
+                        }
                     }
                 }
-            }
 
-            if new_urls_tx.capacity() == concurrency_queue_capacity
-            && urls_to_visit_tx.capacity() == concurrency_queue_capacity
-            && active_spiders.load(Ordering::SeqCst) == 0
-            {
-                break;
-            }
-
-            sleep(Duration::from_millis(5)).await;
-        }
-
-        log::info!("crawler: control loop exited");
-
-        drop(urls_to_visit_tx);
+                while !pending.is_empty() {
+                    match requests_to_visit_tx.try_reserve() {
+                        Ok(permit) => permit.send(pending.pop().unwrap().request),
+                        Err(_) => break,
+                    }
+                }
 
-        barrier.wait().await;
-    }
+                if pending.is_empty()
+                && new_requests_tx.capacity() == new_requests_capacity
+                && requests_to_visit_tx.capacity() == concurrency_queue_capacity
+                && active_spiders.load(Ordering::SeqCst) == 0
+                {
+                    break;
+                }
 
-    fn processors<T: Send + 'static>(
-        &self,
-        concurrency: usize,
-        spider: Arc<dyn Spider<Item = T>>,
-        items: mpsc::Receiver<T>,
-        barrier: Arc<Barrier>,
-    ) {
-        tokio::spawn(async move {
-            tokio_stream::wrappers::ReceiverStream::new(items)
-                .for_each_concurrent(concurrency, |item| async {
-                    let _ = spider.process(item).await;
-                })
-                .await;
+                sleep(Duration::from_millis(5)).await;
+            }
 
-            barrier.wait().await;
+            log::info!("crawler: control loop exited");
         });
+
+        Ok(tokio_stream::wrappers::ReceiverStream::new(events_rx))
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn scrapers<T: Send + 'static>(
         &self,
         concurrency: usize,
         spider: Arc<dyn Spider<Item = T>>,
-        urls_to_vist: mpsc::Receiver<String>,
-        new_urls: mpsc::Sender<(String, Vec<String>)>,
-        items_tx: mpsc::Sender<T>,
+        requests_to_visit: mpsc::Receiver<Request>,
+        new_requests: mpsc::Sender<Vec<Request>>,
+        events: mpsc::Sender<CrawlEvent<T>>,
         active_spiders: Arc<AtomicUsize>,
         delay: Duration,
-        barrier: Arc<Barrier>,
     ) {
         tokio::spawn(async move {
-            tokio_stream::wrappers::ReceiverStream::new(urls_to_vist)
-                .for_each_concurrent(concurrency, |queued_url| {
-                    let queued_url = queued_url.clone();
-                    async {
+            tokio_stream::wrappers::ReceiverStream::new(requests_to_visit)
+                .for_each_concurrent(concurrency, |request| {
+                    let (spider, events, new_requests, active_spiders) =
+                        (&spider, &events, &new_requests, &active_spiders);
+                    async move {
                         active_spiders.fetch_add(1, Ordering::SeqCst);
-                        let mut urls = Vec::new();
-                        let res = spider
-                            .scrapy(queued_url.clone())
-                            .await
-                            .map_err(|err| {
-                                log::error!("{}", err);
-                                err
-                            })
-                            .ok();
-
-                        if let Some((items, new_urls)) = res {
-                            for item in items {
-                                let _ = items_tx.send(item).await;
+                        let url = request.url.clone();
+                        let handler = request.handler.clone();
+                        let mut requests = Vec::new();
+
+                        match spider.scrapy(request).await {
+                            Ok((items, found)) => {
+                                let event = CrawlEvent::Scraped { url, handler, items: items.len(), requests: found.len() };
+                                let _ = events.send(event).await;
+                                for item in items {
+                                    let _ = events.send(CrawlEvent::Item(item)).await;
+                                }
+                                requests = found;
+                            }
+                            Err(error) => {
+                                log::error!("{}", error);
+                                let _ = events.send(CrawlEvent::Failed { url, handler, error }).await;
                             }
-                            urls = new_urls;
                         }
 
-                        let _ = new_urls.send((queued_url, urls)).await;
+                        let _ = new_requests.send(requests).await;
                         sleep(delay).await;
                         active_spiders.fetch_sub(1, Ordering::SeqCst);
                     }
                 })
                 .await;
-
-            drop(items_tx);
-            barrier.wait().await;
         });
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cve(name: &str, update_date: &str) -> Cve {
+        Cve { name: name.to_string(), update_date: update_date.to_string(), ..Cve::default() }
+    }
+
+    #[test]
+    fn incremental_runs_list_by_update_date() {
+        let spider = CveDetails::new(&HttpSettings::default());
+        assert_eq!(spider.start_urls(), vec!["https://www.cvedetails.com/vulnerability-list.php?vendor_id=0&product_id=0&page=1"]);
+
+        let spider = spider.with_since("2021-06-01");
+        assert_eq!(
+            spider.start_urls(),
+            vec!["https://www.cvedetails.com/vulnerability-list.php?vendor_id=0&product_id=0&page=1&order=2"]
+        );
+    }
+
+    #[test]
+    fn malformed_list_rows_are_skipped() {
+        let row = |cells: &str| format!("<tr class=\"srrowns\">{}</tr>", cells);
+        let html = format!(
+            "<table id=\"vulnslisttable\">{}{}{}</table>",
+            row("<td>1</td><td>CVE-2021-0001</td>"),
+            row("<td>2</td><td><a href=\"/cve/CVE-2021-0002/\">CVE-2021-0002</a></td><td></td>"),
+            row(
+                "<td>3</td><td><a href=\"/cve/CVE-2021-0003/\">CVE-2021-0003</a></td><td></td><td>0</td>\
+                 <td>DoS</td><td>2021-06-01</td><td>2021-06-02</td><td>5.0</td><td>None</td>\
+                 <td>Network</td><td>Low</td><td>Not required</td><td>None</td><td>None</td><td>Partial</td>"
+            ),
+        );
+
+        let spider = CveDetails::new(&HttpSettings::default());
+        let (cves, _) = spider.parse_list("https://www.cvedetails.com/vulnerability-list.php", &html);
+        assert_eq!(cves.len(), 1);
+        assert_eq!(cves[0].name, "CVE-2021-0003");
+        assert_eq!(cves[0].url, "https://www.cvedetails.com/cve/CVE-2021-0003/");
+        assert_eq!(cves[0].cwe_id, None);
+        assert_eq!(cves[0].availability, "Partial");
+    }
+
+    #[tokio::test]
+    async fn crawl_state_advances_when_the_crawl_closes() {
+        let path = std::env::temp_dir().join(format!("vxsuite-crawl-state-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let store = Arc::new(CveStore::open(&path).unwrap());
+        let spider = CveDetails::new(&HttpSettings::default()).with_store(store.clone());
+
+        spider.process(cve("CVE-2021-0002", "2021-06-03")).await.unwrap();
+        spider.process(cve("CVE-2021-0001", "2021-06-01")).await.unwrap();
+        assert_eq!(store.last_update("cvedetails").unwrap(), None);
+
+        spider.close().await.unwrap();
+        assert_eq!(store.last_update("cvedetails").unwrap().as_deref(), Some("2021-06-03"));
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,622 @@
+use crate::module::cpe::Cpe;
+use crate::module::crawler::{ AffectedProduct, CpeMatch, Cve };
+use crate::module::error::Error;
+use crate::module::scanner::Service;
+use rusqlite::{ params, Connection, OptionalExtension, Row, ToSql, Transaction };
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Schema changes applied in order, `PRAGMA user_version` records how many already ran.
+const MIGRATIONS: &[&str] = &["
+    CREATE TABLE IF NOT EXISTS cves (
+        name TEXT PRIMARY KEY,
+        url TEXT NOT NULL,
+        cwe_id TEXT,
+        cwe_url TEXT,
+        vulnerability_type TEXT NOT NULL,
+        publish_date TEXT NOT NULL,
+        update_date TEXT NOT NULL,
+        score REAL NOT NULL,
+        access TEXT NOT NULL,
+        complexity TEXT NOT NULL,
+        authentication TEXT NOT NULL,
+        confidentiality TEXT NOT NULL,
+        integrity TEXT NOT NULL,
+        availability TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS cve_products (
+        cve TEXT NOT NULL REFERENCES cves(name) ON DELETE CASCADE,
+        vendor TEXT NOT NULL,
+        product TEXT NOT NULL,
+        version TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_cves_cwe ON cves(cwe_id);
+    CREATE INDEX IF NOT EXISTS idx_cves_score ON cves(score);
+    CREATE INDEX IF NOT EXISTS idx_cve_products_cve ON cve_products(cve);
+    CREATE INDEX IF NOT EXISTS idx_cve_products_product ON cve_products(product, version);
+    CREATE INDEX IF NOT EXISTS idx_cve_products_vendor ON cve_products(vendor);
+", "
+    ALTER TABLE cves ADD COLUMN summary TEXT;
+    ALTER TABLE cves ADD COLUMN cvss3_vector TEXT;
+    ALTER TABLE cves ADD COLUMN cvss3_score REAL;
+    CREATE TABLE cve_references (
+        cve TEXT NOT NULL REFERENCES cves(name) ON DELETE CASCADE,
+        url TEXT NOT NULL
+    );
+    CREATE TABLE cve_cpes (
+        cve TEXT NOT NULL REFERENCES cves(name) ON DELETE CASCADE,
+        criteria TEXT NOT NULL,
+        vulnerable INTEGER NOT NULL,
+        version_start_including TEXT,
+        version_start_excluding TEXT,
+        version_end_including TEXT,
+        version_end_excluding TEXT
+    );
+    CREATE INDEX idx_cve_references_cve ON cve_references(cve);
+    CREATE INDEX idx_cve_cpes_cve ON cve_cpes(cve);
+", "
+    ALTER TABLE cves ADD COLUMN cvss2_vector TEXT;
+", "
+    CREATE TABLE crawl_state (
+        spider TEXT PRIMARY KEY,
+        last_update TEXT NOT NULL
+    );
+", "
+    ALTER TABLE cves ADD COLUMN exploit_count INTEGER NOT NULL DEFAULT 0;
+    CREATE TABLE cve_exploits (
+        cve TEXT NOT NULL REFERENCES cves(name) ON DELETE CASCADE,
+        url TEXT NOT NULL
+    );
+    CREATE INDEX idx_cve_exploits_cve ON cve_exploits(cve);
+"];
+
+/// Filters of a CVE search; unset fields match everything.
+#[derive(Debug, Clone, Default)]
+pub struct CveQuery {
+    pub keyword: Option<String>,
+    pub vendor: Option<String>,
+    pub product: Option<String>,
+    pub version: Option<String>,
+    pub cwe_id: Option<String>,
+    pub min_score: Option<f32>,
+    pub limit: Option<usize>,
+}
+
+/// SQLite backed store of scraped CVEs.
+pub struct CveStore {
+    conn: Mutex<Connection>,
+}
+
+impl CveStore {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let mut conn = Connection::open(path)?;
+        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+        migrate(&mut conn)?;
+
+        Ok(CveStore {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn connection(&self) -> Result<std::sync::MutexGuard<'_, Connection>, Error> {
+        self.conn
+            .lock()
+            .map_err(|_| Error::Database("cve store lock poisoned".to_string()))
+    }
+
+    /// Inserts or replaces `cve`; its affected products, references and configurations
+    /// replace the stored ones unless empty.
+    pub fn save(&self, cve: &Cve) -> Result<(), Error> {
+        self.save_all(std::slice::from_ref(cve))
+    }
+
+    /// Saves `cves` in a single transaction, much faster for bulk imports.
+    pub fn save_all(&self, cves: &[Cve]) -> Result<(), Error> {
+        let mut conn = self.connection()?;
+        let tx = conn.transaction()?;
+
+        for cve in cves {
+            save_cve(&tx, cve)?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Result<Option<Cve>, Error> {
+        let conn = self.connection()?;
+        let cve = conn
+            .query_row("SELECT * FROM cves WHERE name = ?1", params![name], cve_from_row)
+            .optional()?;
+
+        match cve {
+            Some(mut cve) => {
+                load_details(&conn, &mut cve)?;
+                Ok(Some(cve))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// CVEs matching `query`, highest score first (v3 when known, v2 otherwise).
+    pub fn search(&self, query: &CveQuery) -> Result<Vec<Cve>, Error> {
+        let mut clauses = Vec::new();
+        let mut values: Vec<Box<dyn ToSql>> = Vec::new();
+
+        if let Some(keyword) = &query.keyword {
+            clauses.push("(c.name LIKE ? OR c.vulnerability_type LIKE ? OR c.summary LIKE ?)");
+            for _ in 0..3 {
+                values.push(Box::new(format!("%{}%", keyword)));
+            }
+        }
+
+        if let Some(vendor) = &query.vendor {
+            clauses.push("p.vendor = ?");
+            values.push(Box::new(vendor.to_lowercase()));
+        }
+
+        if let Some(product) = &query.product {
+            clauses.push("p.product = ?");
+            values.push(Box::new(product.to_lowercase()));
+        }
+
+        if let Some(version) = &query.version {
+            clauses.push("(p.version = ? OR p.version = '*' OR p.version = '')");
+            values.push(Box::new(version.clone()));
+        }
+
+        if let Some(cwe_id) = &query.cwe_id {
+            clauses.push("c.cwe_id = ?");
+            values.push(Box::new(cwe_id.trim_start_matches("CWE-").to_string()));
+        }
+
+        if let Some(min_score) = query.min_score {
+            clauses.push("COALESCE(c.cvss3_score, c.score) >= ?");
+            values.push(Box::new(min_score));
+        }
+
+        let mut sql = String::from("SELECT DISTINCT c.* FROM cves c LEFT JOIN cve_products p ON p.cve = c.name");
+        if !clauses.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&clauses.join(" AND "));
+        }
+        sql.push_str(" ORDER BY COALESCE(c.cvss3_score, c.score) DESC, c.name DESC");
+        if let Some(limit) = query.limit {
+            sql.push_str(&format!(" LIMIT {}", limit));
+        }
+
+        let conn = self.connection()?;
+        let mut statement = conn.prepare(&sql)?;
+        let params = values.iter().map(|value| value.as_ref()).collect::<Vec<&dyn ToSql>>();
+        let mut cves = statement
+            .query_map(params.as_slice(), cve_from_row)?
+            .collect::<Result<Vec<Cve>, rusqlite::Error>>()?;
+
+        for cve in cves.iter_mut() {
+            load_details(&conn, cve)?;
+        }
+
+        Ok(cves)
+    }
+
+    /// Newest `update_date` a spider has saved, where its next incremental run resumes.
+    pub fn last_update(&self, spider: &str) -> Result<Option<String>, Error> {
+        let conn = self.connection()?;
+        let last_update = conn
+            .query_row("SELECT last_update FROM crawl_state WHERE spider = ?1", params![spider], |row| row.get(0))
+            .optional()?;
+
+        Ok(last_update)
+    }
+
+    pub fn record_update(&self, spider: &str, update_date: &str) -> Result<(), Error> {
+        let conn = self.connection()?;
+        conn.execute(
+            "INSERT INTO crawl_state (spider, last_update) VALUES (?1, ?2)
+             ON CONFLICT(spider) DO UPDATE SET last_update = MAX(last_update, excluded.last_update)",
+            params![spider, update_date],
+        )?;
+
+        Ok(())
+    }
+
+    /// CVEs affecting the product and version detected on a scanned port.
+    pub fn lookup_service(&self, service: &Service) -> Result<Vec<Cve>, Error> {
+        let product = match &service.product {
+            Some(product) => product.clone(),
+            None => return Ok(Vec::new()),
+        };
+
+        let cpe = match Cpe::from_service(service) {
+            Some(cpe) => cpe,
+            None => {
+                return self.search(&CveQuery {
+                    vendor: service.vendor.clone(),
+                    product: Some(product),
+                    version: service.version.clone(),
+                    ..CveQuery::default()
+                })
+            }
+        };
+
+        // Every version is fetched, NVD configurations then decide with their version ranges.
+        let mut cves = self.search(&CveQuery {
+            vendor: service.vendor.clone(),
+            product: Some(product.clone()),
+            ..CveQuery::default()
+        })?;
+        cves.retain(|cve| {
+            if cve.configurations.is_empty() {
+                cve.products.iter().any(|affected| {
+                    affected.product.eq_ignore_ascii_case(&product)
+                        && service.vendor.as_ref().is_none_or(|vendor| affected.vendor.eq_ignore_ascii_case(vendor))
+                        && (affected.version == "*" || Some(&affected.version) == service.version.as_ref())
+                })
+            } else {
+                cve.configurations.iter().any(|criteria| criteria.vulnerable && criteria.matches(&cpe))
+            }
+        });
+
+        Ok(cves)
+    }
+}
+
+fn migrate(conn: &mut Connection) -> Result<(), Error> {
+    let applied: usize = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+
+    for (version, migration) in MIGRATIONS.iter().enumerate().skip(applied) {
+        let tx = conn.transaction()?;
+        tx.execute_batch(migration)?;
+        tx.execute_batch(&format!("PRAGMA user_version = {}", version + 1))?;
+        tx.commit()?;
+    }
+
+    Ok(())
+}
+
+fn save_cve(tx: &Transaction, cve: &Cve) -> Result<(), Error> {
+    tx.execute(
+        "INSERT INTO cves (name, url, cwe_id, cwe_url, vulnerability_type, publish_date, update_date,
+            score, access, complexity, authentication, confidentiality, integrity, availability,
+            summary, cvss3_vector, cvss3_score, cvss2_vector, exploit_count)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19)
+         ON CONFLICT(name) DO UPDATE SET
+            url = excluded.url, cwe_id = excluded.cwe_id, cwe_url = excluded.cwe_url,
+            vulnerability_type = excluded.vulnerability_type, publish_date = excluded.publish_date,
+            update_date = excluded.update_date, score = excluded.score, access = excluded.access,
+            complexity = excluded.complexity, authentication = excluded.authentication,
+            confidentiality = excluded.confidentiality, integrity = excluded.integrity,
+            availability = excluded.availability,
+            summary = COALESCE(excluded.summary, summary),
+            cvss3_vector = COALESCE(excluded.cvss3_vector, cvss3_vector),
+            cvss3_score = COALESCE(excluded.cvss3_score, cvss3_score),
+            cvss2_vector = COALESCE(excluded.cvss2_vector, cvss2_vector),
+            exploit_count = MAX(excluded.exploit_count, exploit_count)",
+        params![
+            cve.name,
+            cve.url,
+            cve.cwe_id,
+            cve.cwe_url,
+            cve.vulnerability_type,
+            cve.publish_date,
+            cve.update_date,
+            cve.score,
+            cve.access,
+            cve.complexity,
+            cve.authentication,
+            cve.confidentiality,
+            cve.integrity,
+            cve.availability,
+            cve.summary,
+            cve.cvss3_vector,
+            cve.cvss3_score,
+            cve.cvss2_vector,
+            cve.exploit_count,
+        ],
+    )?;
+
+    if !cve.products.is_empty() {
+        tx.execute("DELETE FROM cve_products WHERE cve = ?1", params![cve.name])?;
+
+        for product in &cve.products {
+            tx.execute(
+                "INSERT INTO cve_products (cve, vendor, product, version) VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    cve.name,
+                    product.vendor.to_lowercase(),
+                    product.product.to_lowercase(),
+                    product.version,
+                ],
+            )?;
+        }
+    }
+
+    if !cve.references.is_empty() {
+        tx.execute("DELETE FROM cve_references WHERE cve = ?1", params![cve.name])?;
+
+        for reference in &cve.references {
+            tx.execute(
+                "INSERT INTO cve_references (cve, url) VALUES (?1, ?2)",
+                params![cve.name, reference],
+            )?;
+        }
+    }
+
+    if !cve.exploits.is_empty() {
+        tx.execute("DELETE FROM cve_exploits WHERE cve = ?1", params![cve.name])?;
+
+        for exploit in &cve.exploits {
+            tx.execute("INSERT INTO cve_exploits (cve, url) VALUES (?1, ?2)", params![cve.name, exploit])?;
+        }
+    }
+
+    if !cve.configurations.is_empty() {
+        tx.execute("DELETE FROM cve_cpes WHERE cve = ?1", params![cve.name])?;
+
+        for cpe in &cve.configurations {
+            tx.execute(
+                "INSERT INTO cve_cpes (cve, criteria, vulnerable, version_start_including,
+                    version_start_excluding, version_end_including, version_end_excluding)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    cve.name,
+                    cpe.criteria,
+                    cpe.vulnerable,
+                    cpe.version_start_including,
+                    cpe.version_start_excluding,
+                    cpe.version_end_including,
+                    cpe.version_end_excluding,
+                ],
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+fn cve_from_row(row: &Row) -> Result<Cve, rusqlite::Error> {
+    Ok(Cve {
+        name: row.get("name")?,
+        url: row.get("url")?,
+        cwe_id: row.get("cwe_id")?,
+        cwe_url: row.get("cwe_url")?,
+        vulnerability_type: row.get("vulnerability_type")?,
+        publish_date: row.get("publish_date")?,
+        update_date: row.get("update_date")?,
+        score: row.get::<_, f64>("score")? as f32,
+        access: row.get("access")?,
+        complexity: row.get("complexity")?,
+        authentication: row.get("authentication")?,
+        confidentiality: row.get("confidentiality")?,
+        integrity: row.get("integrity")?,
+        availability: row.get("availability")?,
+        summary: row.get("summary")?,
+        cvss2_vector: row.get("cvss2_vector")?,
+        exploit_count: row.get("exploit_count")?,
+        cvss3_vector: row.get("cvss3_vector")?,
+        cvss3_score: row.get::<_, Option<f64>>("cvss3_score")?.map(|score| score as f32),
+        ..Cve::default()
+    })
+}
+
+/// Fills the one-to-many parts of `cve` that live in their own tables.
+fn load_details(conn: &Connection, cve: &mut Cve) -> Result<(), Error> {
+    cve.products = products_of(conn, &cve.name)?;
+
+    let mut statement = conn.prepare_cached("SELECT url FROM cve_references WHERE cve = ?1")?;
+    cve.references = statement
+        .query_map(params![cve.name], |row| row.get(0))?
+        .collect::<Result<Vec<String>, rusqlite::Error>>()?;
+
+    let mut statement = conn.prepare_cached("SELECT url FROM cve_exploits WHERE cve = ?1")?;
+    cve.exploits = statement
+        .query_map(params![cve.name], |row| row.get(0))?
+        .collect::<Result<Vec<String>, rusqlite::Error>>()?;
+
+    let mut statement = conn.prepare_cached(
+        "SELECT criteria, vulnerable, version_start_including, version_start_excluding,
+            version_end_including, version_end_excluding
+         FROM cve_cpes WHERE cve = ?1",
+    )?;
+    cve.configurations = statement
+        .query_map(params![cve.name], |row| {
+            Ok(CpeMatch {
+                criteria: row.get(0)?,
+                vulnerable: row.get(1)?,
+                version_start_including: row.get(2)?,
+                version_start_excluding: row.get(3)?,
+                version_end_including: row.get(4)?,
+                version_end_excluding: row.get(5)?,
+            })
+        })?
+        .collect::<Result<Vec<CpeMatch>, rusqlite::Error>>()?;
+
+    Ok(())
+}
+
+fn products_of(conn: &Connection, name: &str) -> Result<Vec<AffectedProduct>, Error> {
+    let mut statement = conn.prepare_cached("SELECT vendor, product, version FROM cve_products WHERE cve = ?1")?;
+    let products = statement
+        .query_map(params![name], |row| {
+            Ok(AffectedProduct {
+                vendor: row.get(0)?,
+                product: row.get(1)?,
+                version: row.get(2)?,
+            })
+        })?
+        .collect::<Result<Vec<AffectedProduct>, rusqlite::Error>>()?;
+
+    Ok(products)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// A store in a fresh temporary file, removed when dropped.
+    struct TempStore {
+        path: PathBuf,
+        store: CveStore,
+    }
+
+    impl TempStore {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("vxsuite-cvedb-{}-{}.db", name, std::process::id()));
+            let _ = std::fs::remove_file(&path);
+            let store = CveStore::open(&path).unwrap();
+            TempStore { path, store }
+        }
+    }
+
+    impl Drop for TempStore {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+
+    fn cve(name: &str, score: f32) -> Cve {
+        Cve {
+            name: name.to_string(),
+            url: format!("https://www.cvedetails.com/cve/{}/", name),
+            publish_date: "2021-06-01".to_string(),
+            update_date: "2021-06-02".to_string(),
+            score,
+            ..Cve::default()
+        }
+    }
+
+    fn affected(vendor: &str, product: &str, version: &str) -> AffectedProduct {
+        AffectedProduct { vendor: vendor.to_string(), product: product.to_string(), version: version.to_string() }
+    }
+
+    fn service(vendor: Option<&str>, product: &str, version: &str) -> Service {
+        Service {
+            port: 80,
+            name: "http".to_string(),
+            vendor: vendor.map(String::from),
+            product: Some(product.to_string()),
+            version: Some(version.to_string()),
+            cpe: None,
+            banner: String::new(),
+        }
+    }
+
+    fn names(cves: &[Cve]) -> Vec<&str> {
+        cves.iter().map(|cve| cve.name.as_str()).collect()
+    }
+
+    #[test]
+    fn migrations_upgrade_an_old_database_once() {
+        let path = std::env::temp_dir().join(format!("vxsuite-cvedb-migrations-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        {
+            let conn = Connection::open(&path).unwrap();
+            conn.execute_batch(MIGRATIONS[0]).unwrap();
+            conn.execute_batch("PRAGMA user_version = 1").unwrap();
+            conn.execute(
+                "INSERT INTO cves VALUES ('CVE-2020-0001', 'u', NULL, NULL, 't', '2020-01-01', '2020-01-02',
+                    5.0, 'Remote', 'Low', 'None', 'None', 'None', 'Partial')",
+                [],
+            )
+            .unwrap();
+        }
+
+        for _ in 0..2 {
+            let store = CveStore::open(&path).unwrap();
+            let version: usize = store.connection().unwrap().query_row("PRAGMA user_version", [], |row| row.get(0)).unwrap();
+            assert_eq!(version, MIGRATIONS.len());
+
+            let old = store.get("CVE-2020-0001").unwrap().unwrap();
+            assert_eq!(old.exploit_count, 0);
+            assert_eq!(old.summary, None);
+        }
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn saving_again_keeps_what_the_new_record_lacks() {
+        let temp = TempStore::new("upsert");
+        let full = Cve {
+            summary: Some("Heap overflow".to_string()),
+            cvss3_vector: Some("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H".to_string()),
+            cvss3_score: Some(9.8),
+            exploit_count: 2,
+            products: vec![affected("OpenBSD", "OpenSSH", "8.2")],
+            references: vec!["https://example.com/advisory".to_string()],
+            ..cve("CVE-2021-0001", 7.5)
+        };
+        temp.store.save(&full).unwrap();
+
+        // A list row only: no details, a newer date and score.
+        let row = Cve { update_date: "2021-07-01".to_string(), ..cve("CVE-2021-0001", 6.8) };
+        temp.store.save(&row).unwrap();
+
+        let saved = temp.store.get("CVE-2021-0001").unwrap().unwrap();
+        assert_eq!(saved.update_date, "2021-07-01");
+        assert_eq!(saved.score, 6.8);
+        assert_eq!(saved.summary.as_deref(), Some("Heap overflow"));
+        assert_eq!(saved.cvss3_score, Some(9.8));
+        assert_eq!(saved.exploit_count, 2);
+        assert_eq!(saved.products.len(), 1);
+        assert_eq!(saved.products[0].vendor, "openbsd");
+        assert_eq!(saved.references, full.references);
+
+        let replaced = Cve { products: vec![affected("openbsd", "openssh", "8.3")], ..row };
+        temp.store.save(&replaced).unwrap();
+        assert_eq!(temp.store.get("CVE-2021-0001").unwrap().unwrap().products[0].version, "8.3");
+    }
+
+    #[test]
+    fn search_ranks_by_the_best_known_score() {
+        let temp = TempStore::new("search");
+        temp.store
+            .save_all(&[
+                Cve { products: vec![affected("apache", "http_server", "2.4.49")], ..cve("CVE-2021-0001", 5.0) },
+                Cve { cvss3_score: Some(9.8), products: vec![affected("apache", "http_server", "*")], ..cve("CVE-2021-0002", 4.3) },
+                Cve { products: vec![affected("nginx", "nginx", "1.20.0")], ..cve("CVE-2021-0003", 7.5) },
+            ])
+            .unwrap();
+
+        let all = temp.store.search(&CveQuery::default()).unwrap();
+        assert_eq!(names(&all), vec!["CVE-2021-0002", "CVE-2021-0003", "CVE-2021-0001"]);
+
+        let apache = CveQuery { product: Some("HTTP_Server".to_string()), version: Some("2.4.49".to_string()), ..CveQuery::default() };
+        assert_eq!(names(&temp.store.search(&apache).unwrap()), vec!["CVE-2021-0002", "CVE-2021-0001"]);
+
+        let severe = CveQuery { min_score: Some(7.0), limit: Some(1), ..CveQuery::default() };
+        assert_eq!(names(&temp.store.search(&severe).unwrap()), vec!["CVE-2021-0002"]);
+    }
+
+    #[test]
+    fn services_match_on_vendor_product_and_version() {
+        let temp = TempStore::new("lookup");
+        temp.store
+            .save_all(&[
+                Cve { products: vec![affected("acme", "proxy", "1.0")], ..cve("CVE-2021-0001", 5.0) },
+                Cve { products: vec![affected("initech", "proxy", "1.0")], ..cve("CVE-2021-0002", 5.0) },
+                Cve {
+                    products: vec![affected("acme", "proxy", "*")],
+                    configurations: vec![CpeMatch {
+                        criteria: "cpe:2.3:a:acme:proxy:*:*:*:*:*:*:*:*".to_string(),
+                        vulnerable: true,
+                        version_end_excluding: Some("1.1".to_string()),
+                        ..CpeMatch::default()
+                    }],
+                    ..cve("CVE-2021-0003", 5.0)
+                },
+            ])
+            .unwrap();
+
+        let found = temp.store.lookup_service(&service(Some("acme"), "proxy", "1.0")).unwrap();
+        assert_eq!(names(&found), vec!["CVE-2021-0003", "CVE-2021-0001"]);
+
+        let patched = temp.store.lookup_service(&service(Some("acme"), "proxy", "1.1")).unwrap();
+        assert!(patched.is_empty());
+
+        // Without a vendor, the product and version alone decide.
+        let found = temp.store.lookup_service(&service(None, "proxy", "1.0")).unwrap();
+        assert_eq!(names(&found), vec!["CVE-2021-0003", "CVE-2021-0002", "CVE-2021-0001"]);
+    }
+}
@@ -0,0 +1,1420 @@
+use crate::module::error::Error;
+use serde::Serialize;
+use std::fmt;
+use std::str::FromStr;
+
+/// Qualitative severity rating shared by CVSS v3.x and v4.0 (v2 has no None/Critical).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum Severity {
+    None,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    pub fn from_score(score: f64) -> Severity {
+        if score == 0.0 {
+            Severity::None
+        } else if score < 4.0 {
+            Severity::Low
+        } else if score < 7.0 {
+            Severity::Medium
+        } else if score < 9.0 {
+            Severity::High
+        } else {
+            Severity::Critical
+        }
+    }
+
+    /// CVSS v2 only knows Low (0-3.9), Medium (4-6.9) and High (7-10).
+    pub fn from_v2_score(score: f64) -> Severity {
+        if score < 4.0 {
+            Severity::Low
+        } else if score < 7.0 {
+            Severity::Medium
+        } else {
+            Severity::High
+        }
+    }
+
+    /// Lowest score rated with this severity.
+    pub fn min_score(&self) -> f64 {
+        match self {
+            Severity::None => 0.0,
+            Severity::Low => 0.1,
+            Severity::Medium => 4.0,
+            Severity::High => 7.0,
+            Severity::Critical => 9.0,
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Severity::None => "None",
+            Severity::Low => "Low",
+            Severity::Medium => "Medium",
+            Severity::High => "High",
+            Severity::Critical => "Critical",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Severity {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "none" => Ok(Severity::None),
+            "low" => Ok(Severity::Low),
+            "medium" => Ok(Severity::Medium),
+            "high" => Ok(Severity::High),
+            "critical" => Ok(Severity::Critical),
+            _ => Err(Error::Cvss(format!("unknown severity {}", value))),
+        }
+    }
+}
+
+/// Declares a metric enum with the abbreviations used in vector strings.
+macro_rules! metric {
+    ($name:ident { $($variant:ident => $abbr:literal),+ $(,)? }) => {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum $name {
+            $($variant),+
+        }
+
+        impl $name {
+            fn parse(value: &str) -> Option<Self> {
+                match value {
+                    $($abbr => Some($name::$variant),)+
+                    _ => None,
+                }
+            }
+
+            pub fn abbr(&self) -> &'static str {
+                match self {
+                    $($name::$variant => $abbr),+
+                }
+            }
+        }
+    };
+}
+
+/// Splits `AV:N/AC:L/...` into (metric, value) pairs, rejecting duplicates.
+fn metrics(vector: &str) -> Result<Vec<(&str, &str)>, Error> {
+    let mut pairs: Vec<(&str, &str)> = Vec::new();
+
+    for part in vector.split('/').filter(|part| !part.is_empty()) {
+        let (metric, value) = part
+            .split_once(':')
+            .ok_or_else(|| Error::Cvss(format!("malformed metric {}", part)))?;
+
+        if pairs.iter().any(|(known, _)| *known == metric) {
+            return Err(Error::Cvss(format!("duplicated metric {}", metric)));
+        }
+        pairs.push((metric, value));
+    }
+
+    Ok(pairs)
+}
+
+fn required<T>(pairs: &[(&str, &str)], metric: &str, parse: fn(&str) -> Option<T>) -> Result<T, Error> {
+    optional(pairs, metric, parse)?.ok_or_else(|| Error::Cvss(format!("missing metric {}", metric)))
+}
+
+fn optional<T>(pairs: &[(&str, &str)], metric: &str, parse: fn(&str) -> Option<T>) -> Result<Option<T>, Error> {
+    match pairs.iter().find(|(known, _)| *known == metric) {
+        Some((_, value)) => parse(value)
+            .map(Some)
+            .ok_or_else(|| Error::Cvss(format!("invalid value {} for {}", value, metric))),
+        None => Ok(None),
+    }
+}
+
+fn reject_unknown(pairs: &[(&str, &str)], known: &[&str]) -> Result<(), Error> {
+    match pairs.iter().find(|(metric, _)| !known.contains(metric)) {
+        Some((metric, _)) => Err(Error::Cvss(format!("unknown metric {}", metric))),
+        None => Ok(()),
+    }
+}
+
+/// Appends `/METRIC:value` for every metric that is set and not "not defined".
+fn push_metric(vector: &mut String, metric: &str, abbr: Option<&str>, undefined: &str) {
+    if let Some(abbr) = abbr {
+        if abbr != undefined {
+            vector.push_str(&format!("/{}:{}", metric, abbr));
+        }
+    }
+}
+
+fn round_to_1_decimal(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+metric!(V2AccessVector { Local => "L", Adjacent => "A", Network => "N" });
+metric!(V2AccessComplexity { High => "H", Medium => "M", Low => "L" });
+metric!(V2Authentication { Multiple => "M", Single => "S", None => "N" });
+metric!(V2Impact { None => "N", Partial => "P", Complete => "C" });
+metric!(V2Exploitability { Unproven => "U", ProofOfConcept => "POC", Functional => "F", High => "H", NotDefined => "ND" });
+metric!(V2RemediationLevel { OfficialFix => "OF", TemporaryFix => "TF", Workaround => "W", Unavailable => "U", NotDefined => "ND" });
+metric!(V2ReportConfidence { Unconfirmed => "UC", Uncorroborated => "UR", Confirmed => "C", NotDefined => "ND" });
+metric!(V2CollateralDamage { None => "N", Low => "L", LowMedium => "LM", MediumHigh => "MH", High => "H", NotDefined => "ND" });
+metric!(V2TargetDistribution { None => "N", Low => "L", Medium => "M", High => "H", NotDefined => "ND" });
+metric!(V2Requirement { Low => "L", Medium => "M", High => "H", NotDefined => "ND" });
+
+impl V2AccessVector {
+    fn weight(&self) -> f64 {
+        match self {
+            V2AccessVector::Local => 0.395,
+            V2AccessVector::Adjacent => 0.646,
+            V2AccessVector::Network => 1.0,
+        }
+    }
+}
+
+impl V2AccessComplexity {
+    fn weight(&self) -> f64 {
+        match self {
+            V2AccessComplexity::High => 0.35,
+            V2AccessComplexity::Medium => 0.61,
+            V2AccessComplexity::Low => 0.71,
+        }
+    }
+}
+
+impl V2Authentication {
+    fn weight(&self) -> f64 {
+        match self {
+            V2Authentication::Multiple => 0.45,
+            V2Authentication::Single => 0.56,
+            V2Authentication::None => 0.704,
+        }
+    }
+}
+
+impl V2Impact {
+    fn weight(&self) -> f64 {
+        match self {
+            V2Impact::None => 0.0,
+            V2Impact::Partial => 0.275,
+            V2Impact::Complete => 0.660,
+        }
+    }
+}
+
+impl V2Exploitability {
+    fn weight(&self) -> f64 {
+        match self {
+            V2Exploitability::Unproven => 0.85,
+            V2Exploitability::ProofOfConcept => 0.9,
+            V2Exploitability::Functional => 0.95,
+            V2Exploitability::High | V2Exploitability::NotDefined => 1.0,
+        }
+    }
+}
+
+impl V2RemediationLevel {
+    fn weight(&self) -> f64 {
+        match self {
+            V2RemediationLevel::OfficialFix => 0.87,
+            V2RemediationLevel::TemporaryFix => 0.90,
+            V2RemediationLevel::Workaround => 0.95,
+            V2RemediationLevel::Unavailable | V2RemediationLevel::NotDefined => 1.0,
+        }
+    }
+}
+
+impl V2ReportConfidence {
+    fn weight(&self) -> f64 {
+        match self {
+            V2ReportConfidence::Unconfirmed => 0.90,
+            V2ReportConfidence::Uncorroborated => 0.95,
+            V2ReportConfidence::Confirmed | V2ReportConfidence::NotDefined => 1.0,
+        }
+    }
+}
+
+impl V2CollateralDamage {
+    fn weight(&self) -> f64 {
+        match self {
+            V2CollateralDamage::None | V2CollateralDamage::NotDefined => 0.0,
+            V2CollateralDamage::Low => 0.1,
+            V2CollateralDamage::LowMedium => 0.3,
+            V2CollateralDamage::MediumHigh => 0.4,
+            V2CollateralDamage::High => 0.5,
+        }
+    }
+}
+
+impl V2TargetDistribution {
+    fn weight(&self) -> f64 {
+        match self {
+            V2TargetDistribution::None => 0.0,
+            V2TargetDistribution::Low => 0.25,
+            V2TargetDistribution::Medium => 0.75,
+            V2TargetDistribution::High | V2TargetDistribution::NotDefined => 1.0,
+        }
+    }
+}
+
+impl V2Requirement {
+    fn weight(&self) -> f64 {
+        match self {
+            V2Requirement::Low => 0.5,
+            V2Requirement::Medium | V2Requirement::NotDefined => 1.0,
+            V2Requirement::High => 1.51,
+        }
+    }
+}
+
+/// A CVSS v2 vector, e.g. `AV:N/AC:L/Au:N/C:P/I:P/A:P`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CvssV2 {
+    pub access_vector: V2AccessVector,
+    pub access_complexity: V2AccessComplexity,
+    pub authentication: V2Authentication,
+    pub confidentiality: V2Impact,
+    pub integrity: V2Impact,
+    pub availability: V2Impact,
+    pub exploitability: Option<V2Exploitability>,
+    pub remediation_level: Option<V2RemediationLevel>,
+    pub report_confidence: Option<V2ReportConfidence>,
+    pub collateral_damage: Option<V2CollateralDamage>,
+    pub target_distribution: Option<V2TargetDistribution>,
+    pub confidentiality_requirement: Option<V2Requirement>,
+    pub integrity_requirement: Option<V2Requirement>,
+    pub availability_requirement: Option<V2Requirement>,
+}
+
+impl CvssV2 {
+    /// Builds the base vector from the words cvedetails.com uses in its tables.
+    pub fn from_cvedetails(
+        access: &str,
+        complexity: &str,
+        authentication: &str,
+        confidentiality: &str,
+        integrity: &str,
+        availability: &str,
+    ) -> Option<CvssV2> {
+        let impact = |value: &str| match value.to_lowercase().as_str() {
+            "none" => Some(V2Impact::None),
+            "partial" => Some(V2Impact::Partial),
+            "complete" => Some(V2Impact::Complete),
+            _ => None,
+        };
+
+        let access_vector = match access.to_lowercase().as_str() {
+            "local" => V2AccessVector::Local,
+            // cvedetails says "Remote" and "Local Network".
+            "adjacent network" | "adjacent" | "local network" => V2AccessVector::Adjacent,
+            "network" | "remote" => V2AccessVector::Network,
+            _ => return None,
+        };
+
+        let access_complexity = match complexity.to_lowercase().as_str() {
+            "high" => V2AccessComplexity::High,
+            "medium" => V2AccessComplexity::Medium,
+            "low" => V2AccessComplexity::Low,
+            _ => return None,
+        };
+
+        let authentication = match authentication.to_lowercase().as_str() {
+            "multiple systems" | "multiple" => V2Authentication::Multiple,
+            "single system" | "single" => V2Authentication::Single,
+            "not required" | "none" => V2Authentication::None,
+            _ => return None,
+        };
+
+        Some(CvssV2 {
+            access_vector,
+            access_complexity,
+            authentication,
+            confidentiality: impact(confidentiality)?,
+            integrity: impact(integrity)?,
+            availability: impact(availability)?,
+            exploitability: None,
+            remediation_level: None,
+            report_confidence: None,
+            collateral_damage: None,
+            target_distribution: None,
+            confidentiality_requirement: None,
+            integrity_requirement: None,
+            availability_requirement: None,
+        })
+    }
+
+    fn score_with_impact(&self, impact: f64) -> f64 {
+        let exploitability = 20.0
+            * self.access_vector.weight()
+            * self.access_complexity.weight()
+            * self.authentication.weight();
+        let f_impact = if impact == 0.0 { 0.0 } else { 1.176 };
+
+        round_to_1_decimal(((0.6 * impact) + (0.4 * exploitability) - 1.5) * f_impact)
+    }
+
+    fn temporal_factor(&self) -> f64 {
+        self.exploitability.map(|metric| metric.weight()).unwrap_or(1.0)
+            * self.remediation_level.map(|metric| metric.weight()).unwrap_or(1.0)
+            * self.report_confidence.map(|metric| metric.weight()).unwrap_or(1.0)
+    }
+
+    pub fn base_score(&self) -> f64 {
+        let impact = 10.41
+            * (1.0
+                - (1.0 - self.confidentiality.weight())
+                    * (1.0 - self.integrity.weight())
+                    * (1.0 - self.availability.weight()));
+
+        self.score_with_impact(impact)
+    }
+
+    pub fn temporal_score(&self) -> f64 {
+        round_to_1_decimal(self.base_score() * self.temporal_factor())
+    }
+
+    pub fn environmental_score(&self) -> f64 {
+        let requirement = |metric: Option<V2Requirement>| metric.map(|metric| metric.weight()).unwrap_or(1.0);
+
+        let adjusted_impact = (10.41
+            * (1.0
+                - (1.0 - self.confidentiality.weight() * requirement(self.confidentiality_requirement))
+                    * (1.0 - self.integrity.weight() * requirement(self.integrity_requirement))
+                    * (1.0 - self.availability.weight() * requirement(self.availability_requirement))))
+            .min(10.0);
+
+        let adjusted_temporal = round_to_1_decimal(self.score_with_impact(adjusted_impact) * self.temporal_factor());
+        let collateral_damage = self.collateral_damage.map(|metric| metric.weight()).unwrap_or(0.0);
+        let target_distribution = self.target_distribution.map(|metric| metric.weight()).unwrap_or(1.0);
+
+        round_to_1_decimal((adjusted_temporal + (10.0 - adjusted_temporal) * collateral_damage) * target_distribution)
+    }
+
+    pub fn severity(&self) -> Severity {
+        Severity::from_v2_score(self.base_score())
+    }
+}
+
+impl FromStr for CvssV2 {
+    type Err = Error;
+
+    fn from_str(vector: &str) -> Result<Self, Self::Err> {
+        let vector = vector.trim().trim_start_matches('(').trim_end_matches(')');
+        let pairs = metrics(vector)?;
+        reject_unknown(&pairs, &["AV", "AC", "Au", "C", "I", "A", "E", "RL", "RC", "CDP", "TD", "CR", "IR", "AR"])?;
+
+        Ok(CvssV2 {
+            access_vector: required(&pairs, "AV", V2AccessVector::parse)?,
+            access_complexity: required(&pairs, "AC", V2AccessComplexity::parse)?,
+            authentication: required(&pairs, "Au", V2Authentication::parse)?,
+            confidentiality: required(&pairs, "C", V2Impact::parse)?,
+            integrity: required(&pairs, "I", V2Impact::parse)?,
+            availability: required(&pairs, "A", V2Impact::parse)?,
+            exploitability: optional(&pairs, "E", V2Exploitability::parse)?,
+            remediation_level: optional(&pairs, "RL", V2RemediationLevel::parse)?,
+            report_confidence: optional(&pairs, "RC", V2ReportConfidence::parse)?,
+            collateral_damage: optional(&pairs, "CDP", V2CollateralDamage::parse)?,
+            target_distribution: optional(&pairs, "TD", V2TargetDistribution::parse)?,
+            confidentiality_requirement: optional(&pairs, "CR", V2Requirement::parse)?,
+            integrity_requirement: optional(&pairs, "IR", V2Requirement::parse)?,
+            availability_requirement: optional(&pairs, "AR", V2Requirement::parse)?,
+        })
+    }
+}
+
+impl fmt::Display for CvssV2 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut vector = format!(
+            "AV:{}/AC:{}/Au:{}/C:{}/I:{}/A:{}",
+            self.access_vector.abbr(),
+            self.access_complexity.abbr(),
+            self.authentication.abbr(),
+            self.confidentiality.abbr(),
+            self.integrity.abbr(),
+            self.availability.abbr(),
+        );
+
+        push_metric(&mut vector, "E", self.exploitability.map(|metric| metric.abbr()), "ND");
+        push_metric(&mut vector, "RL", self.remediation_level.map(|metric| metric.abbr()), "ND");
+        push_metric(&mut vector, "RC", self.report_confidence.map(|metric| metric.abbr()), "ND");
+        push_metric(&mut vector, "CDP", self.collateral_damage.map(|metric| metric.abbr()), "ND");
+        push_metric(&mut vector, "TD", self.target_distribution.map(|metric| metric.abbr()), "ND");
+        push_metric(&mut vector, "CR", self.confidentiality_requirement.map(|metric| metric.abbr()), "ND");
+        push_metric(&mut vector, "IR", self.integrity_requirement.map(|metric| metric.abbr()), "ND");
+        push_metric(&mut vector, "AR", self.availability_requirement.map(|metric| metric.abbr()), "ND");
+
+        write!(f, "{}", vector)
+    }
+}
+
+metric!(V3AttackVector { Network => "N", Adjacent => "A", Local => "L", Physical => "P" });
+metric!(V3AttackComplexity { Low => "L", High => "H" });
+metric!(V3Privileges { None => "N", Low => "L", High => "H" });
+metric!(V3UserInteraction { None => "N", Required => "R" });
+metric!(V3Scope { Unchanged => "U", Changed => "C" });
+metric!(V3Impact { High => "H", Low => "L", None => "N" });
+metric!(V3Exploitability { NotDefined => "X", Unproven => "U", ProofOfConcept => "P", Functional => "F", High => "H" });
+metric!(V3RemediationLevel { NotDefined => "X", OfficialFix => "O", TemporaryFix => "T", Workaround => "W", Unavailable => "U" });
+metric!(V3ReportConfidence { NotDefined => "X", Unknown => "U", Reasonable => "R", Confirmed => "C" });
+metric!(V3Requirement { NotDefined => "X", Low => "L", Medium => "M", High => "H" });
+
+impl V3AttackVector {
+    fn weight(&self) -> f64 {
+        match self {
+            V3AttackVector::Network => 0.85,
+            V3AttackVector::Adjacent => 0.62,
+            V3AttackVector::Local => 0.55,
+            V3AttackVector::Physical => 0.2,
+        }
+    }
+}
+
+impl V3AttackComplexity {
+    fn weight(&self) -> f64 {
+        match self {
+            V3AttackComplexity::Low => 0.77,
+            V3AttackComplexity::High => 0.44,
+        }
+    }
+}
+
+impl V3Privileges {
+    fn weight(&self, scope: V3Scope) -> f64 {
+        match (self, scope) {
+            (V3Privileges::None, _) => 0.85,
+            (V3Privileges::Low, V3Scope::Unchanged) => 0.62,
+            (V3Privileges::Low, V3Scope::Changed) => 0.68,
+            (V3Privileges::High, V3Scope::Unchanged) => 0.27,
+            (V3Privileges::High, V3Scope::Changed) => 0.5,
+        }
+    }
+}
+
+impl V3UserInteraction {
+    fn weight(&self) -> f64 {
+        match self {
+            V3UserInteraction::None => 0.85,
+            V3UserInteraction::Required => 0.62,
+        }
+    }
+}
+
+impl V3Impact {
+    fn weight(&self) -> f64 {
+        match self {
+            V3Impact::High => 0.56,
+            V3Impact::Low => 0.22,
+            V3Impact::None => 0.0,
+        }
+    }
+}
+
+impl V3Exploitability {
+    fn weight(&self) -> f64 {
+        match self {
+            V3Exploitability::NotDefined | V3Exploitability::High => 1.0,
+            V3Exploitability::Functional => 0.97,
+            V3Exploitability::ProofOfConcept => 0.94,
+            V3Exploitability::Unproven => 0.91,
+        }
+    }
+}
+
+impl V3RemediationLevel {
+    fn weight(&self) -> f64 {
+        match self {
+            V3RemediationLevel::NotDefined | V3RemediationLevel::Unavailable => 1.0,
+            V3RemediationLevel::Workaround => 0.97,
+            V3RemediationLevel::TemporaryFix => 0.96,
+            V3RemediationLevel::OfficialFix => 0.95,
+        }
+    }
+}
+
+impl V3ReportConfidence {
+    fn weight(&self) -> f64 {
+        match self {
+            V3ReportConfidence::NotDefined | V3ReportConfidence::Confirmed => 1.0,
+            V3ReportConfidence::Reasonable => 0.96,
+            V3ReportConfidence::Unknown => 0.92,
+        }
+    }
+}
+
+impl V3Requirement {
+    fn weight(&self) -> f64 {
+        match self {
+            V3Requirement::NotDefined | V3Requirement::Medium => 1.0,
+            V3Requirement::Low => 0.5,
+            V3Requirement::High => 1.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum V3Version {
+    V30,
+    V31,
+}
+
+/// The environmental `M*` metrics, each overriding its base metric when set.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct V3Modified {
+    pub attack_vector: Option<V3AttackVector>,
+    pub attack_complexity: Option<V3AttackComplexity>,
+    pub privileges_required: Option<V3Privileges>,
+    pub user_interaction: Option<V3UserInteraction>,
+    pub scope: Option<V3Scope>,
+    pub confidentiality: Option<V3Impact>,
+    pub integrity: Option<V3Impact>,
+    pub availability: Option<V3Impact>,
+}
+
+/// A CVSS v3.0 or v3.1 vector, e.g. `CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CvssV3 {
+    pub version: V3Version,
+    pub attack_vector: V3AttackVector,
+    pub attack_complexity: V3AttackComplexity,
+    pub privileges_required: V3Privileges,
+    pub user_interaction: V3UserInteraction,
+    pub scope: V3Scope,
+    pub confidentiality: V3Impact,
+    pub integrity: V3Impact,
+    pub availability: V3Impact,
+    pub exploit_code_maturity: Option<V3Exploitability>,
+    pub remediation_level: Option<V3RemediationLevel>,
+    pub report_confidence: Option<V3ReportConfidence>,
+    pub confidentiality_requirement: Option<V3Requirement>,
+    pub integrity_requirement: Option<V3Requirement>,
+    pub availability_requirement: Option<V3Requirement>,
+    pub modified: V3Modified,
+}
+
+impl CvssV3 {
+    /// The "round up" of the specification; v3.1 works around floating point errors.
+    fn roundup(&self, value: f64) -> f64 {
+        match self.version {
+            V3Version::V30 => (value * 10.0).ceil() / 10.0,
+            V3Version::V31 => {
+                let int_input = (value * 100_000.0).round() as i64;
+                if int_input % 10_000 == 0 {
+                    int_input as f64 / 100_000.0
+                } else {
+                    ((int_input / 10_000) as f64 + 1.0) / 10.0
+                }
+            }
+        }
+    }
+
+    fn temporal_factor(&self) -> f64 {
+        self.exploit_code_maturity.map(|metric| metric.weight()).unwrap_or(1.0)
+            * self.remediation_level.map(|metric| metric.weight()).unwrap_or(1.0)
+            * self.report_confidence.map(|metric| metric.weight()).unwrap_or(1.0)
+    }
+
+    pub fn base_score(&self) -> f64 {
+        let iss = 1.0
+            - (1.0 - self.confidentiality.weight())
+                * (1.0 - self.integrity.weight())
+                * (1.0 - self.availability.weight());
+
+        let impact = match self.scope {
+            V3Scope::Unchanged => 6.42 * iss,
+            V3Scope::Changed => 7.52 * (iss - 0.029) - 3.25 * (iss - 0.02).powi(15),
+        };
+
+        let exploitability = 8.22
+            * self.attack_vector.weight()
+            * self.attack_complexity.weight()
+            * self.privileges_required.weight(self.scope)
+            * self.user_interaction.weight();
+
+        if impact <= 0.0 {
+            return 0.0;
+        }
+
+        match self.scope {
+            V3Scope::Unchanged => self.roundup((impact + exploitability).min(10.0)),
+            V3Scope::Changed => self.roundup((1.08 * (impact + exploitability)).min(10.0)),
+        }
+    }
+
+    pub fn temporal_score(&self) -> f64 {
+        self.roundup(self.base_score() * self.temporal_factor())
+    }
+
+    pub fn environmental_score(&self) -> f64 {
+        let modified = &self.modified;
+        let scope = modified.scope.unwrap_or(self.scope);
+        let confidentiality = modified.confidentiality.unwrap_or(self.confidentiality);
+        let integrity = modified.integrity.unwrap_or(self.integrity);
+        let availability = modified.availability.unwrap_or(self.availability);
+        let requirement = |metric: Option<V3Requirement>| metric.map(|metric| metric.weight()).unwrap_or(1.0);
+
+        let miss = (1.0
+            - (1.0 - requirement(self.confidentiality_requirement) * confidentiality.weight())
+                * (1.0 - requirement(self.integrity_requirement) * integrity.weight())
+                * (1.0 - requirement(self.availability_requirement) * availability.weight()))
+        .min(0.915);
+
+        let impact = match (scope, self.version) {
+            (V3Scope::Unchanged, _) => 6.42 * miss,
+            (V3Scope::Changed, V3Version::V30) => 7.52 * (miss - 0.029) - 3.25 * (miss - 0.02).powi(15),
+            (V3Scope::Changed, V3Version::V31) => 7.52 * (miss - 0.029) - 3.25 * (miss * 0.9731 - 0.02).powi(13),
+        };
+
+        let exploitability = 8.22
+            * modified.attack_vector.unwrap_or(self.attack_vector).weight()
+            * modified.attack_complexity.unwrap_or(self.attack_complexity).weight()
+            * modified.privileges_required.unwrap_or(self.privileges_required).weight(scope)
+            * modified.user_interaction.unwrap_or(self.user_interaction).weight();
+
+        if impact <= 0.0 {
+            return 0.0;
+        }
+
+        let base = match scope {
+            V3Scope::Unchanged => self.roundup((impact + exploitability).min(10.0)),
+            V3Scope::Changed => self.roundup((1.08 * (impact + exploitability)).min(10.0)),
+        };
+
+        self.roundup(base * self.temporal_factor())
+    }
+
+    pub fn severity(&self) -> Severity {
+        Severity::from_score(self.base_score())
+    }
+}
+
+const V3_METRICS: &[&str] = &[
+    "AV", "AC", "PR", "UI", "S", "C", "I", "A", "E", "RL", "RC", "CR", "IR", "AR", "MAV", "MAC", "MPR",
+    "MUI", "MS", "MC", "MI", "MA",
+];
+
+/// Parses a modified metric, `X` (not defined) meaning "use the base value".
+fn modified<T>(pairs: &[(&str, &str)], metric: &str, parse: fn(&str) -> Option<T>) -> Result<Option<T>, Error> {
+    match pairs.iter().find(|(known, _)| *known == metric) {
+        Some((_, "X")) | None => Ok(None),
+        Some((_, value)) => parse(value)
+            .map(Some)
+            .ok_or_else(|| Error::Cvss(format!("invalid value {} for {}", value, metric))),
+    }
+}
+
+impl FromStr for CvssV3 {
+    type Err = Error;
+
+    fn from_str(vector: &str) -> Result<Self, Self::Err> {
+        let vector = vector.trim();
+        let (version, metrics_part) = if let Some(rest) = vector.strip_prefix("CVSS:3.1/") {
+            (V3Version::V31, rest)
+        } else if let Some(rest) = vector.strip_prefix("CVSS:3.0/") {
+            (V3Version::V30, rest)
+        } else {
+            return Err(Error::Cvss(format!("{} is not a CVSS v3 vector", vector)));
+        };
+
+        let pairs = metrics(metrics_part)?;
+        reject_unknown(&pairs, V3_METRICS)?;
+
+        Ok(CvssV3 {
+            version,
+            attack_vector: required(&pairs, "AV", V3AttackVector::parse)?,
+            attack_complexity: required(&pairs, "AC", V3AttackComplexity::parse)?,
+            privileges_required: required(&pairs, "PR", V3Privileges::parse)?,
+            user_interaction: required(&pairs, "UI", V3UserInteraction::parse)?,
+            scope: required(&pairs, "S", V3Scope::parse)?,
+            confidentiality: required(&pairs, "C", V3Impact::parse)?,
+            integrity: required(&pairs, "I", V3Impact::parse)?,
+            availability: required(&pairs, "A", V3Impact::parse)?,
+            exploit_code_maturity: optional(&pairs, "E", V3Exploitability::parse)?,
+            remediation_level: optional(&pairs, "RL", V3RemediationLevel::parse)?,
+            report_confidence: optional(&pairs, "RC", V3ReportConfidence::parse)?,
+            confidentiality_requirement: optional(&pairs, "CR", V3Requirement::parse)?,
+            integrity_requirement: optional(&pairs, "IR", V3Requirement::parse)?,
+            availability_requirement: optional(&pairs, "AR", V3Requirement::parse)?,
+            modified: V3Modified {
+                attack_vector: modified(&pairs, "MAV", V3AttackVector::parse)?,
+                attack_complexity: modified(&pairs, "MAC", V3AttackComplexity::parse)?,
+                privileges_required: modified(&pairs, "MPR", V3Privileges::parse)?,
+                user_interaction: modified(&pairs, "MUI", V3UserInteraction::parse)?,
+                scope: modified(&pairs, "MS", V3Scope::parse)?,
+                confidentiality: modified(&pairs, "MC", V3Impact::parse)?,
+                integrity: modified(&pairs, "MI", V3Impact::parse)?,
+                availability: modified(&pairs, "MA", V3Impact::parse)?,
+            },
+        })
+    }
+}
+
+impl fmt::Display for CvssV3 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let version = match self.version {
+            V3Version::V30 => "3.0",
+            V3Version::V31 => "3.1",
+        };
+
+        let mut vector = format!(
+            "CVSS:{}/AV:{}/AC:{}/PR:{}/UI:{}/S:{}/C:{}/I:{}/A:{}",
+            version,
+            self.attack_vector.abbr(),
+            self.attack_complexity.abbr(),
+            self.privileges_required.abbr(),
+            self.user_interaction.abbr(),
+            self.scope.abbr(),
+            self.confidentiality.abbr(),
+            self.integrity.abbr(),
+            self.availability.abbr(),
+        );
+
+        let modified = &self.modified;
+        push_metric(&mut vector, "E", self.exploit_code_maturity.map(|metric| metric.abbr()), "X");
+        push_metric(&mut vector, "RL", self.remediation_level.map(|metric| metric.abbr()), "X");
+        push_metric(&mut vector, "RC", self.report_confidence.map(|metric| metric.abbr()), "X");
+        push_metric(&mut vector, "CR", self.confidentiality_requirement.map(|metric| metric.abbr()), "X");
+        push_metric(&mut vector, "IR", self.integrity_requirement.map(|metric| metric.abbr()), "X");
+        push_metric(&mut vector, "AR", self.availability_requirement.map(|metric| metric.abbr()), "X");
+        push_metric(&mut vector, "MAV", modified.attack_vector.map(|metric| metric.abbr()), "X");
+        push_metric(&mut vector, "MAC", modified.attack_complexity.map(|metric| metric.abbr()), "X");
+        push_metric(&mut vector, "MPR", modified.privileges_required.map(|metric| metric.abbr()), "X");
+        push_metric(&mut vector, "MUI", modified.user_interaction.map(|metric| metric.abbr()), "X");
+        push_metric(&mut vector, "MS", modified.scope.map(|metric| metric.abbr()), "X");
+        push_metric(&mut vector, "MC", modified.confidentiality.map(|metric| metric.abbr()), "X");
+        push_metric(&mut vector, "MI", modified.integrity.map(|metric| metric.abbr()), "X");
+        push_metric(&mut vector, "MA", modified.availability.map(|metric| metric.abbr()), "X");
+
+        write!(f, "{}", vector)
+    }
+}
+
+/// Metric names of a CVSS v4.0 vector, in specification order, with their allowed values.
+/// The first value of every non mandatory metric is its "not defined" default.
+const V4_METRICS: &[(&str, &[&str], bool)] = &[
+    ("AV", &["N", "A", "L", "P"], true),
+    ("AC", &["L", "H"], true),
+    ("AT", &["N", "P"], true),
+    ("PR", &["N", "L", "H"], true),
+    ("UI", &["N", "P", "A"], true),
+    ("VC", &["H", "L", "N"], true),
+    ("VI", &["H", "L", "N"], true),
+    ("VA", &["H", "L", "N"], true),
+    ("SC", &["H", "L", "N"], true),
+    ("SI", &["H", "L", "N"], true),
+    ("SA", &["H", "L", "N"], true),
+    ("E", &["X", "A", "P", "U"], false),
+    ("CR", &["X", "H", "M", "L"], false),
+    ("IR", &["X", "H", "M", "L"], false),
+    ("AR", &["X", "H", "M", "L"], false),
+    ("MAV", &["X", "N", "A", "L", "P"], false),
+    ("MAC", &["X", "L", "H"], false),
+    ("MAT", &["X", "N", "P"], false),
+    ("MPR", &["X", "N", "L", "H"], false),
+    ("MUI", &["X", "N", "P", "A"], false),
+    ("MVC", &["X", "H", "L", "N"], false),
+    ("MVI", &["X", "H", "L", "N"], false),
+    ("MVA", &["X", "H", "L", "N"], false),
+    ("MSC", &["X", "H", "L", "N"], false),
+    ("MSI", &["X", "S", "H", "L", "N"], false),
+    ("MSA", &["X", "S", "H", "L", "N"], false),
+    ("S", &["X", "N", "P"], false),
+    ("AU", &["X", "N", "Y"], false),
+    ("R", &["X", "A", "U", "I"], false),
+    ("V", &["X", "D", "C"], false),
+    ("RE", &["X", "L", "M", "H"], false),
+    ("U", &["X", "Clear", "Green", "Amber", "Red"], false),
+];
+
+/// Score of every CVSS v4.0 macro vector, from the FIRST reference calculator, sorted by macro vector.
+const V4_MACRO_VECTOR_SCORES: &[(&str, f64)] = &[
+    ("000000", 10.0), ("000001", 9.9), ("000010", 9.8), ("000011", 9.5), ("000020", 9.5), ("000021", 9.2),
+    ("000100", 10.0), ("000101", 9.6), ("000110", 9.3), ("000111", 8.7), ("000120", 9.1), ("000121", 8.1),
+    ("000200", 9.3), ("000201", 9.0), ("000210", 8.9), ("000211", 8.0), ("000220", 8.1), ("000221", 6.8),
+    ("001000", 9.8), ("001001", 9.5), ("001010", 9.5), ("001011", 9.2), ("001020", 9.0), ("001021", 8.4),
+    ("001100", 9.3), ("001101", 9.2), ("001110", 8.9), ("001111", 8.1), ("001120", 8.1), ("001121", 6.5),
+    ("001200", 8.8), ("001201", 8.0), ("001210", 7.8), ("001211", 7.0), ("001220", 6.9), ("001221", 4.8),
+    ("002001", 9.2), ("002011", 8.2), ("002021", 7.2), ("002101", 7.9), ("002111", 6.9), ("002121", 5.0),
+    ("002201", 6.9), ("002211", 5.5), ("002221", 2.7), ("010000", 9.9), ("010001", 9.7), ("010010", 9.5),
+    ("010011", 9.2), ("010020", 9.2), ("010021", 8.5), ("010100", 9.5), ("010101", 9.1), ("010110", 9.0),
+    ("010111", 8.3), ("010120", 8.4), ("010121", 7.1), ("010200", 9.2), ("010201", 8.1), ("010210", 8.2),
+    ("010211", 7.1), ("010220", 7.2), ("010221", 5.3), ("011000", 9.5), ("011001", 9.3), ("011010", 9.2),
+    ("011011", 8.5), ("011020", 8.5), ("011021", 7.3), ("011100", 9.2), ("011101", 8.2), ("011110", 8.0),
+    ("011111", 7.2), ("011120", 7.0), ("011121", 5.9), ("011200", 8.4), ("011201", 7.0), ("011210", 7.1),
+    ("011211", 5.2), ("011220", 5.0), ("011221", 3.0), ("012001", 8.6), ("012011", 7.5), ("012021", 5.2),
+    ("012101", 7.1), ("012111", 5.2), ("012121", 2.9), ("012201", 6.3), ("012211", 2.9), ("012221", 1.7),
+    ("100000", 9.8), ("100001", 9.5), ("100010", 9.4), ("100011", 8.7), ("100020", 9.1), ("100021", 8.1),
+    ("100100", 9.4), ("100101", 8.9), ("100110", 8.6), ("100111", 7.4), ("100120", 7.7), ("100121", 6.4),
+    ("100200", 8.7), ("100201", 7.5), ("100210", 7.4), ("100211", 6.3), ("100220", 6.3), ("100221", 4.9),
+    ("101000", 9.4), ("101001", 8.9), ("101010", 8.8), ("101011", 7.7), ("101020", 7.6), ("101021", 6.7),
+    ("101100", 8.6), ("101101", 7.6), ("101110", 7.4), ("101111", 5.8), ("101120", 5.9), ("101121", 5.0),
+    ("101200", 7.2), ("101201", 5.7), ("101210", 5.7), ("101211", 5.2), ("101220", 5.2), ("101221", 2.5),
+    ("102001", 8.3), ("102011", 7.0), ("102021", 5.4), ("102101", 6.5), ("102111", 5.8), ("102121", 2.6),
+    ("102201", 5.3), ("102211", 2.1), ("102221", 1.3), ("110000", 9.5), ("110001", 9.0), ("110010", 8.8),
+    ("110011", 7.6), ("110020", 7.6), ("110021", 7.0), ("110100", 9.0), ("110101", 7.7), ("110110", 7.5),
+    ("110111", 6.2), ("110120", 6.1), ("110121", 5.3), ("110200", 7.7), ("110201", 6.6), ("110210", 6.8),
+    ("110211", 5.9), ("110220", 5.2), ("110221", 3.0), ("111000", 8.9), ("111001", 7.8), ("111010", 7.6),
+    ("111011", 6.7), ("111020", 6.2), ("111021", 5.8), ("111100", 7.4), ("111101", 5.9), ("111110", 5.7),
+    ("111111", 5.7), ("111120", 4.7), ("111121", 2.3), ("111200", 6.1), ("111201", 5.2), ("111210", 5.7),
+    ("111211", 2.9), ("111220", 2.4), ("111221", 1.6), ("112001", 7.1), ("112011", 5.9), ("112021", 3.0),
+    ("112101", 5.8), ("112111", 2.6), ("112121", 1.5), ("112201", 2.3), ("112211", 1.3), ("112221", 0.6),
+    ("200000", 9.3), ("200001", 8.7), ("200010", 8.6), ("200011", 7.2), ("200020", 7.5), ("200021", 5.8),
+    ("200100", 8.6), ("200101", 7.4), ("200110", 7.4), ("200111", 6.1), ("200120", 5.6), ("200121", 3.4),
+    ("200200", 7.0), ("200201", 5.4), ("200210", 5.2), ("200211", 4.0), ("200220", 4.0), ("200221", 2.2),
+    ("201000", 8.5), ("201001", 7.5), ("201010", 7.4), ("201011", 5.5), ("201020", 6.2), ("201021", 5.1),
+    ("201100", 7.2), ("201101", 5.7), ("201110", 5.5), ("201111", 4.1), ("201120", 4.6), ("201121", 1.9),
+    ("201200", 5.3), ("201201", 3.6), ("201210", 3.4), ("201211", 1.9), ("201220", 1.9), ("201221", 0.8),
+    ("202001", 6.4), ("202011", 5.1), ("202021", 2.0), ("202101", 4.7), ("202111", 2.1), ("202121", 1.1),
+    ("202201", 2.4), ("202211", 0.9), ("202221", 0.4), ("210000", 8.8), ("210001", 7.5), ("210010", 7.3),
+    ("210011", 5.3), ("210020", 6.0), ("210021", 5.0), ("210100", 7.3), ("210101", 5.5), ("210110", 5.9),
+    ("210111", 4.0), ("210120", 4.1), ("210121", 2.0), ("210200", 5.4), ("210201", 4.3), ("210210", 4.5),
+    ("210211", 2.2), ("210220", 2.0), ("210221", 1.1), ("211000", 7.5), ("211001", 5.5), ("211010", 5.8),
+    ("211011", 4.5), ("211020", 4.0), ("211021", 2.1), ("211100", 6.1), ("211101", 5.1), ("211110", 4.8),
+    ("211111", 1.8), ("211120", 2.0), ("211121", 0.9), ("211200", 4.6), ("211201", 1.8), ("211210", 1.7),
+    ("211211", 0.7), ("211220", 0.8), ("211221", 0.2), ("212001", 5.3), ("212011", 2.4), ("212021", 1.4),
+    ("212101", 2.4), ("212111", 1.2), ("212121", 0.5), ("212201", 1.0), ("212211", 0.3), ("212221", 0.1),
+];
+
+/// Highest severity vectors of every EQ class, as `metric:value/` parts of a vector.
+const V4_EQ1_MAXES: &[&[&str]] = &[
+    &["AV:N/PR:N/UI:N/"],
+    &["AV:A/PR:N/UI:N/", "AV:N/PR:L/UI:N/", "AV:N/PR:N/UI:P/"],
+    &["AV:P/PR:N/UI:N/", "AV:A/PR:L/UI:P/"],
+];
+const V4_EQ2_MAXES: &[&[&str]] = &[&["AC:L/AT:N/"], &["AC:H/AT:N/", "AC:L/AT:P/"]];
+/// EQ3 and EQ6 are scored together, indexed by EQ3 then EQ6.
+const V4_EQ3_EQ6_MAXES: &[&[&[&str]]] = &[
+    &[
+        &["VC:H/VI:H/VA:H/CR:H/IR:H/AR:H/"],
+        &["VC:H/VI:H/VA:L/CR:M/IR:M/AR:H/", "VC:H/VI:H/VA:H/CR:M/IR:M/AR:M/"],
+    ],
+    &[
+        &["VC:L/VI:H/VA:H/CR:H/IR:H/AR:H/", "VC:H/VI:L/VA:H/CR:H/IR:H/AR:H/"],
+        &[
+            "VC:L/VI:H/VA:L/CR:H/IR:M/AR:H/",
+            "VC:L/VI:H/VA:H/CR:H/IR:M/AR:M/",
+            "VC:H/VI:L/VA:H/CR:M/IR:H/AR:M/",
+            "VC:H/VI:L/VA:L/CR:M/IR:H/AR:H/",
+            "VC:L/VI:L/VA:H/CR:H/IR:H/AR:M/",
+        ],
+    ],
+    &[&[], &["VC:L/VI:L/VA:L/CR:H/IR:H/AR:H/"]],
+];
+const V4_EQ4_MAXES: &[&[&str]] = &[&["SC:H/SI:S/SA:S/"], &["SC:H/SI:H/SA:H/"], &["SC:L/SI:L/SA:L/"]];
+const V4_EQ5_MAXES: &[&[&str]] = &[&["E:A/"], &["E:P/"], &["E:U/"]];
+
+/// Depth of every EQ class, in steps of 0.1 between its highest and lowest severity vectors.
+const V4_EQ1_DEPTHS: &[f64] = &[1.0, 4.0, 5.0];
+const V4_EQ2_DEPTHS: &[f64] = &[1.0, 2.0];
+const V4_EQ3_EQ6_DEPTHS: &[&[f64]] = &[&[7.0, 6.0], &[8.0, 8.0], &[0.0, 10.0]];
+const V4_EQ4_DEPTHS: &[f64] = &[6.0, 5.0, 4.0];
+
+/// Severity level of a metric value, 0.0 being the most severe.
+fn v4_level(metric: &str, value: &str) -> f64 {
+    match (metric, value) {
+        ("AV", "N") | ("PR", "N") | ("UI", "N") | ("AC", "L") | ("AT", "N") => 0.0,
+        ("AV", "A") | ("PR", "L") | ("UI", "P") | ("AC", "H") | ("AT", "P") => 0.1,
+        ("AV", "L") | ("PR", "H") | ("UI", "A") => 0.2,
+        ("AV", "P") => 0.3,
+        ("VC", "H") | ("VI", "H") | ("VA", "H") => 0.0,
+        ("VC", "L") | ("VI", "L") | ("VA", "L") => 0.1,
+        ("VC", "N") | ("VI", "N") | ("VA", "N") => 0.2,
+        ("SI", "S") | ("SA", "S") => 0.0,
+        ("SC", "H") | ("SI", "H") | ("SA", "H") => 0.1,
+        ("SC", "L") | ("SI", "L") | ("SA", "L") => 0.2,
+        ("SC", "N") | ("SI", "N") | ("SA", "N") => 0.3,
+        ("CR", "H") | ("IR", "H") | ("AR", "H") => 0.0,
+        ("CR", "M") | ("IR", "M") | ("AR", "M") => 0.1,
+        ("CR", "L") | ("IR", "L") | ("AR", "L") => 0.2,
+        _ => 0.0,
+    }
+}
+
+fn v4_macro_vector_score(eqs: [usize; 6]) -> Option<f64> {
+    let key = eqs.iter().map(|eq| eq.to_string()).collect::<String>();
+    V4_MACRO_VECTOR_SCORES
+        .binary_search_by(|(macro_vector, _)| (*macro_vector).cmp(key.as_str()))
+        .ok()
+        .map(|index| V4_MACRO_VECTOR_SCORES[index].1)
+}
+
+/// Value of `metric` in a `metric:value/` part of a vector.
+fn v4_part_value<'a>(part: &'a str, metric: &str) -> Option<&'a str> {
+    part.split('/')
+        .filter_map(|pair| pair.split_once(':'))
+        .find(|(name, _)| *name == metric)
+        .map(|(_, value)| value)
+}
+
+/// The metrics a CVSS v4.0 score takes into account, named CVSS-B, CVSS-BT and CVSS-BTE by the specification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum V4Nomenclature {
+    Base,
+    BaseThreat,
+    BaseThreatEnvironmental,
+}
+
+/// A CVSS v4.0 vector, e.g. `CVSS:4.0/AV:N/AC:L/AT:N/PR:N/UI:N/VC:H/VI:H/VA:H/SC:N/SI:N/SA:N`.
+///
+/// Scored as the FIRST reference calculator does: the score of its macro vector, lowered by
+/// how far the vector is from the most severe vectors of that macro vector.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CvssV4 {
+    metrics: Vec<(&'static str, String)>,
+}
+
+impl CvssV4 {
+    /// Value of `metric`, its "not defined" default when absent from the vector.
+    pub fn metric(&self, metric: &str) -> Option<&str> {
+        self.metrics
+            .iter()
+            .find(|(name, _)| *name == metric)
+            .map(|(_, value)| value.as_str())
+            .or_else(|| {
+                V4_METRICS
+                    .iter()
+                    .find(|(name, _, _)| *name == metric)
+                    .map(|(_, values, _)| values[0])
+            })
+    }
+
+    /// Value used for scoring: the modified metric when defined, otherwise the base one.
+    fn effective(&self, metric: &str) -> &str {
+        match self.metric(&format!("M{}", metric)) {
+            Some(value) if value != "X" => value,
+            _ => self.metric(metric).unwrap_or("X"),
+        }
+    }
+
+    /// Value of `metric` as scored under `nomenclature`, the metrics left out or not defined
+    /// taking their worst case: `E:A` and `CR/IR/AR:H`.
+    fn value(&self, metric: &str, nomenclature: V4Nomenclature) -> &str {
+        match metric {
+            "E" => match self.metric("E") {
+                Some(value) if value != "X" && nomenclature != V4Nomenclature::Base => value,
+                _ => "A",
+            },
+            "CR" | "IR" | "AR" => match self.metric(metric) {
+                Some(value) if value != "X" && nomenclature == V4Nomenclature::BaseThreatEnvironmental => value,
+                _ => "H",
+            },
+            _ if nomenclature == V4Nomenclature::BaseThreatEnvironmental => self.effective(metric),
+            _ => self.metric(metric).unwrap_or("X"),
+        }
+    }
+
+    fn equivalence_classes(&self, nomenclature: V4Nomenclature) -> [usize; 6] {
+        let value = |metric: &str| self.value(metric, nomenclature);
+
+        let (av, pr, ui) = (value("AV"), value("PR"), value("UI"));
+        let eq1 = if av == "N" && pr == "N" && ui == "N" {
+            0
+        } else if (av == "N" || pr == "N" || ui == "N") && av != "P" {
+            1
+        } else {
+            2
+        };
+
+        let eq2 = if value("AC") == "L" && value("AT") == "N" { 0 } else { 1 };
+
+        let (vc, vi, va) = (value("VC"), value("VI"), value("VA"));
+        let eq3 = if vc == "H" && vi == "H" {
+            0
+        } else if vc == "H" || vi == "H" || va == "H" {
+            1
+        } else {
+            2
+        };
+
+        let eq4 = if value("SI") == "S" || value("SA") == "S" {
+            0
+        } else if value("SC") == "H" || value("SI") == "H" || value("SA") == "H" {
+            1
+        } else {
+            2
+        };
+
+        let eq5 = match value("E") {
+            "P" => 1,
+            "U" => 2,
+            _ => 0,
+        };
+
+        let eq6 = if (value("CR") == "H" && vc == "H") || (value("IR") == "H" && vi == "H") || (value("AR") == "H" && va == "H") {
+            0
+        } else {
+            1
+        };
+
+        [eq1, eq2, eq3, eq4, eq5, eq6]
+    }
+
+    /// The EQ1..EQ6 equivalence classes that index the v4.0 score lookup table.
+    pub fn macro_vector(&self) -> String {
+        self.equivalence_classes(V4Nomenclature::BaseThreatEnvironmental)
+            .iter()
+            .map(|eq| eq.to_string())
+            .collect()
+    }
+
+    fn score(&self, nomenclature: V4Nomenclature) -> f64 {
+        let value = |metric: &str| self.value(metric, nomenclature);
+
+        if ["VC", "VI", "VA", "SC", "SI", "SA"].iter().all(|metric| value(metric) == "N") {
+            return 0.0;
+        }
+
+        let eqs = self.equivalence_classes(nomenclature);
+        let score = v4_macro_vector_score(eqs).expect("cvss: every v4.0 macro vector has a score");
+        let lower = |changes: &[usize]| {
+            let mut lower = eqs;
+            for eq in changes {
+                lower[*eq] += 1;
+            }
+            v4_macro_vector_score(lower)
+        };
+
+        let eq3_eq6_lower = match (eqs[2], eqs[5]) {
+            // Both 01 and 10 are one step lower than 00, the higher one is taken.
+            (0, 0) => match (lower(&[5]), lower(&[2])) {
+                (Some(left), Some(right)) => Some(left.max(right)),
+                (left, right) => left.or(right),
+            },
+            (1, 0) => lower(&[5]),
+            (0, 1) | (1, 1) => lower(&[2]),
+            _ => lower(&[2, 5]),
+        };
+
+        // The first highest severity vector of the macro vector that is at least as severe on every metric.
+        let maxes = V4_EQ1_MAXES[eqs[0]].iter().flat_map(|eq1| {
+            V4_EQ2_MAXES[eqs[1]].iter().flat_map(move |eq2| {
+                V4_EQ3_EQ6_MAXES[eqs[2]][eqs[5]].iter().flat_map(move |eq3_eq6| {
+                    V4_EQ4_MAXES[eqs[3]].iter().flat_map(move |eq4| {
+                        V4_EQ5_MAXES[eqs[4]].iter().map(move |eq5| format!("{}{}{}{}{}", eq1, eq2, eq3_eq6, eq4, eq5))
+                    })
+                })
+            })
+        });
+
+        let metrics = ["AV", "PR", "UI", "AC", "AT", "VC", "VI", "VA", "SC", "SI", "SA", "CR", "IR", "AR"];
+        let mut distances = [0.0; 14];
+        for max in maxes {
+            for (index, metric) in metrics.iter().enumerate() {
+                let max_value = v4_part_value(&max, metric).unwrap_or_default();
+                distances[index] = v4_level(metric, value(metric)) - v4_level(metric, max_value);
+            }
+            if distances.iter().all(|distance| *distance >= 0.0) {
+                break;
+            }
+        }
+        let distance = |from: usize, to: usize| distances[from..to].iter().sum::<f64>();
+
+        let step = 0.1;
+        let classes = [
+            (lower(&[0]), distance(0, 3), V4_EQ1_DEPTHS[eqs[0]]),
+            (lower(&[1]), distance(3, 5), V4_EQ2_DEPTHS[eqs[1]]),
+            (eq3_eq6_lower, distance(5, 8) + distance(11, 14), V4_EQ3_EQ6_DEPTHS[eqs[2]][eqs[5]]),
+            (lower(&[3]), distance(8, 11), V4_EQ4_DEPTHS[eqs[3]]),
+            // EQ5 has a single metric, any vector is its highest severity one.
+            (lower(&[4]), 0.0, 1.0),
+        ];
+
+        let mut existing = 0;
+        let mut normalized = 0.0;
+        for (lower, distance, depth) in classes.iter() {
+            if let Some(lower) = lower {
+                existing += 1;
+                normalized += (score - lower) * (distance / (depth * step));
+            }
+        }
+
+        let mean = if existing == 0 { 0.0 } else { normalized / existing as f64 };
+        let score = (score - mean).clamp(0.0, 10.0);
+
+        // The reference calculator adds an epsilon against floating point errors before rounding.
+        ((score + 1e-6) * 10.0).round() / 10.0
+    }
+
+    /// CVSS-B, the score of the base metrics alone.
+    pub fn base_score(&self) -> f64 {
+        self.score(V4Nomenclature::Base)
+    }
+
+    /// CVSS-BT, the base score adjusted by the exploit maturity.
+    pub fn threat_score(&self) -> f64 {
+        self.score(V4Nomenclature::BaseThreat)
+    }
+
+    /// CVSS-BTE, with the exploit maturity, the security requirements and the modified metrics.
+    pub fn environmental_score(&self) -> f64 {
+        self.score(V4Nomenclature::BaseThreatEnvironmental)
+    }
+
+    pub fn severity(&self) -> Severity {
+        Severity::from_score(self.base_score())
+    }
+}
+
+impl FromStr for CvssV4 {
+    type Err = Error;
+
+    fn from_str(vector: &str) -> Result<Self, Self::Err> {
+        let vector = vector.trim();
+        let rest = vector
+            .strip_prefix("CVSS:4.0/")
+            .ok_or_else(|| Error::Cvss(format!("{} is not a CVSS v4.0 vector", vector)))?;
+
+        let pairs = metrics(rest)?;
+        let known = V4_METRICS.iter().map(|(name, _, _)| *name).collect::<Vec<&str>>();
+        reject_unknown(&pairs, &known)?;
+
+        let mut parsed = Vec::new();
+        for (name, values, mandatory) in V4_METRICS {
+            match pairs.iter().find(|(metric, _)| metric == name) {
+                Some((_, value)) if values.contains(value) => parsed.push((*name, value.to_string())),
+                Some((_, value)) => return Err(Error::Cvss(format!("invalid value {} for {}", value, name))),
+                None if *mandatory => return Err(Error::Cvss(format!("missing metric {}", name))),
+                None => {}
+            }
+        }
+
+        Ok(CvssV4 { metrics: parsed })
+    }
+}
+
+impl fmt::Display for CvssV4 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CVSS:4.0")?;
+
+        for (name, value) in &self.metrics {
+            if value != "X" {
+                write!(f, "/{}:{}", name, value)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Any supported CVSS vector, told apart by its `CVSS:x.y/` prefix (v2 has none).
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cvss {
+    V2(CvssV2),
+    V3(CvssV3),
+    V4(CvssV4),
+}
+
+impl Cvss {
+    pub fn base_score(&self) -> f64 {
+        match self {
+            Cvss::V2(cvss) => cvss.base_score(),
+            Cvss::V3(cvss) => cvss.base_score(),
+            Cvss::V4(cvss) => cvss.base_score(),
+        }
+    }
+
+    /// Temporal score, the threat score (CVSS-BT) for v4.0.
+    pub fn temporal_score(&self) -> f64 {
+        match self {
+            Cvss::V2(cvss) => cvss.temporal_score(),
+            Cvss::V3(cvss) => cvss.temporal_score(),
+            Cvss::V4(cvss) => cvss.threat_score(),
+        }
+    }
+
+    pub fn environmental_score(&self) -> f64 {
+        match self {
+            Cvss::V2(cvss) => cvss.environmental_score(),
+            Cvss::V3(cvss) => cvss.environmental_score(),
+            Cvss::V4(cvss) => cvss.environmental_score(),
+        }
+    }
+
+    pub fn severity(&self) -> Severity {
+        match self {
+            Cvss::V2(cvss) => cvss.severity(),
+            Cvss::V3(cvss) => cvss.severity(),
+            Cvss::V4(cvss) => cvss.severity(),
+        }
+    }
+}
+
+impl FromStr for Cvss {
+    type Err = Error;
+
+    fn from_str(vector: &str) -> Result<Self, Self::Err> {
+        let vector = vector.trim();
+
+        if vector.starts_with("CVSS:4.0/") {
+            vector.parse().map(Cvss::V4)
+        } else if vector.starts_with("CVSS:3.") {
+            vector.parse().map(Cvss::V3)
+        } else {
+            vector.parse().map(Cvss::V2)
+        }
+    }
+}
+
+impl fmt::Display for Cvss {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Cvss::V2(cvss) => cvss.fmt(f),
+            Cvss::V3(cvss) => cvss.fmt(f),
+            Cvss::V4(cvss) => cvss.fmt(f),
+        }
+    }
+}
+
+/// Normalized vector and every score of a [`Cvss`], for display.
+#[derive(Debug, Clone, Serialize)]
+pub struct CvssScores {
+    pub vector: String,
+    pub base_score: f64,
+    pub temporal_score: f64,
+    pub environmental_score: f64,
+    pub severity: Severity,
+    pub macro_vector: Option<String>,
+}
+
+impl Cvss {
+    pub fn scores(&self) -> CvssScores {
+        CvssScores {
+            vector: self.to_string(),
+            base_score: self.base_score(),
+            temporal_score: self.temporal_score(),
+            environmental_score: self.environmental_score(),
+            severity: self.severity(),
+            macro_vector: match self {
+                Cvss::V4(cvss) => Some(cvss.macro_vector()),
+                _ => None,
+            },
+        }
+    }
+}
+
+impl CvssScores {
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "{} base: {:.1} temporal: {:.1} environmental: {:.1} ({})",
+            self.vector,
+            self.base_score,
+            self.temporal_score,
+            self.environmental_score,
+            self.severity,
+        );
+
+        if let Some(macro_vector) = &self.macro_vector {
+            summary.push_str(&format!(" macro vector: {}", macro_vector));
+        }
+
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scores(vector: &str) -> (f64, f64, f64, Severity) {
+        let cvss = vector.parse::<Cvss>().unwrap();
+        (cvss.base_score(), cvss.temporal_score(), cvss.environmental_score(), cvss.severity())
+    }
+
+    #[test]
+    fn scores_v2_vectors() {
+        assert_eq!(scores("AV:N/AC:L/Au:N/C:P/I:P/A:P"), (7.5, 7.5, 7.5, Severity::High));
+        assert_eq!(scores("(AV:N/AC:M/Au:N/C:N/I:P/A:N)"), (4.3, 4.3, 4.3, Severity::Medium));
+        assert_eq!(scores("AV:L/AC:L/Au:N/C:C/I:C/A:C/E:F/RL:OF/RC:C").1, 6.0);
+        assert_eq!(
+            scores("AV:N/AC:L/Au:N/C:N/I:N/A:C/E:F/RL:OF/RC:C/CDP:H/TD:H/CR:M/IR:M/AR:H").2,
+            9.2
+        );
+    }
+
+    #[test]
+    fn scores_v3_vectors() {
+        assert_eq!(scores("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H"), (9.8, 9.8, 9.8, Severity::Critical));
+        assert_eq!(scores("CVSS:3.1/AV:N/AC:L/PR:N/UI:R/S:C/C:L/I:L/A:N").0, 6.1);
+        assert_eq!(scores("CVSS:3.0/AV:N/AC:L/PR:N/UI:N/S:C/C:H/I:H/A:H").0, 10.0);
+        assert_eq!(scores("CVSS:3.1/AV:L/AC:L/PR:L/UI:N/S:U/C:H/I:N/A:N/E:P/RL:O/RC:C").1, 5.0);
+        assert_eq!(scores("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:N/I:N/A:N"), (0.0, 0.0, 0.0, Severity::None));
+        assert_eq!(scores("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H/CR:L/IR:L/AR:L/MAV:L").2, 6.6);
+    }
+
+    #[test]
+    fn scores_v4_vectors() {
+        let base = |vector: &str| scores(vector).0;
+
+        assert_eq!(base("CVSS:4.0/AV:N/AC:L/AT:N/PR:N/UI:N/VC:H/VI:H/VA:H/SC:H/SI:H/SA:H"), 10.0);
+        assert_eq!(base("CVSS:4.0/AV:N/AC:L/AT:N/PR:N/UI:N/VC:H/VI:H/VA:H/SC:N/SI:N/SA:N"), 9.3);
+        assert_eq!(base("CVSS:4.0/AV:N/AC:H/AT:N/PR:N/UI:N/VC:H/VI:H/VA:H/SC:N/SI:N/SA:N"), 9.2);
+        assert_eq!(base("CVSS:4.0/AV:N/AC:L/AT:N/PR:L/UI:N/VC:H/VI:H/VA:H/SC:N/SI:N/SA:N"), 8.7);
+        assert_eq!(base("CVSS:4.0/AV:L/AC:L/AT:N/PR:L/UI:N/VC:H/VI:H/VA:H/SC:N/SI:N/SA:N"), 8.5);
+        assert_eq!(base("CVSS:4.0/AV:N/AC:L/AT:N/PR:N/UI:N/VC:L/VI:N/VA:N/SC:N/SI:N/SA:N"), 6.9);
+        assert_eq!(base("CVSS:4.0/AV:N/AC:L/AT:N/PR:N/UI:N/VC:N/VI:N/VA:N/SC:N/SI:N/SA:N"), 0.0);
+    }
+
+    #[test]
+    fn v4_threat_and_environmental_metrics_only_change_their_scores() {
+        let cvss = "CVSS:4.0/AV:N/AC:L/AT:N/PR:N/UI:N/VC:H/VI:H/VA:H/SC:N/SI:N/SA:N/E:U".parse::<Cvss>().unwrap();
+        assert_eq!((cvss.base_score(), cvss.temporal_score()), (9.3, 8.1));
+        assert_eq!(cvss.severity(), Severity::Critical);
+
+        let cvss = "CVSS:4.0/AV:N/AC:L/AT:N/PR:N/UI:N/VC:H/VI:H/VA:H/SC:N/SI:N/SA:N/CR:L/IR:L/AR:L/MSI:S"
+            .parse::<CvssV4>()
+            .unwrap();
+        assert_eq!(cvss.macro_vector(), "000001");
+        assert_eq!((cvss.base_score(), cvss.threat_score(), cvss.environmental_score()), (9.3, 9.3, 9.8));
+    }
+
+    #[test]
+    fn every_v4_macro_vector_has_a_score() {
+        assert_eq!(V4_MACRO_VECTOR_SCORES.len(), 270);
+        assert!(V4_MACRO_VECTOR_SCORES.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        assert_eq!(v4_macro_vector_score([2, 1, 2, 2, 2, 1]), Some(0.1));
+        // EQ3 2 always comes with EQ6 1, no vector being High without being High.
+        assert_eq!(v4_macro_vector_score([0, 0, 2, 0, 0, 0]), None);
+    }
+
+    #[test]
+    fn vectors_round_trip() {
+        for vector in [
+            "AV:N/AC:L/Au:N/C:P/I:P/A:P/E:F/CDP:LM",
+            "CVSS:3.0/AV:A/AC:H/PR:L/UI:R/S:C/C:L/I:N/A:H/E:U/MAV:N/MS:U",
+            "CVSS:4.0/AV:N/AC:L/AT:P/PR:N/UI:A/VC:H/VI:L/VA:N/SC:N/SI:H/SA:L/E:P/MSI:S/U:Amber",
+        ] {
+            assert_eq!(vector.parse::<Cvss>().unwrap().to_string(), vector);
+        }
+
+        // "Not defined" metrics are dropped.
+        let cvss = "CVSS:4.0/AV:N/AC:L/AT:N/PR:N/UI:N/VC:H/VI:H/VA:H/SC:N/SI:N/SA:N/E:X/CR:X".parse::<Cvss>().unwrap();
+        assert_eq!(cvss.to_string(), "CVSS:4.0/AV:N/AC:L/AT:N/PR:N/UI:N/VC:H/VI:H/VA:H/SC:N/SI:N/SA:N");
+    }
+
+    #[test]
+    fn rejects_malformed_vectors() {
+        for vector in [
+            "AV:N/AC:L/Au:N/C:P/I:P",
+            "CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:X",
+            "CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H/AV:L",
+            "CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H/XX:Y",
+            "CVSS:4.0/AV:N/AC:L/AT:N/PR:N/UI:N/VC:H/VI:H/VA:H/SC:N/SI:S/SA:N",
+            "CVSS:2.0/AV:N",
+        ] {
+            assert!(vector.parse::<Cvss>().is_err(), "{}", vector);
+        }
+    }
+
+    #[test]
+    fn severities() {
+        assert_eq!(Severity::from_score(0.0), Severity::None);
+        assert_eq!(Severity::from_score(3.9), Severity::Low);
+        assert_eq!(Severity::from_score(7.0), Severity::High);
+        assert_eq!(Severity::from_v2_score(0.0), Severity::Low);
+        assert_eq!(Severity::from_v2_score(9.5), Severity::High);
+        assert_eq!("CRITICAL".parse::<Severity>().unwrap().min_score(), 9.0);
+    }
+}
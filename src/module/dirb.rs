@@ -0,0 +1,351 @@
+use crate::module::crawler::HttpSettings;
+use crate::module::error::Error;
+use crate::module::headers::HeaderRotation;
+use futures::stream::{ self, StreamExt };
+use rand::{ distributions::Alphanumeric, Rng };
+use reqwest::{ header, redirect, Client };
+use serde::Serialize;
+use url::Url;
+
+/// Options of a dirb-style content discovery run.
+#[derive(Debug, Clone)]
+pub struct DirbOptions {
+    pub extensions: Vec<String>,
+    pub recursive: bool,
+    pub max_depth: usize,
+    pub concurrency: usize,
+    pub filter: ResponseFilter,
+    pub calibrate: bool,
+}
+
+impl Default for DirbOptions {
+    fn default() -> Self {
+        DirbOptions {
+            extensions: Vec::new(),
+            recursive: false,
+            max_depth: 3,
+            concurrency: 20,
+            filter: ResponseFilter::default(),
+            calibrate: true,
+        }
+    }
+}
+
+/// Decides which responses are reported. Empty `status` means "any status not hidden".
+#[derive(Debug, Clone)]
+pub struct ResponseFilter {
+    pub status: Vec<u16>,
+    pub hide_status: Vec<u16>,
+    pub hide_size: Vec<usize>,
+    pub hide_words: Vec<usize>,
+}
+
+impl Default for ResponseFilter {
+    fn default() -> Self {
+        ResponseFilter {
+            status: Vec::new(),
+            hide_status: vec![404],
+            hide_size: Vec::new(),
+            hide_words: Vec::new(),
+        }
+    }
+}
+
+impl ResponseFilter {
+    fn accepts(&self, hit: &DirbHit) -> bool {
+        if !self.status.is_empty() && !self.status.contains(&hit.status) {
+            return false;
+        }
+
+        !self.hide_status.contains(&hit.status)
+            && !self.hide_size.contains(&hit.size)
+            && !self.hide_words.contains(&hit.words)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DirbHit {
+    pub url: String,
+    pub status: u16,
+    pub size: usize,
+    pub words: usize,
+    pub redirect: Option<String>,
+}
+
+/// Signature of the answer the server gives for paths that do not exist.
+#[derive(Debug, Clone, PartialEq)]
+struct Baseline {
+    status: u16,
+    size: usize,
+    words: usize,
+}
+
+impl Baseline {
+    fn matches(&self, hit: &DirbHit) -> bool {
+        hit.status == self.status && (hit.size == self.size || hit.words == self.words)
+    }
+}
+
+pub struct DirBuster {
+    client: Client,
+    headers: HeaderRotation,
+    base_url: Url,
+    wordlist: Vec<String>,
+    options: DirbOptions,
+}
+
+impl DirBuster {
+    pub fn new(
+        settings: &HttpSettings,
+        base_url: &str,
+        wordlist: Vec<String>,
+        options: DirbOptions,
+    ) -> Result<Self, Error> {
+        let mut base_url = Url::parse(base_url)
+            .map_err(|err| Error::Internal(format!("dirb: {} : {}", base_url, err)))?;
+
+        if !base_url.path().ends_with('/') {
+            let path = format!("{}/", base_url.path());
+            base_url.set_path(&path);
+        }
+
+        let client = settings
+            .client_builder()
+            .redirect(redirect::Policy::none())
+            .build()?;
+
+        Ok(DirBuster {
+            client,
+            headers: settings.headers.clone(),
+            base_url,
+            wordlist,
+            options,
+        })
+    }
+
+    pub async fn run(&self) -> Vec<DirbHit> {
+        let mut hits = Vec::new();
+        let mut directories = vec![(self.base_url.clone(), 0)];
+
+        while let Some((directory, depth)) = directories.pop() {
+            log::info!("dirb: scanning {}", directory);
+
+            let baselines = if self.options.calibrate {
+                self.calibrate(&directory).await
+            } else {
+                Vec::new()
+            };
+
+            let found = stream::iter(self.candidates(&directory))
+                .map(|url| self.probe(url))
+                .buffer_unordered(self.options.concurrency.max(1))
+                .filter_map(|hit| async move { hit })
+                .filter(|hit| {
+                    let keep = !baselines.iter().any(|baseline| baseline.matches(hit))
+                        && self.options.filter.accepts(hit);
+                    async move { keep }
+                })
+                .collect::<Vec<DirbHit>>()
+                .await;
+
+            for hit in found {
+                log::info!("dirb: found {} [{}]", hit.url, hit.status);
+
+                if self.options.recursive && depth < self.options.max_depth {
+                    if let Some(subdirectory) = self.as_directory(&hit) {
+                        directories.push((subdirectory, depth + 1));
+                    }
+                }
+
+                hits.push(hit);
+            }
+        }
+
+        hits
+    }
+
+    fn candidates(&self, directory: &Url) -> Vec<Url> {
+        let mut urls = Vec::new();
+
+        for word in &self.wordlist {
+            let word = word.trim_start_matches('/');
+            if let Ok(url) = directory.join(word) {
+                urls.push(url);
+            }
+
+            if word.ends_with('/') {
+                continue;
+            }
+
+            for extension in &self.options.extensions {
+                let extension = extension.trim_start_matches('.');
+                if let Ok(url) = directory.join(&format!("{}.{}", word, extension)) {
+                    urls.push(url);
+                }
+            }
+        }
+
+        urls
+    }
+
+    /// Requests random paths to learn what a "not found" answer looks like on this directory.
+    async fn calibrate(&self, directory: &Url) -> Vec<Baseline> {
+        let random: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(16)
+            .map(char::from)
+            .collect();
+
+        let mut paths = vec![random.clone(), format!("{}/", random)];
+        for extension in &self.options.extensions {
+            paths.push(format!("{}.{}", random, extension.trim_start_matches('.')));
+        }
+
+        let mut baselines = Vec::new();
+        for path in paths {
+            let hit = match directory.join(&path) {
+                Ok(url) => self.probe(url).await,
+                Err(_) => None,
+            };
+
+            if let Some(hit) = hit {
+                let baseline = Baseline {
+                    status: hit.status,
+                    size: hit.size,
+                    words: hit.words,
+                };
+                log::debug!("dirb: calibrated {} -> {:?}", directory, baseline);
+
+                if !baselines.contains(&baseline) {
+                    baselines.push(baseline);
+                }
+            }
+        }
+
+        baselines
+    }
+
+    async fn probe(&self, url: Url) -> Option<DirbHit> {
+        let res = self
+            .client
+            .get(url.clone())
+            .headers(self.headers.for_url(&url))
+            .send()
+            .await
+            .map_err(|err| log::debug!("dirb: {} : {}", url, err))
+            .ok()?;
+
+        let status = res.status().as_u16();
+        let redirect = res
+            .headers()
+            .get(header::LOCATION)
+            .and_then(|location| location.to_str().ok())
+            .and_then(|location| url.join(location).ok())
+            .map(|location| location.to_string());
+        let body = res.bytes().await.ok()?;
+        let words = String::from_utf8_lossy(&body).split_whitespace().count();
+
+        Some(DirbHit {
+            url: url.to_string(),
+            status,
+            size: body.len(),
+            words,
+            redirect,
+        })
+    }
+
+    fn as_directory(&self, hit: &DirbHit) -> Option<Url> {
+        if hit.url.ends_with('/') && hit.status != 404 {
+            return Url::parse(&hit.url).ok();
+        }
+
+        let directory = format!("{}/", hit.url);
+        match &hit.redirect {
+            Some(redirect) if *redirect == directory => Url::parse(&directory).ok(),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hit(status: u16, size: usize, words: usize) -> DirbHit {
+        DirbHit { url: "http://target/x".to_string(), status, size, words, redirect: None }
+    }
+
+    fn buster(extensions: &[&str]) -> DirBuster {
+        let options = DirbOptions {
+            extensions: extensions.iter().map(|extension| extension.to_string()).collect(),
+            ..DirbOptions::default()
+        };
+        let wordlist = vec!["admin".to_string(), "/backup/".to_string()];
+        DirBuster::new(&HttpSettings::default(), "http://target/app", wordlist, options).unwrap()
+    }
+
+    #[test]
+    fn baseline_matches_same_status_and_size_or_words() {
+        let baseline = Baseline { status: 200, size: 1200, words: 80 };
+
+        assert!(baseline.matches(&hit(200, 1200, 80)));
+        // Pages echoing the requested path differ in size but not in words.
+        assert!(baseline.matches(&hit(200, 1216, 80)));
+        assert!(baseline.matches(&hit(200, 1200, 81)));
+        assert!(!baseline.matches(&hit(200, 1216, 81)));
+        assert!(!baseline.matches(&hit(403, 1200, 80)));
+    }
+
+    #[test]
+    fn filter_hides_then_keeps_listed_statuses() {
+        let filter = ResponseFilter::default();
+        assert!(filter.accepts(&hit(200, 10, 2)));
+        assert!(!filter.accepts(&hit(404, 10, 2)));
+
+        let filter = ResponseFilter { status: vec![200, 301], hide_size: vec![10], ..ResponseFilter::default() };
+        assert!(filter.accepts(&hit(301, 0, 0)));
+        assert!(!filter.accepts(&hit(403, 0, 0)));
+        assert!(!filter.accepts(&hit(200, 10, 2)));
+
+        let filter = ResponseFilter { hide_words: vec![2], ..ResponseFilter::default() };
+        assert!(!filter.accepts(&hit(200, 10, 2)));
+    }
+
+    #[test]
+    fn candidates_append_extensions_to_files_only() {
+        let buster = buster(&["php", ".bak"]);
+        let urls = buster
+            .candidates(&buster.base_url)
+            .iter()
+            .map(|url| url.to_string())
+            .collect::<Vec<String>>();
+
+        assert_eq!(
+            urls,
+            vec![
+                "http://target/app/admin",
+                "http://target/app/admin.php",
+                "http://target/app/admin.bak",
+                "http://target/app/backup/",
+            ]
+        );
+    }
+
+    #[test]
+    fn directories_are_found_by_slash_or_redirect() {
+        let buster = buster(&[]);
+
+        let listing = DirbHit { url: "http://target/app/backup/".to_string(), ..hit(403, 0, 0) };
+        assert_eq!(buster.as_directory(&listing).unwrap().as_str(), "http://target/app/backup/");
+
+        let redirect = DirbHit {
+            url: "http://target/app/admin".to_string(),
+            redirect: Some("http://target/app/admin/".to_string()),
+            ..hit(301, 0, 0)
+        };
+        assert_eq!(buster.as_directory(&redirect).unwrap().as_str(), "http://target/app/admin/");
+
+        let login = DirbHit { redirect: Some("http://target/login".to_string()), ..redirect };
+        assert!(buster.as_directory(&login).is_none());
+    }
+}
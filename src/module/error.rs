@@ -2,7 +2,7 @@ use thiserror::Error;
 
 #[derive(Error, Debug, Clone)]
 pub enum Error {
-    #[error("Internal")]
+    #[error("Internal : {0}")]
     Internal(String),
     #[error("Spider is not valid! : {0}")]
     InvalidSpider(String),
@@ -11,14 +11,34 @@ pub enum Error {
     #[error("WebDriver : {0}")]
     WebDriver(String),
     #[error("tokio join error : {0}")]
-    TokioJoinError(String),
+    TokioJoin(String),
     #[error("{0} : Invalid HTTP response")]
     InvalidHttpResponse(String),
+    #[error("TLS : {0}")]
+    Tls(String),
+    #[error("Database : {0}")]
+    Database(String),
+    #[error("CVSS : {0}")]
+    Cvss(String),
+    #[error("CPE : {0}")]
+    Cpe(String),
+    #[error("Session : {0}")]
+    Session(String),
+    #[error("Cache : {0}")]
+    Cache(String),
+    #[error("Proxy : {0}")]
+    Proxy(String),
+    #[error("Config : {0}")]
+    Config(String),
+    #[error("NVD feed : {0}")]
+    Feed(String),
+    #[error("Request : {0}")]
+    Request(String),
 }
 
 impl std::convert::From<tokio::task::JoinError> for Error {
     fn from(err: tokio::task::JoinError) -> Self {
-        Error::TokioJoinError(err.to_string())
+        Error::TokioJoin(err.to_string())
     }
 }
 
@@ -39,3 +59,9 @@ impl std::convert::From<fantoccini::error::NewSessionError> for Error {
         Error::WebDriver(err.to_string())
     }
 }
+
+impl std::convert::From<rusqlite::Error> for Error {
+    fn from(err: rusqlite::Error) -> Self {
+        Error::Database(err.to_string())
+    }
+}
@@ -0,0 +1,373 @@
+use crate::module::cache::HttpCache;
+use crate::module::error::Error;
+use crate::module::render::{ perform, Action, ACTIONS_META };
+use crate::module::request::Request;
+use crate::module::session::{ Response, Session };
+use crate::module::webdriver::WebDriverPool;
+use fantoccini::cookies::Cookie;
+use reqwest::Method;
+use select::{ document::Document, node::Node, predicate::{ Name, Predicate } };
+use serde::{ Deserialize, Serialize };
+use std::fmt;
+use std::sync::Arc;
+use url::Url;
+
+/// Meta of a request holding the fetch mode it needs, instead of the spider's.
+pub const FETCH_META: &str = "fetch";
+/// Meta of a request holding the selectors its static page must match in auto mode, instead of the spider's.
+pub const EXPECT_META: &str = "expect";
+
+/// Pages of single page apps hold scripts and next to no text until rendered.
+const MIN_STATIC_TEXT: usize = 100;
+
+/// How the pages of a spider are fetched.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FetchMode {
+    /// Over HTTP only.
+    Static,
+    /// By the browser, the page source being read once the actions ran.
+    Rendered,
+    /// Over HTTP, then by the browser if the page lacks the expected content.
+    Auto,
+}
+
+impl FetchMode {
+    pub fn from_name(name: &str) -> Option<FetchMode> {
+        match name.to_lowercase().as_str() {
+            "static" => Some(FetchMode::Static),
+            "rendered" | "render" => Some(FetchMode::Rendered),
+            "auto" => Some(FetchMode::Auto),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for FetchMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FetchMode::Static => write!(f, "static"),
+            FetchMode::Rendered => write!(f, "rendered"),
+            FetchMode::Auto => write!(f, "auto"),
+        }
+    }
+}
+
+/// Fetches the pages of a spider over HTTP with its session, in a browser, or the latter only when needed.
+///
+/// The browser gets the session cookies but not its other headers, and only GETs are ever rendered.
+pub struct Fetcher {
+    mode: FetchMode,
+    webdriver: Option<Arc<WebDriverPool>>,
+    expected: Vec<Selector>,
+    actions: Vec<Action>,
+    /// Rendered pages, apart from the static ones cached by the session.
+    cache: Option<HttpCache>,
+}
+
+impl Fetcher {
+    pub fn new(mode: FetchMode) -> Self {
+        Fetcher {
+            mode,
+            webdriver: None,
+            expected: Vec::new(),
+            actions: Vec::new(),
+            cache: None,
+        }
+    }
+
+    pub fn with_mode(mut self, mode: FetchMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn with_webdriver(mut self, webdriver: Arc<WebDriverPool>) -> Self {
+        self.webdriver = Some(webdriver);
+        self
+    }
+
+    /// CSS selectors a static page must all match not to be rendered in auto mode.
+    ///
+    /// Without any, the pages holding scripts but next to no text are rendered.
+    pub fn with_expected(mut self, selectors: &[String]) -> Result<Self, Error> {
+        self.expected = selectors.iter().map(|selector| Selector::parse(selector)).collect::<Result<_, _>>()?;
+        Ok(self)
+    }
+
+    /// Runs `actions` on every rendered page before reading its source.
+    pub fn with_actions(mut self, actions: Vec<Action>) -> Self {
+        self.actions = actions;
+        self
+    }
+
+    /// Records the rendered pages in the `rendered` directory of `cache`, and serves them from there.
+    pub fn with_cache(mut self, cache: &HttpCache) -> Self {
+        self.cache = Some(cache.subdirectory("rendered"));
+        self
+    }
+
+    fn is_offline(&self) -> bool {
+        self.cache.as_ref().is_some_and(|cache| cache.is_offline())
+    }
+
+    /// Opens a first browser session if pages may be rendered, failing early if the WebDriver is unreachable.
+    pub async fn open(&self) -> Result<(), Error> {
+        if self.mode == FetchMode::Static || self.is_offline() {
+            return Ok(());
+        }
+
+        self.webdriver()?.checkout().await.map(|_| ())
+    }
+
+    /// Fetches `request` with the mode it asks for, the fetcher's otherwise.
+    pub async fn fetch(&self, session: &Session, request: &Request) -> Result<Response, Error> {
+        let mode = request.meta::<FetchMode>(FETCH_META).unwrap_or(self.mode);
+        if mode == FetchMode::Static || request.method != Method::GET {
+            return session.fetch(request).await;
+        }
+        if mode == FetchMode::Rendered {
+            return self.render(session, request).await;
+        }
+
+        let response = session.fetch(request).await?;
+        if !self.needs_rendering(request, &response)? {
+            return Ok(response);
+        }
+
+        log::info!("fetch: rendering {}", request.url);
+        self.render(session, request).await
+    }
+
+    /// Whether the static `response` to `request` lacks what it should show.
+    fn needs_rendering(&self, request: &Request, response: &Response) -> Result<bool, Error> {
+        if !response.status.is_success() || !response.content_type().unwrap_or_default().contains("html") {
+            return Ok(false);
+        }
+
+        let expected = match request.meta::<Vec<String>>(EXPECT_META) {
+            Some(selectors) => selectors.iter().map(|selector| Selector::parse(selector)).collect::<Result<_, _>>()?,
+            None => self.expected.clone(),
+        };
+
+        let html = response.text();
+        let document = Document::from(html.as_str());
+        if !expected.is_empty() {
+            return Ok(expected.iter().any(|selector| document.select(selector).next().is_none()));
+        }
+
+        let scripts = document.select(Name("script")).count();
+        let text = document.select(Name("body")).map(|body| visible_text(&body)).sum::<usize>();
+        Ok(scripts > 0 && text < MIN_STATIC_TEXT)
+    }
+
+    /// The page of `request` once rendered by the browser.
+    async fn render(&self, session: &Session, request: &Request) -> Result<Response, Error> {
+        if let Some(response) = self.cache.as_ref().and_then(|cache| cache.get(request)) {
+            return Ok(response);
+        }
+        if self.is_offline() {
+            return Err(Error::Cache(format!("{} : not rendered before, offline", request.url)));
+        }
+
+        let webdriver = self.webdriver()?;
+        let actions = request
+            .meta::<Vec<Action>>(ACTIONS_META)
+            .unwrap_or_else(|| self.actions.clone());
+        let mut cookies = match Url::parse(&request.url) {
+            Ok(url) => session.cookies(&url),
+            Err(err) => return Err(Error::Internal(format!("{} : {}", request.url, err))),
+        };
+        cookies.extend(request.cookies.clone());
+
+        // A session dying mid-page is retried once on a new one.
+        let mut retried = false;
+        let (url, html) = loop {
+            let client = webdriver.checkout().await?;
+            let mut loaded = client.goto(&request.url).await.map_err(Error::from);
+
+            // Cookies only go to the site the browser is on, the page is loaded again with them.
+            if loaded.is_ok() && !cookies.is_empty() {
+                for (name, value) in &cookies {
+                    let cookie = Cookie::build(name.clone(), value.clone()).path("/").finish();
+                    if let Err(err) = client.add_cookie(cookie).await {
+                        log::warn!("fetch: {} : cookie {} : {}", request.url, name, err);
+                    }
+                }
+                loaded = client.refresh().await.map_err(Error::from);
+            }
+
+            let source = match loaded {
+                // The page, not the session, is at fault when an action fails.
+                Ok(()) => match perform(&client, &actions).await {
+                    Ok(()) => match (client.current_url().await, client.source().await) {
+                        (Ok(url), Ok(html)) => Ok((url.to_string(), html)),
+                        (Err(err), _) | (_, Err(err)) => Err(Error::from(err)),
+                    },
+                    Err(err) => return Err(err),
+                },
+                Err(err) => Err(err),
+            };
+
+            match source {
+                Ok(page) => break page,
+                Err(err) => {
+                    client.discard();
+                    if retried {
+                        return Err(err);
+                    }
+                    log::warn!("fetch: {} : {}, retrying on a new session", request.url, err);
+                    retried = true;
+                }
+            }
+        };
+
+        let response = Response::html(&url, html)?;
+        if let Some(cache) = &self.cache {
+            if let Err(err) = cache.put(request, &response) {
+                log::warn!("{}", err);
+            }
+        }
+
+        Ok(response)
+    }
+
+    fn webdriver(&self) -> Result<&WebDriverPool, Error> {
+        self.webdriver
+            .as_deref()
+            .ok_or_else(|| Error::WebDriver(format!("{} fetching needs a WebDriver, none was given", self.mode)))
+    }
+}
+
+/// Length of the text of `node` outside its scripts and styles.
+fn visible_text(node: &Node) -> usize {
+    match node.name() {
+        Some("script") | Some("style") | Some("noscript") => 0,
+        Some(_) => node.children().map(|child| visible_text(&child)).sum(),
+        None => node.as_text().map(|text| text.trim().len()).unwrap_or(0),
+    }
+}
+
+/// The CSS selectors a static page is checked with: `tag`, `.class`, `#id`, `[attr]` and `[attr=value]`,
+/// compounded and separated by descendant combinators, `,` separating alternatives.
+///
+/// `a > b` is read as `a b`, and pseudo-classes are refused.
+#[derive(Debug, Clone)]
+struct Selector {
+    alternatives: Vec<Vec<Compound>>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Compound {
+    name: Option<String>,
+    id: Option<String>,
+    classes: Vec<String>,
+    attributes: Vec<(String, Option<String>)>,
+}
+
+impl Selector {
+    fn parse(selector: &str) -> Result<Selector, Error> {
+        let invalid = |reason: &str| Error::InvalidSpider(format!("selector {} : {}", selector, reason));
+
+        let mut alternatives = Vec::new();
+        for alternative in selector.split(',') {
+            let compounds = alternative
+                .replace('>', " ")
+                .split_whitespace()
+                .map(|compound| Compound::parse(compound).ok_or_else(|| invalid("only tags, classes, ids and attributes are supported")))
+                .collect::<Result<Vec<Compound>, Error>>()?;
+            if compounds.is_empty() {
+                return Err(invalid("empty"));
+            }
+            alternatives.push(compounds);
+        }
+
+        Ok(Selector { alternatives })
+    }
+}
+
+impl Compound {
+    fn parse(compound: &str) -> Option<Compound> {
+        let mut parsed = Compound::default();
+        let mut rest = compound;
+
+        let end = rest.find(['.', '#', '[']).unwrap_or(rest.len());
+        match &rest[..end] {
+            "" | "*" => {}
+            name if name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') => parsed.name = Some(name.to_lowercase()),
+            _ => return None,
+        }
+        rest = &rest[end..];
+
+        while !rest.is_empty() {
+            if let Some(attribute) = rest.strip_prefix('[') {
+                let end = attribute.find(']')?;
+                let (name, value) = match attribute[..end].split_once('=') {
+                    Some((name, value)) => (name, Some(value.trim_matches(|c| c == '"' || c == '\'').to_string())),
+                    None => (&attribute[..end], None),
+                };
+                parsed.attributes.push((name.trim().to_string(), value));
+                rest = &attribute[end + 1..];
+                continue;
+            }
+
+            let (marker, tail) = rest.split_at(1);
+            let end = tail.find(['.', '#', '[']).unwrap_or(tail.len());
+            let value = &tail[..end];
+            if value.is_empty() || value.contains(':') {
+                return None;
+            }
+            match marker {
+                "." => parsed.classes.push(value.to_string()),
+                "#" => parsed.id = Some(value.to_string()),
+                _ => return None,
+            }
+            rest = &tail[end..];
+        }
+
+        Some(parsed)
+    }
+
+    fn matches(&self, node: &Node) -> bool {
+        let name = match node.name() {
+            Some(name) => name,
+            None => return false,
+        };
+
+        self.name.as_ref().is_none_or(|expected| expected == name)
+            && self.id.as_ref().is_none_or(|id| node.attr("id") == Some(id.as_str()))
+            && self.classes.iter().all(|class| {
+                node.attr("class")
+                    .is_some_and(|classes| classes.split_whitespace().any(|candidate| candidate == class))
+            })
+            && self.attributes.iter().all(|(attribute, value)| match (node.attr(attribute), value) {
+                (Some(actual), Some(value)) => actual == value,
+                (actual, None) => actual.is_some(),
+                (None, Some(_)) => false,
+            })
+    }
+}
+
+impl Predicate for &Selector {
+    fn matches(&self, node: &Node) -> bool {
+        self.alternatives.iter().any(|compounds| {
+            let (last, ancestors) = match compounds.split_last() {
+                Some(split) => split,
+                None => return false,
+            };
+            if !last.matches(node) {
+                return false;
+            }
+
+            // Matching the closest ancestors first is enough with descendant combinators only.
+            let mut pending = ancestors.iter().rev().peekable();
+            let mut parent = node.parent();
+            while let (Some(compound), Some(ancestor)) = (pending.peek(), parent) {
+                if compound.matches(&ancestor) {
+                    pending.next();
+                }
+                parent = ancestor.parent();
+            }
+            pending.peek().is_none()
+        })
+    }
+}
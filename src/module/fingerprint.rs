@@ -0,0 +1,418 @@
+use crate::commons::contants::{ HTTPS_PORTS, HTTP_PORTS };
+use crate::module::crawler::HttpSettings;
+use crate::module::error::Error;
+use crate::module::scanner::{ Connector, Domain };
+use crate::module::tls::{ peer_certificates, CertificateInfo };
+use reqwest::{ header, redirect, Client, Response };
+use select::{ document::Document, predicate::Name };
+use serde::Serialize;
+use url::Url;
+
+const MAX_REDIRECTS: usize = 10;
+
+/// What a web service tells about itself on its landing page.
+#[derive(Debug, Clone, Serialize)]
+pub struct HttpFingerprint {
+    pub url: String,
+    pub status: u16,
+    pub server: Option<String>,
+    pub powered_by: Option<String>,
+    pub title: Option<String>,
+    pub favicon_hash: Option<i32>,
+    pub certificate: Option<CertificateInfo>,
+    pub redirects: Vec<String>,
+    pub technologies: Vec<Technology>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Technology {
+    pub name: String,
+    pub category: String,
+}
+
+impl HttpFingerprint {
+    pub fn summary(&self) -> String {
+        let mut line = format!("{} [{}]", self.url, self.status);
+        if let Some(title) = &self.title {
+            line.push_str(&format!(" \"{}\"", title));
+        }
+        if let Some(server) = &self.server {
+            line.push_str(&format!(" server: {}", server));
+        }
+        if let Some(powered_by) = &self.powered_by {
+            line.push_str(&format!(" powered-by: {}", powered_by));
+        }
+        if let Some(hash) = self.favicon_hash {
+            line.push_str(&format!(" favicon: {}", hash));
+        }
+        if !self.technologies.is_empty() {
+            let technologies = self
+                .technologies
+                .iter()
+                .map(|technology| format!("{} ({})", technology.name, technology.category))
+                .collect::<Vec<String>>();
+            line.push_str(&format!(" tech: {}", technologies.join(", ")));
+        }
+        if !self.redirects.is_empty() {
+            line.push_str(&format!(" redirects: {}", self.redirects.join(" -> ")));
+        }
+        if let Some(certificate) = &self.certificate {
+            line.push_str(&format!(" cert: {} sans: {}", certificate.subject, certificate.sans.join(",")));
+        }
+
+        line
+    }
+}
+
+enum Matcher {
+    Header(&'static str, &'static str),
+    Cookie(&'static str),
+    Body(&'static str),
+    Title(&'static str),
+    Favicon(i32),
+}
+
+struct Signature {
+    name: &'static str,
+    category: &'static str,
+    matchers: &'static [Matcher],
+}
+
+static SIGNATURES: &[Signature] = &[
+    Signature { name: "WordPress", category: "CMS", matchers: &[Matcher::Body("/wp-content/"), Matcher::Body("/wp-includes/"), Matcher::Header("link", "api.w.org")] },
+    Signature { name: "Drupal", category: "CMS", matchers: &[Matcher::Header("x-generator", "drupal"), Matcher::Header("x-drupal-cache", ""), Matcher::Body("drupal-settings-json")] },
+    Signature { name: "Joomla", category: "CMS", matchers: &[Matcher::Body("content=\"joomla"), Matcher::Body("/media/jui/")] },
+    Signature { name: "Magento", category: "CMS", matchers: &[Matcher::Cookie("frontend"), Matcher::Body("mage/cookies")] },
+    Signature { name: "Apache", category: "Web server", matchers: &[Matcher::Header("server", "apache")] },
+    Signature { name: "nginx", category: "Web server", matchers: &[Matcher::Header("server", "nginx")] },
+    Signature { name: "Microsoft IIS", category: "Web server", matchers: &[Matcher::Header("server", "microsoft-iis")] },
+    Signature { name: "LiteSpeed", category: "Web server", matchers: &[Matcher::Header("server", "litespeed")] },
+    Signature { name: "Apache Tomcat", category: "Web server", matchers: &[Matcher::Title("apache tomcat")] },
+    Signature { name: "PHP", category: "Language", matchers: &[Matcher::Header("x-powered-by", "php"), Matcher::Cookie("PHPSESSID")] },
+    Signature { name: "ASP.NET", category: "Framework", matchers: &[Matcher::Header("x-powered-by", "asp.net"), Matcher::Header("x-aspnet-version", ""), Matcher::Cookie("ASP.NET_SessionId")] },
+    Signature { name: "Java Servlet", category: "Framework", matchers: &[Matcher::Cookie("JSESSIONID")] },
+    Signature { name: "Express", category: "Framework", matchers: &[Matcher::Header("x-powered-by", "express")] },
+    Signature { name: "Next.js", category: "Framework", matchers: &[Matcher::Header("x-powered-by", "next.js"), Matcher::Body("/_next/static/")] },
+    Signature { name: "Django", category: "Framework", matchers: &[Matcher::Cookie("csrftoken"), Matcher::Body("csrfmiddlewaretoken")] },
+    Signature { name: "Laravel", category: "Framework", matchers: &[Matcher::Cookie("laravel_session")] },
+    Signature { name: "Ruby on Rails", category: "Framework", matchers: &[Matcher::Header("x-runtime", ""), Matcher::Body("csrf-param\" content=\"authenticity_token")] },
+    Signature { name: "Spring Boot", category: "Framework", matchers: &[Matcher::Body("Whitelabel Error Page"), Matcher::Favicon(116323821)] },
+    Signature { name: "Jenkins", category: "CI", matchers: &[Matcher::Header("x-jenkins", ""), Matcher::Favicon(81586312)] },
+    Signature { name: "GitLab", category: "DevOps", matchers: &[Matcher::Cookie("_gitlab_session"), Matcher::Title("gitlab")] },
+    Signature { name: "Grafana", category: "Monitoring", matchers: &[Matcher::Title("grafana"), Matcher::Cookie("grafana_session")] },
+    Signature { name: "phpMyAdmin", category: "Database admin", matchers: &[Matcher::Title("phpmyadmin"), Matcher::Cookie("phpMyAdmin")] },
+    Signature { name: "Cloudflare", category: "WAF", matchers: &[Matcher::Header("server", "cloudflare"), Matcher::Header("cf-ray", ""), Matcher::Cookie("__cfduid")] },
+    Signature { name: "Akamai", category: "WAF", matchers: &[Matcher::Header("server", "akamaighost"), Matcher::Header("x-akamai-transformed", "")] },
+    Signature { name: "Sucuri", category: "WAF", matchers: &[Matcher::Header("x-sucuri-id", ""), Matcher::Header("server", "sucuri")] },
+    Signature { name: "Imperva Incapsula", category: "WAF", matchers: &[Matcher::Header("x-iinfo", ""), Matcher::Cookie("incap_ses_"), Matcher::Cookie("visid_incap_")] },
+    Signature { name: "F5 BIG-IP", category: "WAF", matchers: &[Matcher::Cookie("BIGipServer"), Matcher::Header("server", "big-ip")] },
+    Signature { name: "AWS Elastic Load Balancer", category: "Load balancer", matchers: &[Matcher::Cookie("AWSALB"), Matcher::Cookie("AWSELB"), Matcher::Header("server", "awselb")] },
+    Signature { name: "ModSecurity", category: "WAF", matchers: &[Matcher::Header("server", "mod_security"), Matcher::Body("This error was generated by Mod_Security")] },
+];
+
+/// Everything a signature can be matched against.
+struct Evidence<'a> {
+    headers: &'a header::HeaderMap,
+    cookies: &'a [String],
+    body: &'a str,
+    title: Option<&'a str>,
+    favicon_hash: Option<i32>,
+}
+
+impl Matcher {
+    fn matches(&self, evidence: &Evidence) -> bool {
+        match self {
+            Matcher::Header(name, pattern) => evidence
+                .headers
+                .get_all(*name)
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .any(|value| value.to_lowercase().contains(pattern)),
+            Matcher::Cookie(prefix) => evidence.cookies.iter().any(|cookie| cookie.starts_with(prefix)),
+            Matcher::Body(pattern) => evidence.body.contains(pattern),
+            Matcher::Title(pattern) => evidence
+                .title
+                .map(|title| title.to_lowercase().contains(pattern))
+                .unwrap_or(false),
+            Matcher::Favicon(hash) => evidence.favicon_hash == Some(*hash),
+        }
+    }
+}
+
+fn match_signatures(evidence: &Evidence) -> Vec<Technology> {
+    SIGNATURES
+        .iter()
+        .filter(|signature| signature.matchers.iter().any(|matcher| matcher.matches(evidence)))
+        .map(|signature| Technology {
+            name: signature.name.to_string(),
+            category: signature.category.to_string(),
+        })
+        .collect()
+}
+
+pub struct HttpFingerprinter {
+    client: Client,
+    settings: HttpSettings,
+}
+
+impl HttpFingerprinter {
+    pub fn new(settings: &HttpSettings) -> Result<Self, Error> {
+        let client = settings
+            .client_builder()
+            .redirect(redirect::Policy::none())
+            .danger_accept_invalid_certs(true)
+            .build()?;
+
+        Ok(HttpFingerprinter {
+            client,
+            settings: settings.clone(),
+        })
+    }
+
+    /// Fingerprints every open port of `domain` that usually serves HTTP(S).
+    pub async fn fingerprint_domain(&self, domain: &Domain) -> Vec<HttpFingerprint> {
+        let mut fingerprints = Vec::new();
+
+        for port in domain.open_port.iter().filter(|port| HTTP_PORTS.contains(&port.port)) {
+            match self.fingerprint_port(&domain.domain, port.port).await {
+                Ok(fingerprint) => fingerprints.push(fingerprint),
+                Err(err) => log::warn!("fingerprint: {}:{} : {}", domain.domain, port.port, err),
+            }
+        }
+
+        fingerprints
+    }
+
+    /// Tries the scheme usually spoken on `port` first, then the other one.
+    pub async fn fingerprint_port(&self, host: &str, port: u16) -> Result<HttpFingerprint, Error> {
+        let schemes = if HTTPS_PORTS.contains(&port) {
+            ["https", "http"]
+        } else {
+            ["http", "https"]
+        };
+
+        let mut last_error = Error::InvalidHttpResponse(format!("{}:{}", host, port));
+        for scheme in schemes.iter() {
+            let url = format!("{}://{}:{}/", scheme, host, port);
+            match self.fingerprint(&url).await {
+                Ok(fingerprint) => return Ok(fingerprint),
+                Err(err) => last_error = err,
+            }
+        }
+
+        Err(last_error)
+    }
+
+    pub async fn fingerprint(&self, url: &str) -> Result<HttpFingerprint, Error> {
+        let mut url = Url::parse(url).map_err(|err| Error::Internal(format!("{} : {}", url, err)))?;
+        let mut redirects = Vec::new();
+        let mut cookies = Vec::new();
+
+        let res = loop {
+            let res = self.client.get(url.clone()).headers(self.settings.headers.for_url(&url)).send().await?;
+            cookies.extend(cookie_names(&res));
+
+            let location = res
+                .headers()
+                .get(header::LOCATION)
+                .and_then(|location| location.to_str().ok())
+                .and_then(|location| url.join(location).ok());
+
+            match location {
+                Some(location) if res.status().is_redirection() && redirects.len() < MAX_REDIRECTS => {
+                    redirects.push(url.to_string());
+                    url = location;
+                }
+                _ => break res,
+            }
+        };
+
+        let status = res.status().as_u16();
+        let headers = res.headers().clone();
+        let body = res.text().await.unwrap_or_default();
+        let document = Document::from(body.as_str());
+
+        let title = document
+            .select(Name("title"))
+            .next()
+            .map(|title| title.text().trim().to_string())
+            .filter(|title| !title.is_empty());
+        let favicon_hash = self.favicon_hash(&url, &document).await;
+
+        let certificate = if url.scheme() == "https" {
+            self.certificate(&url).await
+        } else {
+            None
+        };
+
+        let technologies = match_signatures(&Evidence {
+            headers: &headers,
+            cookies: &cookies,
+            body: &body,
+            title: title.as_deref(),
+            favicon_hash,
+        });
+
+        Ok(HttpFingerprint {
+            url: url.to_string(),
+            status,
+            server: header_value(&headers, header::SERVER.as_str()),
+            powered_by: header_value(&headers, "x-powered-by"),
+            title,
+            favicon_hash,
+            certificate,
+            redirects,
+            technologies,
+        })
+    }
+
+    async fn favicon_hash(&self, url: &Url, document: &Document) -> Option<i32> {
+        let icon = document
+            .select(Name("link"))
+            .filter(|link| {
+                link.attr("rel")
+                    .map(|rel| rel.to_lowercase().split_whitespace().any(|rel| rel == "icon"))
+                    .unwrap_or(false)
+            })
+            .filter_map(|link| link.attr("href"))
+            .next()
+            .unwrap_or("/favicon.ico");
+
+        let icon = url.join(icon).ok()?;
+        let res = self.client.get(icon.clone()).headers(self.settings.headers.for_url(&icon)).send().await.ok()?;
+        if !res.status().is_success() {
+            return None;
+        }
+
+        let bytes = res.bytes().await.ok()?;
+        if bytes.is_empty() {
+            return None;
+        }
+
+        Some(favicon_hash(&bytes))
+    }
+
+    async fn certificate(&self, url: &Url) -> Option<CertificateInfo> {
+        // An HTTP proxy cannot relay the handshake, which would then reach the target around it.
+        let connector = Connector::through(self.settings.proxies.as_ref())?;
+        let host = url.host_str()?;
+        let port = url.port_or_known_default()?;
+
+        let chain = peer_certificates(&connector, host, port, self.settings.timeout)
+            .await
+            .map_err(|err| log::debug!("fingerprint: {}", err))
+            .ok()?;
+
+        chain.first().and_then(|der| CertificateInfo::from_der(der).ok())
+    }
+}
+
+fn header_value(headers: &header::HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_string())
+}
+
+fn cookie_names(res: &Response) -> Vec<String> {
+    res.headers()
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .filter_map(|cookie| cookie.split('=').next())
+        .map(|name| name.trim().to_string())
+        .collect()
+}
+
+/// Shodan compatible favicon hash: murmur3 of the base64 encoding with a newline every 76 chars.
+pub fn favicon_hash(bytes: &[u8]) -> i32 {
+    let encoded = base64::encode(bytes);
+    let mut wrapped = String::with_capacity(encoded.len() + encoded.len() / 76 + 1);
+
+    for (i, c) in encoded.chars().enumerate() {
+        if i > 0 && i % 76 == 0 {
+            wrapped.push('\n');
+        }
+        wrapped.push(c);
+    }
+    wrapped.push('\n');
+
+    murmur3_32(wrapped.as_bytes(), 0) as i32
+}
+
+fn murmur3_32(data: &[u8], seed: u32) -> u32 {
+    const C1: u32 = 0xcc9e_2d51;
+    const C2: u32 = 0x1b87_3593;
+
+    let mut hash = seed;
+    let mut chunks = data.chunks_exact(4);
+
+    for chunk in &mut chunks {
+        let mut k = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        k = k.wrapping_mul(C1).rotate_left(15).wrapping_mul(C2);
+        hash ^= k;
+        hash = hash.rotate_left(13).wrapping_mul(5).wrapping_add(0xe654_6b64);
+    }
+
+    let tail = chunks.remainder();
+    if !tail.is_empty() {
+        let mut k = 0u32;
+        for (i, byte) in tail.iter().enumerate() {
+            k |= (*byte as u32) << (8 * i);
+        }
+        k = k.wrapping_mul(C1).rotate_left(15).wrapping_mul(C2);
+        hash ^= k;
+    }
+
+    hash ^= data.len() as u32;
+    hash ^= hash >> 16;
+    hash = hash.wrapping_mul(0x85eb_ca6b);
+    hash ^= hash >> 13;
+    hash = hash.wrapping_mul(0xc2b2_ae35);
+    hash ^= hash >> 16;
+
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn murmur3_matches_the_reference_vectors() {
+        assert_eq!(murmur3_32(b"", 0), 0);
+        assert_eq!(murmur3_32(b"", 1), 0x514e_28b7);
+        assert_eq!(murmur3_32(b"\0\0\0\0", 0), 0x2362_f9de);
+        assert_eq!(murmur3_32(b"aaaa", 0x9747_b28c), 0x5a97_808a);
+        assert_eq!(murmur3_32(b"Hello, world!", 0x9747_b28c), 0x2488_4cba);
+        assert_eq!(murmur3_32(b"The quick brown fox jumps over the lazy dog", 0x9747_b28c), 0x2fa8_26cd);
+    }
+
+    #[test]
+    fn favicon_hash_is_shodan_compatible() {
+        // mmh3.hash(base64.encodebytes(icon)), as Shodan computes http.favicon.hash.
+        let icon = std::fs::read(Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/fingerprint/favicon.ico")).unwrap();
+        assert_eq!(favicon_hash(&icon), -265101478);
+    }
+
+    #[test]
+    fn signatures_match_headers_cookies_body_title_and_favicon() {
+        let mut headers = header::HeaderMap::new();
+        headers.insert(header::SERVER, "nginx/1.18.0 (Ubuntu)".parse().unwrap());
+        headers.insert("x-powered-by", "PHP/8.1.2".parse().unwrap());
+        let cookies = vec!["laravel_session".to_string()];
+        let body = r#"<link rel="stylesheet" href="/wp-content/themes/a.css">"#;
+
+        let names = |evidence: &Evidence| {
+            match_signatures(evidence).into_iter().map(|technology| technology.name).collect::<Vec<String>>()
+        };
+
+        let evidence = Evidence { headers: &headers, cookies: &cookies, body, title: Some("Grafana"), favicon_hash: Some(81586312) };
+        assert_eq!(names(&evidence), vec!["WordPress", "nginx", "PHP", "Laravel", "Jenkins", "Grafana"]);
+
+        let empty = header::HeaderMap::new();
+        let evidence = Evidence { headers: &empty, cookies: &[], body: "", title: None, favicon_hash: None };
+        assert!(names(&evidence).is_empty());
+    }
+}
@@ -0,0 +1,183 @@
+use crate::module::error::Error;
+use rand::Rng;
+use reqwest::header::{ self, HeaderMap, HeaderValue };
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{ Arc, Mutex };
+use url::Url;
+
+/// A browser as servers see it: its user agent and the headers it sends along.
+#[derive(Debug)]
+pub struct HeaderProfile {
+    pub name: &'static str,
+    pub user_agent: &'static str,
+    pub accept: &'static str,
+    pub accept_language: &'static str,
+}
+
+const CHROME_ACCEPT: &str = "text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,image/apng,*/*;q=0.8,application/signed-exchange;v=b3;q=0.7";
+const FIREFOX_ACCEPT: &str = "text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,*/*;q=0.8";
+
+pub const HEADER_PROFILES: &[HeaderProfile] = &[
+    HeaderProfile {
+        name: "chrome",
+        user_agent: "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36",
+        accept: CHROME_ACCEPT,
+        accept_language: "en-US,en;q=0.9",
+    },
+    HeaderProfile {
+        name: "chrome-mac",
+        user_agent: "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36",
+        accept: CHROME_ACCEPT,
+        accept_language: "en-US,en;q=0.9",
+    },
+    HeaderProfile {
+        name: "edge",
+        user_agent: "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36 Edg/124.0.0.0",
+        accept: CHROME_ACCEPT,
+        accept_language: "en-US,en;q=0.9",
+    },
+    HeaderProfile {
+        name: "firefox",
+        user_agent: "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:125.0) Gecko/20100101 Firefox/125.0",
+        accept: FIREFOX_ACCEPT,
+        accept_language: "en-US,en;q=0.5",
+    },
+    HeaderProfile {
+        name: "firefox-linux",
+        user_agent: "Mozilla/5.0 (X11; Linux x86_64; rv:125.0) Gecko/20100101 Firefox/125.0",
+        accept: FIREFOX_ACCEPT,
+        accept_language: "en-US,en;q=0.5",
+    },
+    HeaderProfile {
+        name: "safari",
+        user_agent: "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.4.1 Safari/605.1.15",
+        accept: "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8",
+        accept_language: "en-US,en;q=0.9",
+    },
+];
+
+impl HeaderProfile {
+    pub fn from_name(name: &str) -> Option<&'static HeaderProfile> {
+        HEADER_PROFILES.iter().find(|profile| profile.name == name)
+    }
+}
+
+/// When the profile of the requests changes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rotation {
+    /// Always the first profile.
+    Fixed,
+    /// A random profile for every request.
+    Request,
+    /// A random profile for every host, kept for all its requests as a browser would.
+    Host,
+}
+
+impl Rotation {
+    pub fn from_name(name: &str) -> Option<Rotation> {
+        match name {
+            "fixed" => Some(Rotation::Fixed),
+            "request" => Some(Rotation::Request),
+            "host" => Some(Rotation::Host),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Rotation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Rotation::Fixed => write!(f, "fixed"),
+            Rotation::Request => write!(f, "request"),
+            Rotation::Host => write!(f, "host"),
+        }
+    }
+}
+
+/// The headers identifying the HTTP requests, from a set of profiles taken in turn.
+#[derive(Debug, Clone)]
+pub struct HeaderRotation {
+    profiles: Vec<&'static HeaderProfile>,
+    rotation: Rotation,
+    /// Replaces the user agent of the profiles, for engagements where the requests must be recognisable.
+    user_agent: Option<HeaderValue>,
+    hosts: Arc<Mutex<HashMap<String, usize>>>,
+}
+
+impl Default for HeaderRotation {
+    fn default() -> Self {
+        HeaderRotation {
+            profiles: vec![&HEADER_PROFILES[0]],
+            rotation: Rotation::Fixed,
+            user_agent: None,
+            hosts: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+impl HeaderRotation {
+    /// Rotates through the profiles `names`, `all` or none meaning every profile.
+    pub fn new(names: &[String], rotation: Rotation) -> Result<Self, Error> {
+        let profiles = if names.is_empty() || names.iter().any(|name| name == "all") {
+            HEADER_PROFILES.iter().collect()
+        } else {
+            names
+                .iter()
+                .map(|name| {
+                    HeaderProfile::from_name(name).ok_or_else(|| {
+                        let known = HEADER_PROFILES.iter().map(|profile| profile.name).collect::<Vec<&str>>();
+                        Error::Config(format!("{} : unknown header profile, expected one of {} or all", name, known.join(", ")))
+                    })
+                })
+                .collect::<Result<Vec<&'static HeaderProfile>, Error>>()?
+        };
+
+        Ok(HeaderRotation { profiles, rotation, ..HeaderRotation::default() })
+    }
+
+    pub fn with_user_agent(mut self, user_agent: &str) -> Result<Self, Error> {
+        let value = HeaderValue::from_str(user_agent)
+            .map_err(|_| Error::Config(format!("{} : not a valid user agent", user_agent)))?;
+        self.user_agent = Some(value);
+        Ok(self)
+    }
+
+    pub fn is_rotating(&self) -> bool {
+        self.rotation != Rotation::Fixed && self.profiles.len() > 1
+    }
+
+    /// The headers of the first profile, those of the clients by default.
+    pub fn first(&self) -> HeaderMap {
+        self.headers(self.profiles[0])
+    }
+
+    /// The headers of a request to `url`.
+    pub fn for_url(&self, url: &Url) -> HeaderMap {
+        let index = match self.rotation {
+            Rotation::Fixed => 0,
+            _ if self.profiles.len() == 1 => 0,
+            Rotation::Request => rand::thread_rng().gen_range(0..self.profiles.len()),
+            Rotation::Host => {
+                let host = url.host_str().unwrap_or_default().to_string();
+                let mut hosts = self.hosts.lock().unwrap();
+                let count = self.profiles.len();
+                *hosts.entry(host).or_insert_with(|| rand::thread_rng().gen_range(0..count))
+            }
+        };
+
+        self.headers(self.profiles[index])
+    }
+
+    fn headers(&self, profile: &HeaderProfile) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        let user_agent = match &self.user_agent {
+            Some(user_agent) => user_agent.clone(),
+            None => HeaderValue::from_static(profile.user_agent),
+        };
+        headers.insert(header::USER_AGENT, user_agent);
+        headers.insert(header::ACCEPT, HeaderValue::from_static(profile.accept));
+        headers.insert(header::ACCEPT_LANGUAGE, HeaderValue::from_static(profile.accept_language));
+        headers
+    }
+}
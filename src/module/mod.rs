@@ -1,4 +1,23 @@
 pub mod crawler;
 pub mod scanner;
 pub mod error;
-pub mod dns;
+pub mod sitemap;
+pub mod dirb;
+pub mod fingerprint;
+pub mod tls;
+pub mod subdomain;
+pub mod cvedb;
+pub mod nvd;
+pub mod cvss;
+pub mod cpe;
+pub mod request;
+pub mod session;
+pub mod cache;
+pub mod check;
+pub mod webdriver;
+pub mod render;
+pub mod screenshot;
+pub mod fetch;
+pub mod proxy;
+pub mod config;
+pub mod headers;
@@ -0,0 +1,559 @@
+use crate::module::cpe::Cpe;
+use crate::module::crawler::{ AffectedProduct, CpeMatch, Cve };
+use crate::module::error::Error;
+use flate2::read::GzDecoder;
+use serde::Deserialize;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+/// Either flavour of NVD data feed, told apart by their top level key.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Feed {
+    V2 { vulnerabilities: Vec<V2Vulnerability> },
+    V1 {
+        #[serde(rename = "CVE_Items")]
+        cve_items: Vec<V1Item>,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct V1Item {
+    cve: V1Cve,
+    #[serde(default)]
+    configurations: V1Configurations,
+    #[serde(default)]
+    impact: V1Impact,
+    published_date: String,
+    last_modified_date: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct V1Cve {
+    #[serde(rename = "CVE_data_meta")]
+    meta: V1Meta,
+    #[serde(default)]
+    problemtype: V1ProblemType,
+    #[serde(default)]
+    references: V1References,
+    #[serde(default)]
+    description: V1Description,
+}
+
+#[derive(Debug, Deserialize)]
+struct V1Meta {
+    #[serde(rename = "ID")]
+    id: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct V1ProblemType {
+    #[serde(default)]
+    problemtype_data: Vec<V1ProblemTypeData>,
+}
+
+#[derive(Debug, Deserialize)]
+struct V1ProblemTypeData {
+    #[serde(default)]
+    description: Vec<LangString>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct V1References {
+    #[serde(default)]
+    reference_data: Vec<Reference>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct V1Description {
+    #[serde(default)]
+    description_data: Vec<LangString>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct V1Configurations {
+    #[serde(default)]
+    nodes: Vec<V1Node>,
+}
+
+#[derive(Debug, Deserialize)]
+struct V1Node {
+    #[serde(default)]
+    children: Vec<V1Node>,
+    #[serde(default)]
+    cpe_match: Vec<V1CpeMatch>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct V1CpeMatch {
+    vulnerable: bool,
+    cpe23_uri: String,
+    version_start_including: Option<String>,
+    version_start_excluding: Option<String>,
+    version_end_including: Option<String>,
+    version_end_excluding: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct V1Impact {
+    base_metric_v3: Option<V1BaseMetricV3>,
+    base_metric_v2: Option<V1BaseMetricV2>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct V1BaseMetricV3 {
+    cvss_v3: CvssData,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct V1BaseMetricV2 {
+    cvss_v2: CvssData,
+}
+
+#[derive(Debug, Deserialize)]
+struct V2Vulnerability {
+    cve: V2Cve,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct V2Cve {
+    id: String,
+    published: String,
+    last_modified: String,
+    #[serde(default)]
+    descriptions: Vec<LangString>,
+    #[serde(default)]
+    metrics: V2Metrics,
+    #[serde(default)]
+    weaknesses: Vec<V2Weakness>,
+    #[serde(default)]
+    configurations: Vec<V2Configuration>,
+    #[serde(default)]
+    references: Vec<Reference>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct V2Metrics {
+    #[serde(default)]
+    cvss_metric_v31: Vec<V2Metric>,
+    #[serde(default)]
+    cvss_metric_v30: Vec<V2Metric>,
+    #[serde(default)]
+    cvss_metric_v2: Vec<V2Metric>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct V2Metric {
+    cvss_data: CvssData,
+}
+
+#[derive(Debug, Deserialize)]
+struct V2Weakness {
+    #[serde(default)]
+    description: Vec<LangString>,
+}
+
+#[derive(Debug, Deserialize)]
+struct V2Configuration {
+    #[serde(default)]
+    nodes: Vec<V2Node>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct V2Node {
+    #[serde(default)]
+    cpe_match: Vec<V2CpeMatch>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct V2CpeMatch {
+    vulnerable: bool,
+    criteria: String,
+    version_start_including: Option<String>,
+    version_start_excluding: Option<String>,
+    version_end_including: Option<String>,
+    version_end_excluding: Option<String>,
+}
+
+/// The CVSS fields shared by every metric version, v2 only fields left empty for v3.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CvssData {
+    vector_string: String,
+    base_score: f32,
+    access_vector: Option<String>,
+    access_complexity: Option<String>,
+    authentication: Option<String>,
+    confidentiality_impact: Option<String>,
+    integrity_impact: Option<String>,
+    availability_impact: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LangString {
+    #[serde(default)]
+    lang: String,
+    value: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Reference {
+    url: String,
+}
+
+/// Reads an NVD JSON 1.1 or 2.0 feed file, gzip compressed or not.
+pub fn import_feed<P: AsRef<Path>>(path: P) -> Result<Vec<Cve>, Error> {
+    let path = path.as_ref();
+    let mut content = fs::read(path).map_err(|err| Error::Feed(format!("{} : {}", path.display(), err)))?;
+
+    // gzip magic number
+    if content.starts_with(&[0x1f, 0x8b]) {
+        let mut decoded = Vec::new();
+        GzDecoder::new(content.as_slice())
+            .read_to_end(&mut decoded)
+            .map_err(|err| Error::Feed(format!("{} : {}", path.display(), err)))?;
+        content = decoded;
+    }
+
+    let feed: Feed = serde_json::from_slice(&content)
+        .map_err(|err| Error::Feed(format!("{} : not an NVD feed : {}", path.display(), err)))?;
+
+    Ok(match feed {
+        Feed::V1 { cve_items } => cve_items.into_iter().map(from_v1).collect(),
+        Feed::V2 { vulnerabilities } => vulnerabilities.into_iter().map(|vulnerability| from_v2(vulnerability.cve)).collect(),
+    })
+}
+
+fn from_v1(item: V1Item) -> Cve {
+    let cwe = item
+        .cve
+        .problemtype
+        .problemtype_data
+        .iter()
+        .flat_map(|data| data.description.iter())
+        .find_map(|description| cwe_number(&description.value));
+
+    let mut matches = Vec::new();
+    let mut nodes = item.configurations.nodes;
+    while let Some(node) = nodes.pop() {
+        matches.extend(node.cpe_match.into_iter().map(|cpe| CpeMatch {
+            criteria: cpe.cpe23_uri,
+            vulnerable: cpe.vulnerable,
+            version_start_including: cpe.version_start_including,
+            version_start_excluding: cpe.version_start_excluding,
+            version_end_including: cpe.version_end_including,
+            version_end_excluding: cpe.version_end_excluding,
+        }));
+        nodes.extend(node.children);
+    }
+
+    let mut cve = new_cve(&item.cve.meta.id, cwe, &item.published_date, &item.last_modified_date);
+    cve.summary = english(&item.cve.description.description_data);
+    cve.references = item.cve.references.reference_data.into_iter().map(|reference| reference.url).collect();
+
+    if let Some(metric) = item.impact.base_metric_v3 {
+        cve.cvss3_vector = Some(metric.cvss_v3.vector_string);
+        cve.cvss3_score = Some(metric.cvss_v3.base_score);
+    }
+
+    if let Some(metric) = item.impact.base_metric_v2 {
+        apply_cvss2(&mut cve, &metric.cvss_v2);
+    }
+
+    apply_configurations(&mut cve, matches);
+    cve
+}
+
+fn from_v2(item: V2Cve) -> Cve {
+    let cwe = item
+        .weaknesses
+        .iter()
+        .flat_map(|weakness| weakness.description.iter())
+        .find_map(|description| cwe_number(&description.value));
+
+    let matches = item
+        .configurations
+        .into_iter()
+        .flat_map(|configuration| configuration.nodes)
+        .flat_map(|node| node.cpe_match)
+        .map(|cpe| CpeMatch {
+            criteria: cpe.criteria,
+            vulnerable: cpe.vulnerable,
+            version_start_including: cpe.version_start_including,
+            version_start_excluding: cpe.version_start_excluding,
+            version_end_including: cpe.version_end_including,
+            version_end_excluding: cpe.version_end_excluding,
+        })
+        .collect();
+
+    let mut cve = new_cve(&item.id, cwe, &item.published, &item.last_modified);
+    cve.summary = english(&item.descriptions);
+    cve.references = item.references.into_iter().map(|reference| reference.url).collect();
+
+    let metrics = item.metrics;
+    if let Some(metric) = metrics.cvss_metric_v31.first().or_else(|| metrics.cvss_metric_v30.first()) {
+        cve.cvss3_vector = Some(metric.cvss_data.vector_string.clone());
+        cve.cvss3_score = Some(metric.cvss_data.base_score);
+    }
+
+    if let Some(metric) = metrics.cvss_metric_v2.first() {
+        apply_cvss2(&mut cve, &metric.cvss_data);
+    }
+
+    apply_configurations(&mut cve, matches);
+    cve
+}
+
+fn new_cve(id: &str, cwe: Option<String>, published: &str, modified: &str) -> Cve {
+    Cve {
+        name: id.to_string(),
+        url: format!("https://nvd.nist.gov/vuln/detail/{}", id),
+        cwe_url: cwe
+            .as_ref()
+            .map(|cwe| format!("https://cwe.mitre.org/data/definitions/{}.html", cwe)),
+        cwe_id: cwe,
+        publish_date: date_only(published),
+        update_date: date_only(modified),
+        ..Cve::default()
+    }
+}
+
+/// Fills the v2 columns the cvedetails spider scrapes, using the same capitalisation.
+fn apply_cvss2(cve: &mut Cve, cvss: &CvssData) {
+    let field = |value: &Option<String>| value.as_deref().map(capitalize).unwrap_or_default();
+
+    cve.score = cvss.base_score;
+    cve.cvss2_vector = Some(cvss.vector_string.clone());
+    cve.access = field(&cvss.access_vector);
+    cve.complexity = field(&cvss.access_complexity);
+    cve.authentication = field(&cvss.authentication);
+    cve.confidentiality = field(&cvss.confidentiality_impact);
+    cve.integrity = field(&cvss.integrity_impact);
+    cve.availability = field(&cvss.availability_impact);
+}
+
+fn apply_configurations(cve: &mut Cve, matches: Vec<CpeMatch>) {
+    // Without a v2 metric the score comes from v3 alone.
+    if cve.score == 0.0 {
+        cve.score = cve.cvss3_score.unwrap_or_default();
+    }
+
+    for cpe in matches.iter().filter(|cpe| cpe.vulnerable) {
+        let name = match Cpe::parse_formatted(&cpe.criteria) {
+            Ok(name) => name,
+            Err(err) => {
+                log::warn!("{} : {}", cve.name, err);
+                continue;
+            }
+        };
+
+        let product = AffectedProduct {
+            vendor: name.vendor.unquoted().unwrap_or_else(|| "*".to_string()),
+            product: name.product.unquoted().unwrap_or_else(|| "*".to_string()),
+            version: name.version.unquoted().unwrap_or_else(|| "*".to_string()),
+        };
+
+        let known = cve.products.iter().any(|known| {
+            known.vendor == product.vendor && known.product == product.product && known.version == product.version
+        });
+        if !known {
+            cve.products.push(product);
+        }
+    }
+
+    cve.configurations = matches;
+}
+
+fn cwe_number(value: &str) -> Option<String> {
+    value.strip_prefix("CWE-").map(|number| number.to_string())
+}
+
+fn english(strings: &[LangString]) -> Option<String> {
+    strings
+        .iter()
+        .find(|string| string.lang == "en")
+        .or_else(|| strings.first())
+        .map(|string| string.value.clone())
+}
+
+fn date_only(timestamp: &str) -> String {
+    timestamp.split('T').next().unwrap_or(timestamp).to_string()
+}
+
+fn capitalize(value: &str) -> String {
+    let lower = value.to_lowercase().replace('_', " ");
+    let mut chars = lower.chars();
+
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> Vec<Cve> {
+        import_feed(Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/nvd").join(name)).unwrap()
+    }
+
+    fn cvss(vector: &str, score: f32, access: &str, complexity: &str, impact: &str) -> CvssData {
+        CvssData {
+            vector_string: vector.to_string(),
+            base_score: score,
+            access_vector: Some(access.to_string()),
+            access_complexity: Some(complexity.to_string()),
+            authentication: Some("NONE".to_string()),
+            confidentiality_impact: Some(impact.to_string()),
+            integrity_impact: Some(impact.to_string()),
+            availability_impact: None,
+        }
+    }
+
+    fn cpe_match(criteria: &str, vulnerable: bool) -> CpeMatch {
+        CpeMatch { criteria: criteria.to_string(), vulnerable, ..CpeMatch::default() }
+    }
+
+    fn products(cve: &Cve) -> Vec<(&str, &str, &str)> {
+        cve.products
+            .iter()
+            .map(|affected| (affected.vendor.as_str(), affected.product.as_str(), affected.version.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn gzipped_v1_feeds_are_imported() {
+        let cves = fixture("nvdcve-1.1-sample.json.gz");
+        assert_eq!(cves.len(), 1);
+
+        let cve = &cves[0];
+        assert_eq!(cve.name, "CVE-2021-3156");
+        assert_eq!(cve.url, "https://nvd.nist.gov/vuln/detail/CVE-2021-3156");
+        assert_eq!(cve.cwe_id.as_deref(), Some("193"));
+        assert_eq!(cve.publish_date, "2021-01-26");
+        assert_eq!(cve.update_date, "2021-10-25");
+        assert!(cve.summary.as_deref().unwrap().starts_with("Sudo before 1.9.5p2"));
+        assert_eq!(cve.cvss3_vector.as_deref(), Some("CVSS:3.1/AV:L/AC:L/PR:L/UI:N/S:U/C:H/I:H/A:H"));
+        assert_eq!(cve.cvss3_score, Some(7.8));
+        assert_eq!(cve.cvss2_vector.as_deref(), Some("AV:L/AC:L/Au:N/C:C/I:C/A:C"));
+        assert_eq!(cve.score, 7.2);
+        assert_eq!(cve.access, "Local");
+        assert_eq!(cve.confidentiality, "Complete");
+        assert_eq!(
+            cve.references,
+            vec!["https://www.sudo.ws/stable.html#1.9.5p2", "https://www.openwall.com/lists/oss-security/2021/01/26/3"]
+        );
+
+        // Nested children are flattened, the non vulnerable platform kept as configuration only.
+        assert_eq!(cve.configurations.len(), 3);
+        let range = cve.configurations.iter().find(|cpe| cpe.version_end_excluding.is_some()).unwrap();
+        assert_eq!(range.criteria, "cpe:2.3:a:sudo_project:sudo:*:*:*:*:*:*:*:*");
+        assert_eq!(range.version_start_including.as_deref(), Some("1.8.2"));
+        assert_eq!(range.version_end_excluding.as_deref(), Some("1.8.32"));
+        assert!(cve.configurations.iter().any(|cpe| !cpe.vulnerable && cpe.criteria.contains(":debian:")));
+        assert_eq!(products(cve), vec![("sudo_project", "sudo", "1.9.5"), ("sudo_project", "sudo", "*")]);
+    }
+
+    #[test]
+    fn plain_v2_feeds_are_imported() {
+        let cves = fixture("nvdcve-2.0-sample.json");
+        assert_eq!(cves.len(), 2);
+
+        let apache = &cves[0];
+        assert_eq!(apache.name, "CVE-2021-41773");
+        assert_eq!(apache.cwe_id.as_deref(), Some("22"));
+        assert_eq!(apache.cwe_url.as_deref(), Some("https://cwe.mitre.org/data/definitions/22.html"));
+        assert_eq!(apache.publish_date, "2021-10-05");
+        assert_eq!(apache.update_date, "2023-02-10");
+        assert!(apache.summary.as_deref().unwrap().starts_with("A flaw was found"));
+        assert_eq!(apache.cvss3_vector.as_deref(), Some("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:N/A:N"));
+        assert_eq!(apache.cvss3_score, Some(7.5));
+        assert_eq!(apache.score, 5.0);
+        assert_eq!(apache.references.len(), 2);
+        assert_eq!(apache.configurations.len(), 2);
+        assert_eq!(products(apache), vec![("apache", "http_server", "2.4.49")]);
+
+        // CVSS 3.0 only: the score falls back on v3 and the v2 columns stay empty.
+        let agent = &cves[1];
+        assert_eq!(agent.cvss3_vector.as_deref(), Some("CVSS:3.0/AV:L/AC:L/PR:L/UI:N/S:U/C:H/I:H/A:H"));
+        assert_eq!(agent.score, 7.8);
+        assert_eq!(agent.cvss2_vector, None);
+        assert_eq!(agent.access, "");
+        assert_eq!(agent.cwe_id, None);
+        assert!(agent.references.is_empty());
+        assert_eq!(agent.configurations[0].version_end_excluding.as_deref(), Some("1.4.2"));
+        assert_eq!(products(agent), vec![("example", "agent", "*")]);
+    }
+
+    #[test]
+    fn feeds_are_told_apart_by_their_top_level_key() {
+        let v1: Feed = serde_json::from_str(r#"{ "CVE_Items": [] }"#).unwrap();
+        assert!(matches!(v1, Feed::V1 { .. }));
+
+        let v2: Feed = serde_json::from_str(r#"{ "format": "NVD_CVE", "vulnerabilities": [] }"#).unwrap();
+        assert!(matches!(v2, Feed::V2 { .. }));
+
+        assert!(serde_json::from_str::<Feed>(r#"{ "items": [] }"#).is_err());
+    }
+
+    #[test]
+    fn unreadable_feeds_are_feed_errors() {
+        let path = std::env::temp_dir().join(format!("vxsuite-nvd-{}.json", std::process::id()));
+
+        // The gzip magic alone is sniffed, the truncated stream then fails to decode.
+        fs::write(&path, [0x1f, 0x8b, 0x08]).unwrap();
+        assert!(matches!(import_feed(&path), Err(Error::Feed(_))));
+
+        fs::write(&path, b"{}").unwrap();
+        assert!(matches!(import_feed(&path), Err(Error::Feed(message)) if message.contains("not an NVD feed")));
+
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(import_feed(&path), Err(Error::Feed(_))));
+    }
+
+    #[test]
+    fn cvss2_fields_use_the_scraped_capitalisation() {
+        let mut cve = Cve::default();
+        apply_cvss2(&mut cve, &cvss("AV:A/AC:M/Au:N/C:P/I:P/A:N", 5.8, "ADJACENT_NETWORK", "MEDIUM", "PARTIAL"));
+
+        assert_eq!(cve.score, 5.8);
+        assert_eq!(cve.cvss2_vector.as_deref(), Some("AV:A/AC:M/Au:N/C:P/I:P/A:N"));
+        assert_eq!(cve.access, "Adjacent network");
+        assert_eq!(cve.complexity, "Medium");
+        assert_eq!(cve.authentication, "None");
+        assert_eq!(cve.confidentiality, "Partial");
+        assert_eq!(cve.integrity, "Partial");
+        assert_eq!(cve.availability, "");
+    }
+
+    #[test]
+    fn configurations_list_each_vulnerable_product_once() {
+        let mut cve = Cve { name: "CVE-2021-0001".to_string(), cvss3_score: Some(9.1), ..Cve::default() };
+        apply_configurations(
+            &mut cve,
+            vec![
+                cpe_match("cpe:2.3:a:acme:proxy:1.0:*:*:*:*:*:*:*", true),
+                cpe_match("cpe:2.3:a:acme:proxy:1.0:*:*:*:*:*:*:*", true),
+                cpe_match("cpe:2.3:a:acme:proxy:*:*:*:*:*:*:*:*", true),
+                cpe_match("cpe:2.3:o:acme:os:2:*:*:*:*:*:*:*", false),
+                cpe_match("not a cpe", true),
+            ],
+        );
+
+        assert_eq!(cve.score, 9.1);
+        assert_eq!(cve.configurations.len(), 5);
+        assert_eq!(products(&cve), vec![("acme", "proxy", "1.0"), ("acme", "proxy", "*")]);
+    }
+}
@@ -0,0 +1,170 @@
+use crate::module::error::Error;
+use fantoccini::{ error::CmdError, Client, Locator };
+use serde::{ Deserialize, Serialize };
+use std::fmt;
+use std::str::FromStr;
+use std::time::{ Duration, Instant };
+use tokio::time::sleep;
+
+/// Meta of a request holding the actions to run once its page is rendered, instead of the spider's.
+pub const ACTIONS_META: &str = "actions";
+
+/// How long the waits of an action last by default.
+const DEFAULT_TIMEOUT_MS: u64 = 10_000;
+/// How often the conditions are polled.
+const POLL: Duration = Duration::from_millis(100);
+
+/// An interaction with a rendered page, run in order before the page source is read.
+///
+/// Written `wait-for:.quote`, `wait-idle:500`, `scroll:10`, `click:#more`, `type:#q=text` or `sleep:1000`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum Action {
+    /// Waits until `selector` matches an element.
+    WaitFor { selector: String, timeout_ms: u64 },
+    /// Waits until no resource was loaded for `quiet_ms`, at most `timeout_ms`.
+    WaitIdle { quiet_ms: u64, timeout_ms: u64 },
+    /// Scrolls to the bottom until the page stops growing, at most `max` times.
+    Scroll { max: usize },
+    Click { selector: String },
+    /// Types `text` into the field matching `selector`.
+    Type { selector: String, text: String },
+    Sleep { ms: u64 },
+}
+
+impl Action {
+    pub async fn run(&self, client: &Client) -> Result<(), Error> {
+        match self {
+            Action::WaitFor { selector, timeout_ms } => {
+                client
+                    .wait()
+                    .at_most(Duration::from_millis(*timeout_ms))
+                    .every(POLL)
+                    .for_element(Locator::Css(selector))
+                    .await
+                    .map_err(|err| match err {
+                        CmdError::WaitTimeout => Error::WebDriver(format!("timed out waiting for {}", selector)),
+                        err => err.into(),
+                    })?;
+            }
+            Action::WaitIdle { quiet_ms, timeout_ms } => wait_idle(client, *quiet_ms, *timeout_ms).await?,
+            Action::Scroll { max } => scroll(client, *max).await?,
+            Action::Click { selector } => client.find(Locator::Css(selector)).await?.click().await?,
+            Action::Type { selector, text } => {
+                let field = client.find(Locator::Css(selector)).await?;
+                field.clear().await?;
+                field.send_keys(text).await?;
+            }
+            Action::Sleep { ms } => sleep(Duration::from_millis(*ms)).await,
+        }
+
+        Ok(())
+    }
+}
+
+/// Runs `actions` on the page `client` shows.
+pub async fn perform(client: &Client, actions: &[Action]) -> Result<(), Error> {
+    for action in actions {
+        log::debug!("render: {}", action);
+        action.run(client).await?;
+    }
+
+    Ok(())
+}
+
+/// Polls the number of resources the page loaded until it stays the same for `quiet_ms`.
+async fn wait_idle(client: &Client, quiet_ms: u64, timeout_ms: u64) -> Result<(), Error> {
+    let script = "return [document.readyState, performance.getEntriesByType('resource').length];";
+    let started = Instant::now();
+    let mut last = None;
+    let mut quiet_since = Instant::now();
+
+    while started.elapsed() < Duration::from_millis(timeout_ms) {
+        let state = client.execute(script, Vec::new()).await?;
+        let current = (state[0].as_str() == Some("complete"), state[1].as_u64());
+
+        if last != Some(current) {
+            last = Some(current);
+            quiet_since = Instant::now();
+        } else if current.0 && quiet_since.elapsed() >= Duration::from_millis(quiet_ms) {
+            return Ok(());
+        }
+
+        sleep(POLL).await;
+    }
+
+    // Pages polling forever never go idle, their current state is as good as any.
+    log::debug!("render: still loading after {}ms", timeout_ms);
+    Ok(())
+}
+
+/// Scrolls to the bottom, waiting after each scroll for the content it loads.
+async fn scroll(client: &Client, max: usize) -> Result<(), Error> {
+    let script = "window.scrollTo(0, document.body.scrollHeight); return document.body.scrollHeight;";
+    let mut height = client.execute(script, Vec::new()).await?.as_u64();
+
+    for _ in 0..max {
+        wait_idle(client, 500, 3_000).await?;
+        let grown = client.execute(script, Vec::new()).await?.as_u64();
+        if grown == height {
+            break;
+        }
+        height = grown;
+    }
+
+    Ok(())
+}
+
+impl FromStr for Action {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Error> {
+        let (name, argument) = value.split_once(':').unwrap_or((value, ""));
+        let number = |default: u64| -> Result<u64, Error> {
+            match argument.trim() {
+                "" => Ok(default),
+                argument => argument
+                    .parse()
+                    .map_err(|_| Error::WebDriver(format!("{} : {} is not a number", value, argument))),
+            }
+        };
+        let selector = || -> Result<String, Error> {
+            match argument.trim() {
+                "" => Err(Error::WebDriver(format!("{} : missing selector", value))),
+                selector => Ok(selector.to_string()),
+            }
+        };
+
+        match name.trim() {
+            "wait-for" => Ok(Action::WaitFor { selector: selector()?, timeout_ms: DEFAULT_TIMEOUT_MS }),
+            "wait-idle" => Ok(Action::WaitIdle { quiet_ms: number(500)?, timeout_ms: DEFAULT_TIMEOUT_MS }),
+            "scroll" => Ok(Action::Scroll { max: number(20)? as usize }),
+            "click" => Ok(Action::Click { selector: selector()? }),
+            "type" => match argument.split_once('=') {
+                Some((selector, text)) if !selector.trim().is_empty() => Ok(Action::Type {
+                    selector: selector.trim().to_string(),
+                    text: text.to_string(),
+                }),
+                _ => Err(Error::WebDriver(format!("{} : expected type:SELECTOR=TEXT", value))),
+            },
+            "sleep" => Ok(Action::Sleep { ms: number(1_000)? }),
+            _ => Err(Error::WebDriver(format!(
+                "{} : unknown action, expected wait-for, wait-idle, scroll, click, type or sleep",
+                value
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Action::WaitFor { selector, .. } => write!(f, "wait-for:{}", selector),
+            Action::WaitIdle { quiet_ms, .. } => write!(f, "wait-idle:{}", quiet_ms),
+            Action::Scroll { max } => write!(f, "scroll:{}", max),
+            Action::Click { selector } => write!(f, "click:{}", selector),
+            Action::Type { selector, text } => write!(f, "type:{}={}", selector, text),
+            Action::Sleep { ms } => write!(f, "sleep:{}", ms),
+        }
+    }
+}
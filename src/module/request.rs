@@ -0,0 +1,388 @@
+use crate::module::error::Error;
+use reqwest::header::{ self, HeaderMap, HeaderName, HeaderValue };
+use reqwest::{ Client, Method, RequestBuilder };
+use select::{ document::Document, node::Node, predicate::Name };
+use serde::{ de::DeserializeOwned, Serialize };
+use serde_json::Value;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{ BTreeMap, HashMap };
+use std::hash::{ Hash, Hasher };
+use url::{ form_urlencoded, Url };
+
+/// Handler of the requests that do not name one.
+pub const DEFAULT_HANDLER: &str = "parse";
+
+/// A page for a spider to fetch, with the handler that parses it and the context it carries.
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub url: String,
+    pub method: Method,
+    pub headers: HeaderMap,
+    pub body: Option<Vec<u8>>,
+    pub cookies: BTreeMap<String, String>,
+    /// Higher priorities are fetched first, equal ones in the order they were queued.
+    pub priority: i32,
+    pub handler: String,
+    pub meta: HashMap<String, Value>,
+    /// Number of requests between this one and a start request.
+    pub depth: usize,
+}
+
+impl Request {
+    pub fn new(method: Method, url: &str) -> Self {
+        Request {
+            url: url.to_string(),
+            method,
+            headers: HeaderMap::new(),
+            body: None,
+            cookies: BTreeMap::new(),
+            priority: 0,
+            handler: DEFAULT_HANDLER.to_string(),
+            meta: HashMap::new(),
+            depth: 0,
+        }
+    }
+
+    pub fn get(url: &str) -> Self {
+        Request::new(Method::GET, url)
+    }
+
+    pub fn post(url: &str) -> Self {
+        Request::new(Method::POST, url)
+    }
+
+    /// A GET of `url` found while parsing this request, one level deeper.
+    pub fn follow(&self, url: &str) -> Self {
+        Request {
+            depth: self.depth + 1,
+            ..Request::get(url)
+        }
+    }
+
+    pub fn with_handler(mut self, handler: &str) -> Self {
+        self.handler = handler.to_string();
+        self
+    }
+
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Result<Self, Error> {
+        let name = HeaderName::from_bytes(name.trim().as_bytes())
+            .map_err(|err| Error::Request(format!("invalid header name {} : {}", name, err)))?;
+        let value = HeaderValue::from_str(value.trim())
+            .map_err(|err| Error::Request(format!("invalid header value {} : {}", value, err)))?;
+
+        self.headers.insert(name, value);
+        Ok(self)
+    }
+
+    pub fn with_cookie(mut self, name: &str, value: &str) -> Self {
+        self.cookies.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    pub fn with_body(mut self, content_type: &str, body: Vec<u8>) -> Result<Self, Error> {
+        self.body = Some(body);
+        self.with_header(header::CONTENT_TYPE.as_str(), content_type)
+    }
+
+    /// Form fields, sent in the query string of a GET and as an urlencoded body otherwise.
+    pub fn with_form(mut self, fields: &[(String, String)]) -> Result<Self, Error> {
+        let encoded = form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(fields)
+            .finish();
+
+        if self.method == Method::GET {
+            let mut url = Url::parse(&self.url)
+                .map_err(|err| Error::Request(format!("{} : {}", self.url, err)))?;
+            url.set_query(Some(&encoded));
+            self.url = url.to_string();
+            Ok(self)
+        } else {
+            self.with_body("application/x-www-form-urlencoded", encoded.into_bytes())
+        }
+    }
+
+    pub fn with_json<T: Serialize>(self, value: &T) -> Result<Self, Error> {
+        let body = serde_json::to_vec(value)
+            .map_err(|err| Error::Request(format!("request json body : {}", err)))?;
+
+        self.with_body("application/json", body)
+    }
+
+    /// Attaches `value` under `key`, e.g. the parent item a detail page completes.
+    pub fn with_meta<T: Serialize>(mut self, key: &str, value: &T) -> Result<Self, Error> {
+        let value = serde_json::to_value(value)
+            .map_err(|err| Error::Request(format!("request meta {} : {}", key, err)))?;
+
+        self.meta.insert(key.to_string(), value);
+        Ok(self)
+    }
+
+    pub fn meta<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        self.meta
+            .get(key)
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+    }
+
+    /// Identifies the requests fetching the same resource: method, URL and body.
+    pub fn fingerprint(&self) -> String {
+        match &self.body {
+            Some(body) => {
+                let mut hasher = DefaultHasher::new();
+                body.hash(&mut hasher);
+                format!("{} {} {:016x}", self.method, self.url, hasher.finish())
+            }
+            None => format!("{} {}", self.method, self.url),
+        }
+    }
+
+    /// A reqwest request for `client` with this method, headers, cookies and body.
+    pub fn build(&self, client: &Client) -> RequestBuilder {
+        let mut builder = client
+            .request(self.method.clone(), &self.url)
+            .headers(self.headers.clone());
+
+        if !self.cookies.is_empty() {
+            let cookies = self
+                .cookies
+                .iter()
+                .map(|(name, value)| format!("{}={}", name, value))
+                .collect::<Vec<String>>();
+            builder = builder.header(header::COOKIE, cookies.join("; "));
+        }
+
+        match &self.body {
+            Some(body) => builder.body(body.clone()),
+            None => builder,
+        }
+    }
+}
+
+impl From<String> for Request {
+    fn from(url: String) -> Self {
+        Request::get(&url)
+    }
+}
+
+/// An HTML `<form>` with the values a browser would submit by default.
+#[derive(Debug, Clone, Serialize)]
+pub struct Form {
+    pub action: String,
+    pub method: String,
+    pub fields: Vec<(String, String)>,
+}
+
+impl Form {
+    /// Reads `form`, resolving its action against `base`, the page it was found on.
+    pub fn from_node(base: &Url, form: &Node) -> Form {
+        let action = form
+            .attr("action")
+            .filter(|action| !action.trim().is_empty())
+            .and_then(|action| base.join(action.trim()).ok())
+            .unwrap_or_else(|| base.clone());
+        let method = form.attr("method").unwrap_or("get").trim().to_uppercase();
+
+        let mut fields = Vec::new();
+        for node in form.descendants() {
+            let name = match node.attr("name") {
+                Some(name) if node.attr("disabled").is_none() => name.to_string(),
+                _ => continue,
+            };
+
+            match node.name() {
+                Some("input") => {
+                    let kind = node.attr("type").unwrap_or("text").to_lowercase();
+                    let value = node.attr("value").unwrap_or_default().to_string();
+                    match kind.as_str() {
+                        "submit" | "button" | "image" | "reset" | "file" => {}
+                        "checkbox" | "radio" => {
+                            if node.attr("checked").is_some() {
+                                let value = if value.is_empty() { "on".to_string() } else { value };
+                                fields.push((name, value));
+                            }
+                        }
+                        _ => fields.push((name, value)),
+                    }
+                }
+                Some("textarea") => fields.push((name, node.text())),
+                Some("select") => {
+                    let options = node.select(Name("option")).collect::<Vec<Node>>();
+                    let selected = options
+                        .iter()
+                        .find(|option| option.attr("selected").is_some())
+                        .or_else(|| options.first());
+                    if let Some(option) = selected {
+                        let value = option.attr("value").map(|value| value.to_string()).unwrap_or_else(|| option.text());
+                        fields.push((name, value.trim().to_string()));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Form {
+            action: action.to_string(),
+            method,
+            fields,
+        }
+    }
+
+    /// Every form of `document`, found on `base`.
+    pub fn all(base: &Url, document: &Document) -> Vec<Form> {
+        document
+            .select(Name("form"))
+            .map(|form| Form::from_node(base, &form))
+            .collect()
+    }
+
+    /// Sets `name` to `value`, replacing the default value if the form has one.
+    pub fn set(&mut self, name: &str, value: &str) -> &mut Self {
+        match self.fields.iter_mut().find(|(field, _)| field == name) {
+            Some(field) => field.1 = value.to_string(),
+            None => self.fields.push((name.to_string(), value.to_string())),
+        }
+        self
+    }
+
+    /// The request submitting this form, found while parsing `parent`.
+    pub fn submission(&self, parent: &Request) -> Result<Request, Error> {
+        let method = Method::from_bytes(self.method.as_bytes())
+            .map_err(|_| Error::Request(format!("{} : invalid form method {}", self.action, self.method)))?;
+        Request {
+            method,
+            ..parent.follow(&self.action)
+        }
+        .with_form(&self.fields)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = r#"
+        <form action="../search?stale=1" method="get">
+            <input type="hidden" name="csrf" value="t0k3n">
+            <input name="q">
+            <input type="checkbox" name="exact">
+            <input type="checkbox" name="safe" checked>
+            <input type="radio" name="sort" value="date" checked>
+            <input type="radio" name="sort" value="score">
+            <input type="text" name="off" value="x" disabled>
+            <input type="submit" name="go" value="Search">
+            <select name="lang">
+                <option value="en">English</option>
+                <option value="fr" selected>Français</option>
+            </select>
+            <select name="size"><option>10</option><option>20</option></select>
+            <textarea name="note">a
+b</textarea>
+        </form>
+        <form method=" post "><input name="login" value="admin"></form>
+    "#;
+
+    fn forms() -> Vec<Form> {
+        let base = Url::parse("https://example.com/app/page.html").unwrap();
+        Form::all(&base, &Document::from(PAGE))
+    }
+
+    fn fields(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn forms_hold_what_a_browser_would_submit() {
+        let forms = forms();
+        assert_eq!(forms.len(), 2);
+
+        let search = &forms[0];
+        assert_eq!(search.action, "https://example.com/search?stale=1");
+        assert_eq!(search.method, "GET");
+        assert_eq!(
+            search.fields,
+            fields(&[
+                ("csrf", "t0k3n"),
+                ("q", ""),
+                ("safe", "on"),
+                ("sort", "date"),
+                ("lang", "fr"),
+                ("size", "10"),
+                ("note", "a\nb"),
+            ])
+        );
+
+        // Without an action the form goes back to the page it is on.
+        assert_eq!(forms[1].action, "https://example.com/app/page.html");
+        assert_eq!(forms[1].method, "POST");
+    }
+
+    #[test]
+    fn submissions_follow_the_form_method() {
+        let parent = Request::get("https://example.com/app/page.html").with_handler("listing");
+        let mut forms = forms();
+
+        let search = forms[0].set("q", "rust & co").submission(&parent).unwrap();
+        assert_eq!(search.method, Method::GET);
+        assert_eq!(search.depth, 1);
+        assert_eq!(search.handler, DEFAULT_HANDLER);
+        assert_eq!(search.body, None);
+        assert_eq!(
+            search.url,
+            "https://example.com/search?csrf=t0k3n&q=rust+%26+co&safe=on&sort=date&lang=fr&size=10&note=a%0Ab"
+        );
+
+        let login = forms[1].set("password", "s3cret").submission(&parent).unwrap();
+        assert_eq!(login.method, Method::POST);
+        assert_eq!(login.url, "https://example.com/app/page.html");
+        assert_eq!(login.body.as_deref(), Some(&b"login=admin&password=s3cret"[..]));
+        assert_eq!(login.headers[header::CONTENT_TYPE], "application/x-www-form-urlencoded");
+
+        forms[1].method = "PO ST".to_string();
+        assert!(matches!(forms[1].submission(&parent), Err(Error::Request(_))));
+    }
+
+    #[test]
+    fn bodies_set_their_content_type() {
+        let form = Request::post("https://example.com/login").with_form(&fields(&[("user", "a b")])).unwrap();
+        assert_eq!(form.body.as_deref(), Some(&b"user=a+b"[..]));
+        assert_eq!(form.headers[header::CONTENT_TYPE], "application/x-www-form-urlencoded");
+
+        let json = Request::post("https://example.com/api").with_json(&serde_json::json!({ "id": 7 })).unwrap();
+        assert_eq!(json.body.as_deref(), Some(&br#"{"id":7}"#[..]));
+        assert_eq!(json.headers[header::CONTENT_TYPE], "application/json");
+
+        let invalid = Request::get("not a url").with_form(&fields(&[("q", "x")]));
+        assert!(matches!(invalid, Err(Error::Request(_))));
+    }
+
+    #[test]
+    fn headers_are_validated() {
+        let request = Request::get("https://example.com").with_header(" X-Api-Key ", " abc ").unwrap();
+        assert_eq!(request.headers["x-api-key"], "abc");
+
+        assert!(matches!(Request::get("https://example.com").with_header("bad name", "x"), Err(Error::Request(_))));
+        assert!(matches!(Request::get("https://example.com").with_header("X-Line", "a\nb"), Err(Error::Request(_))));
+    }
+
+    #[test]
+    fn fingerprints_tell_bodies_apart_but_not_handlers() {
+        let get = Request::get("https://example.com/a");
+        assert_eq!(get.fingerprint(), "GET https://example.com/a");
+        assert_eq!(get.clone().with_handler("other").with_priority(3).fingerprint(), get.fingerprint());
+        assert_ne!(Request::post("https://example.com/a").fingerprint(), get.fingerprint());
+
+        let post = |body: &str| {
+            Request::post("https://example.com/a")
+                .with_body("text/plain", body.as_bytes().to_vec())
+                .unwrap()
+                .fingerprint()
+        };
+        assert_eq!(post("one"), post("one"));
+        assert_ne!(post("one"), post("two"));
+        assert!(post("one").starts_with("POST https://example.com/a "));
+    }
+}
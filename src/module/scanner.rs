@@ -1,52 +1,267 @@
-use std::net::{ SocketAddr, ToSocketAddrs };
+use std::io::{ self, Read, Write };
+use std::net::ToSocketAddrs;
 use std::{ net::TcpStream, time::Duration };
 use rayon::prelude::*;
+use rayon::ThreadPoolBuilder;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
 use crate::commons::contants::{ TOP_100_PORTS };
+use crate::module::cpe::Cpe;
+use crate::module::proxy::{ socks5_connect, ProxyList };
+use futures::Stream;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use url::Url;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Port {
     pub port:  u16,
     pub state: bool,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Domain {
     pub domain: String,
     pub open_port: Vec<Port>,
 }
 
-pub fn scan_ports(mut domain: Domain) -> Domain {
-    domain.open_port = TOP_100_PORTS
-        .into_par_iter()
-        .map(|port| scan_port(&domain.domain, *port))
-        .filter(|port| port.state)
-        .collect();
+/// How the scanner reaches its targets, directly or through a SOCKS5 pivot.
+#[derive(Debug, Clone, Default)]
+pub enum Connector {
+    #[default]
+    Direct,
+    /// Every connection asked to the proxy, which then tells whether the port is open.
+    Socks5(Url),
+}
+
+impl Connector {
+    /// Through the first SOCKS5 proxy of `proxies`, `None` when they are all HTTP proxies,
+    /// which cannot relay raw TCP connections.
+    pub fn through(proxies: Option<&ProxyList>) -> Option<Self> {
+        match proxies {
+            Some(proxies) => proxies.socks5().map(|proxy| Connector::Socks5(proxy.clone())),
+            None => Some(Connector::Direct),
+        }
+    }
+
+    pub fn connect(&self, host: &str, port: u16, timeout: Duration) -> io::Result<TcpStream> {
+        match self {
+            Connector::Direct => {
+                let socket_addr = format!("{}:{}", host, port)
+                    .to_socket_addrs()?
+                    .next()
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("{} : no address", host)))?;
+                TcpStream::connect_timeout(&socket_addr, timeout)
+            }
+            Connector::Socks5(proxy) => socks5_connect(proxy, host, port, timeout),
+        }
+    }
+}
+
+/// How hard the scanner knocks on its targets.
+#[derive(Debug, Clone)]
+pub struct ScanSettings {
+    pub timeout: Duration,
+    /// Ports probed at the same time.
+    pub threads: usize,
+    pub connector: Connector,
+}
+
+impl Default for ScanSettings {
+    fn default() -> Self {
+        ScanSettings {
+            timeout: Duration::from_secs(3),
+            threads: 20,
+            connector: Connector::default(),
+        }
+    }
+}
+
+pub fn scan_ports(mut domain: Domain, settings: &ScanSettings) -> Domain {
+    domain.open_port = with_threads(settings.threads, || {
+        TOP_100_PORTS
+            .into_par_iter()
+            .map(|port| scan_port(&domain.domain, *port, settings))
+            .filter(|port| port.state)
+            .collect()
+    });
 
     domain
 }
 
-pub fn scan_port(host: &str, port: u16) -> Port {
-    let timeout = Duration::from_secs(3);
-    let socket_addr: Vec<SocketAddr> = format!("{}:{}", host, port)
-        .to_socket_addrs()
-        .expect("can't create socket address.")
-        .collect();
+/// Scans the same ports as `scan_ports`, yielding each open port as soon as it answers.
+///
+/// The connections are made on the blocking threads of the Tokio runtime; dropping the stream
+/// stops the scan.
+pub fn scan_ports_stream(host: &str, settings: &ScanSettings) -> impl Stream<Item = Port> + Unpin {
+    let (open_tx, open_rx) = mpsc::unbounded_channel();
+    let host = host.to_string();
+    let settings = settings.clone();
+
+    tokio::task::spawn_blocking(move || {
+        with_threads(settings.threads, || {
+            TOP_100_PORTS.into_par_iter().for_each(|port| {
+                if open_tx.is_closed() {
+                    return;
+                }
+                let port = scan_port(&host, *port, &settings);
+                if port.state {
+                    let _ = open_tx.send(port);
+                }
+            })
+        })
+    });
+
+    UnboundedReceiverStream::new(open_rx)
+}
+
+/// Runs `scan` with `threads` connections at most, on the global pool if no other can be made.
+fn with_threads<R: Send>(threads: usize, scan: impl FnOnce() -> R + Send) -> R {
+    match ThreadPoolBuilder::new().num_threads(threads).build() {
+        Ok(pool) => pool.install(scan),
+        Err(_) => scan(),
+    }
+}
+
+pub fn scan_port(host: &str, port: u16, settings: &ScanSettings) -> Port {
+    Port{
+        port,
+        state: settings.connector.connect(host, port, settings.timeout).is_ok(),
+    }
+}
+
+/// A service identified from the banner a port sends (or answers to an HTTP probe).
+#[derive(Debug, Clone, Serialize)]
+pub struct Service {
+    pub port: u16,
+    pub name: String,
+    pub vendor: Option<String>,
+    pub product: Option<String>,
+    pub version: Option<String>,
+    pub cpe: Option<String>,
+    pub banner: String,
+}
+
+/// (service, vendor, product, banner regex with the version as first group if any)
+const BANNER_SIGNATURES: &[(&str, &str, &str, &str)] = &[
+    ("ssh", "openbsd", "openssh", r"^SSH-[\d.]+-OpenSSH_([\w.]+)"),
+    ("ssh", "dropbear_ssh_project", "dropbear_ssh", r"^SSH-[\d.]+-dropbear_([\w.]+)"),
+    ("ftp", "proftpd", "proftpd", r"^220.*ProFTPD ([\d.]+\w*)"),
+    ("ftp", "beasts", "vsftpd", r"^220.*\(vsFTPd ([\d.]+)\)"),
+    ("ftp", "filezilla-project", "filezilla_server", r"^220.*FileZilla Server (?:version )?([\d.]+)"),
+    ("ftp", "pureftpd", "pure-ftpd", r"^220.*Pure-FTPd"),
+    ("smtp", "exim", "exim", r"^220.*ESMTP Exim ([\d.]+)"),
+    ("smtp", "postfix", "postfix", r"^220.*ESMTP Postfix"),
+    ("smtp", "sendmail", "sendmail", r"^220.*Sendmail ([\d.]+)"),
+    ("mysql", "mariadb", "mariadb", r"^(?s).{5}(?:5\.5\.5-)?([\d.]+)-MariaDB"),
+    ("mysql", "oracle", "mysql", r"^(?s).{4}\n([\d.]+)"),
+    ("http", "apache", "http_server", r"(?im)^Server: Apache/([\d.]+)"),
+    ("http", "apache", "tomcat", r"(?im)^Server: Apache-Coyote/([\d.]+)"),
+    ("http", "nginx", "nginx", r"(?im)^Server: nginx/([\d.]+)"),
+    ("http", "microsoft", "internet_information_services", r"(?im)^Server: Microsoft-IIS/([\d.]+)"),
+    ("http", "lighttpd", "lighttpd", r"(?im)^Server: lighttpd/([\d.]+)"),
+    ("http", "eclipse", "jetty", r"(?im)^Server: Jetty\(([\d.]+)"),
+    ("http", "openresty", "openresty", r"(?im)^Server: openresty/([\d.]+)"),
+];
+
+/// The banner signatures with their patterns compiled, once for the whole scan.
+static BANNER_REGEXES: Lazy<Vec<Regex>> = Lazy::new(|| {
+    BANNER_SIGNATURES
+        .iter()
+        .map(|(_, _, _, pattern)| Regex::new(pattern).expect("scanner: Compiling banner signature"))
+        .collect()
+});
+
+/// Reads what the service sends on connect, falling back to an HTTP probe for silent services.
+pub fn grab_banner(host: &str, port: u16, settings: &ScanSettings) -> Option<String> {
+    let mut stream = settings.connector.connect(host, port, settings.timeout).ok()?;
+    stream.set_read_timeout(Some(settings.timeout)).ok()?;
+
+    let mut buffer = [0u8; 1024];
+    let read = match stream.read(&mut buffer) {
+        Ok(read) if read > 0 => read,
+        _ => {
+            let probe = format!("HEAD / HTTP/1.0\r\nHost: {}\r\n\r\n", host);
+            stream.write_all(probe.as_bytes()).ok()?;
+            stream.read(&mut buffer).ok()?
+        }
+    };
 
-    if socket_addr.len() == 0 {
-        return Port{
-            port: port,
-            state: false,
-        };
+    if read == 0 {
+        return None;
     }
 
-    let status = if let Ok(_) = TcpStream::connect_timeout(&socket_addr[0], timeout) {
-        true
-    } else {
-        false
+    Some(String::from_utf8_lossy(&buffer[..read]).to_string())
+}
+
+pub fn identify_service(port: u16, banner: &str) -> Service {
+    let mut service = Service {
+        port,
+        name: "unknown".to_string(),
+        vendor: None,
+        product: None,
+        version: None,
+        cpe: None,
+        banner: banner.trim().to_string(),
     };
 
-    Port{
-        port: port,
-        state: status,
+    for ((name, vendor, product, _), regex) in BANNER_SIGNATURES.iter().zip(BANNER_REGEXES.iter()) {
+        if let Some(captures) = regex.captures(banner) {
+            service.name = name.to_string();
+            service.vendor = Some(vendor.to_string());
+            service.product = Some(product.to_string());
+            service.version = captures.get(1).map(|version| version.as_str().to_string());
+            service.cpe = Cpe::from_service(&service).map(|cpe| cpe.to_string());
+            return service;
+        }
+    }
+
+    if banner.starts_with("SSH-") {
+        service.name = "ssh".to_string();
+    } else if banner.starts_with("HTTP/") {
+        service.name = "http".to_string();
+    } else if banner.starts_with("220") {
+        service.name = "ftp/smtp".to_string();
+    }
+
+    service
+}
+
+pub fn detect_service(host: &str, port: u16, settings: &ScanSettings) -> Option<Service> {
+    grab_banner(host, port, settings).map(|banner| identify_service(port, &banner))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identifies_services_from_their_banners() {
+        let ssh = identify_service(22, "SSH-2.0-OpenSSH_8.2p1 Ubuntu-4ubuntu0.5\r\n");
+        assert_eq!(ssh.name, "ssh");
+        assert_eq!(ssh.product.as_deref(), Some("openssh"));
+        assert_eq!(ssh.version.as_deref(), Some("8.2p1"));
+        assert_eq!(ssh.cpe.as_deref(), Some("cpe:2.3:a:openbsd:openssh:8.2:p1:*:*:*:*:*:*"));
+
+        let http = identify_service(8080, "HTTP/1.1 200 OK\r\nServer: nginx/1.18.0\r\n\r\n");
+        assert_eq!((http.name.as_str(), http.version.as_deref()), ("http", Some("1.18.0")));
+
+        let unknown = identify_service(9000, "HTTP/1.0 404 Not Found\r\nServer: custom\r\n");
+        assert_eq!((unknown.name.as_str(), unknown.product), ("http", None));
+    }
+
+    #[test]
+    fn only_socks5_proxies_relay_connections() {
+        assert!(matches!(Connector::through(None), Some(Connector::Direct)));
+
+        let http = ProxyList::parse(["http://10.0.0.1:8080"]).unwrap();
+        assert!(Connector::through(Some(&http)).is_none());
+
+        let mixed = ProxyList::parse(["http://10.0.0.1:8080", "socks5h://10.0.0.2:1080"]).unwrap();
+        match Connector::through(Some(&mixed)) {
+            Some(Connector::Socks5(proxy)) => assert_eq!(proxy.as_str(), "socks5h://10.0.0.2:1080"),
+            connector => panic!("{:?}", connector),
+        }
     }
 }
@@ -0,0 +1,322 @@
+use crate::commons::output::{ Format, Output };
+use crate::module::error::Error;
+use crate::module::render::{ perform, Action };
+use crate::module::webdriver::{ WebDriverPool, WebDriverSettings };
+use fantoccini::error::CmdError;
+use futures::stream::{ self, StreamExt };
+use serde::Serialize;
+use std::collections::{ BTreeMap, HashSet };
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+use url::Url;
+
+/// A captured page, or why it could not be.
+#[derive(Debug, Clone, Serialize)]
+pub struct Screenshot {
+    pub url: String,
+    /// Where the browser ended up after the redirects.
+    pub final_url: Option<String>,
+    pub title: Option<String>,
+    /// PNG file, relative to the output directory.
+    pub file: Option<String>,
+    pub error: Option<String>,
+}
+
+impl Screenshot {
+    pub fn summary(&self) -> String {
+        let file = match &self.file {
+            Some(file) => file,
+            None => return format!("{} : {}", self.url, self.error.as_deref().unwrap_or("no screenshot")),
+        };
+
+        let mut line = self.final_url.clone().unwrap_or_else(|| self.url.clone());
+        if let Some(title) = &self.title {
+            line.push_str(&format!(" \"{}\"", title));
+        }
+        line.push_str(&format!(" -> {}", file));
+        line
+    }
+}
+
+/// Screenshots web services into a directory, with a `manifest.json` and an `index.html` gallery.
+pub struct Screenshotter {
+    webdriver: WebDriverPool,
+    dir: PathBuf,
+    actions: Vec<Action>,
+    concurrency: usize,
+}
+
+impl Screenshotter {
+    pub fn new(webdriver: WebDriverPool, dir: &str, concurrency: usize) -> Result<Self, Error> {
+        fs::create_dir_all(dir).map_err(|err| Error::Internal(format!("{} : {}", dir, err)))?;
+
+        Ok(Screenshotter {
+            webdriver,
+            dir: PathBuf::from(dir),
+            // Dashboards and login pages often draw themselves after the load event.
+            actions: vec![Action::WaitIdle { quiet_ms: 500, timeout_ms: 5_000 }],
+            concurrency: concurrency.max(1),
+        })
+    }
+
+    /// Actions run on every page before its screenshot.
+    pub fn with_actions(mut self, actions: Vec<Action>) -> Self {
+        self.actions = actions;
+        self
+    }
+
+    /// Opens a first session, failing early when the WebDriver server is unreachable.
+    pub async fn open(&self) -> Result<(), Error> {
+        self.webdriver.checkout().await.map(|_| ())
+    }
+
+    /// Screenshots the URLs, results in the order given.
+    pub async fn capture_all(&self, urls: &[String]) -> Vec<Screenshot> {
+        let mut names = HashSet::new();
+        let targets = urls
+            .iter()
+            .map(|url| {
+                let mut name = file_name(url);
+                let stem = name.trim_end_matches(".png").to_string();
+                let mut suffix = 1;
+                while !names.insert(name.clone()) {
+                    suffix += 1;
+                    name = format!("{}_{}.png", stem, suffix);
+                }
+                (url.clone(), name)
+            })
+            .collect::<Vec<(String, String)>>();
+
+        stream::iter(targets)
+            .map(|(url, name)| async move { self.capture(&url, &name).await })
+            .buffered(self.concurrency)
+            .collect()
+            .await
+    }
+
+    /// Screenshots `url` into `name`, recording the failure in the result if any.
+    pub async fn capture(&self, url: &str, name: &str) -> Screenshot {
+        let mut screenshot = Screenshot {
+            url: url.to_string(),
+            final_url: None,
+            title: None,
+            file: None,
+            error: None,
+        };
+
+        if let Err(err) = self.try_capture(&mut screenshot, name).await {
+            log::warn!("screenshot: {} : {}", url, err);
+            screenshot.error = Some(err.to_string());
+        }
+
+        screenshot
+    }
+
+    async fn try_capture(&self, screenshot: &mut Screenshot, name: &str) -> Result<(), Error> {
+        let client = self.webdriver.checkout().await?;
+
+        match client.goto(&screenshot.url).await {
+            Ok(()) => {}
+            // What loaded so far still shows what the service is.
+            Err(CmdError::Standard(err)) if err.error() == "timeout" => {
+                log::debug!("screenshot: {} still loading", screenshot.url);
+            }
+            Err(err) => return Err(err.into()),
+        }
+
+        perform(&client, &self.actions).await?;
+
+        screenshot.final_url = client.current_url().await.ok().map(|url| url.to_string());
+        screenshot.title = client.title().await.ok().filter(|title| !title.trim().is_empty());
+
+        let png = client.screenshot().await?;
+        let path = self.dir.join(name);
+        fs::write(&path, png).map_err(|err| Error::Internal(format!("{} : {}", path.display(), err)))?;
+        screenshot.file = Some(name.to_string());
+
+        Ok(())
+    }
+
+    /// Writes the manifest and the gallery of `screenshots`, returning the path of the gallery.
+    pub fn write_report(&self, screenshots: &[Screenshot]) -> Result<PathBuf, Error> {
+        let manifest = self.dir.join("manifest.json");
+        let data = serde_json::to_vec_pretty(screenshots).map_err(|err| Error::Internal(err.to_string()))?;
+        fs::write(&manifest, data).map_err(|err| Error::Internal(format!("{} : {}", manifest.display(), err)))?;
+
+        let gallery = self.dir.join("index.html");
+        fs::write(&gallery, gallery_html(screenshots))
+            .map_err(|err| Error::Internal(format!("{} : {}", gallery.display(), err)))?;
+
+        Ok(gallery)
+    }
+}
+
+/// `https://10.0.0.1:8443/admin` as `https_10.0.0.1_8443_admin.png`.
+fn file_name(url: &str) -> String {
+    let name = match Url::parse(url) {
+        Ok(url) => format!(
+            "{}_{}_{}{}",
+            url.scheme(),
+            url.host_str().unwrap_or(""),
+            url.port_or_known_default().unwrap_or(0),
+            url.path().trim_end_matches('/'),
+        ),
+        Err(_) => url.to_string(),
+    };
+
+    let name = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '_' })
+        .take(120)
+        .collect::<String>();
+    format!("{}.png", name)
+}
+
+/// Titles differing only by case, spacing or numbers (hostnames, versions, dates) fall in the same group.
+fn title_group(title: Option<&str>) -> String {
+    let title = match title {
+        Some(title) => title,
+        None => return String::new(),
+    };
+
+    let mut group = String::new();
+    let mut last = ' ';
+    for c in title.trim().to_lowercase().chars() {
+        let c = if c.is_ascii_digit() {
+            '#'
+        } else if c.is_whitespace() {
+            ' '
+        } else {
+            c
+        };
+        if (c == '#' || c == ' ') && c == last {
+            continue;
+        }
+        group.push(c);
+        last = c;
+    }
+
+    group.trim().to_string()
+}
+
+/// A static page of the screenshots, the largest groups of similar titles first and the failures last.
+fn gallery_html(screenshots: &[Screenshot]) -> String {
+    let mut groups: BTreeMap<String, Vec<&Screenshot>> = BTreeMap::new();
+    let mut failures = Vec::new();
+    for screenshot in screenshots {
+        if screenshot.file.is_some() {
+            groups.entry(title_group(screenshot.title.as_deref())).or_default().push(screenshot);
+        } else {
+            failures.push(screenshot);
+        }
+    }
+
+    let mut groups = groups.into_iter().collect::<Vec<(String, Vec<&Screenshot>)>>();
+    groups.sort_by(|(a, a_shots), (b, b_shots)| b_shots.len().cmp(&a_shots.len()).then(a.cmp(b)));
+
+    let mut html = String::from(concat!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>vxsuite screenshots</title>\n<style>\n",
+        "body { font-family: sans-serif; margin: 2em; background: #f4f4f4; }\n",
+        "section { margin-bottom: 2em; }\n",
+        ".grid { display: flex; flex-wrap: wrap; gap: 1em; }\n",
+        ".shot { width: 320px; background: #fff; padding: .5em; box-shadow: 0 1px 3px #aaa; word-break: break-all; }\n",
+        ".shot img { width: 100%; border: 1px solid #ddd; }\n",
+        ".shot p { margin: .3em 0; font-size: .85em; }\n",
+        "</style>\n</head>\n<body>\n",
+    ));
+    html.push_str(&format!(
+        "<h1>{} services, {} groups, {} failed</h1>\n",
+        screenshots.len(),
+        groups.len(),
+        failures.len()
+    ));
+
+    for (_, shots) in &groups {
+        let heading = shots[0].title.as_deref().unwrap_or("(no title)");
+        html.push_str(&format!("<section>\n<h2>{} ({})</h2>\n<div class=\"grid\">\n", escape(heading), shots.len()));
+        for shot in shots {
+            let file = escape(shot.file.as_deref().unwrap_or(""));
+            html.push_str(&format!(
+                "<div class=\"shot\">\n<a href=\"{}\"><img src=\"{}\" loading=\"lazy\"></a>\n<p><a href=\"{}\">{}</a></p>\n",
+                file,
+                file,
+                escape(&shot.url),
+                escape(&shot.url)
+            ));
+            if let Some(final_url) = shot.final_url.as_ref().filter(|final_url| **final_url != shot.url) {
+                html.push_str(&format!("<p>&rarr; {}</p>\n", escape(final_url)));
+            }
+            if let Some(title) = &shot.title {
+                html.push_str(&format!("<p>{}</p>\n", escape(title)));
+            }
+            html.push_str("</div>\n");
+        }
+        html.push_str("</div>\n</section>\n");
+    }
+
+    if !failures.is_empty() {
+        html.push_str("<section>\n<h2>Failed</h2>\n<ul>\n");
+        for shot in failures {
+            html.push_str(&format!(
+                "<li>{} : {}</li>\n",
+                escape(&shot.url),
+                escape(shot.error.as_deref().unwrap_or(""))
+            ));
+        }
+        html.push_str("</ul>\n</section>\n");
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Screenshots `urls` into `dir`, then writes the manifest and the gallery there.
+pub async fn take_screenshots(
+    mut settings: WebDriverSettings,
+    dir: &str,
+    sessions: usize,
+    actions: Option<Vec<Action>>,
+    mut urls: Vec<String>,
+    format: Format,
+) -> Result<(), Error> {
+    let mut seen = HashSet::new();
+    urls.retain(|url| seen.insert(url.clone()));
+    if urls.is_empty() {
+        Output::warning("no web service to screenshot");
+        return Ok(());
+    }
+
+    // A service that never finishes loading must not hold a session for the browser's 5 minutes.
+    settings.page_load_timeout.get_or_insert(Duration::from_secs(30));
+    let mut screenshotter = Screenshotter::new(WebDriverPool::new(&settings, sessions)?, dir, sessions)?;
+    if let Some(actions) = actions {
+        screenshotter = screenshotter.with_actions(actions);
+    }
+    screenshotter.open().await?;
+
+    if format == Format::Text {
+        Output::info(&format!("screenshotting {} web services into {}", urls.len(), dir));
+    }
+    let screenshots = screenshotter.capture_all(&urls).await;
+    for screenshot in &screenshots {
+        match (format, &screenshot.file) {
+            (Format::Text, None) => Output::error(&screenshot.summary()),
+            _ => Output::result(format, &screenshot.summary(), screenshot),
+        }
+    }
+
+    let gallery = screenshotter.write_report(&screenshots)?;
+    if format == Format::Text {
+        Output::info(&format!("gallery written to {}", gallery.display()));
+    }
+
+    Ok(())
+}
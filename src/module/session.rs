@@ -0,0 +1,555 @@
+use crate::module::cache::HttpCache;
+use crate::module::error::Error;
+use crate::module::headers::HeaderRotation;
+use crate::module::request::{ Form, Request };
+use regex::Regex;
+use reqwest::{ header::{ self, HeaderMap }, redirect, Client, ClientBuilder, Method, StatusCode };
+use select::{ document::Document, node::Node, predicate::{ Attr, Name } };
+use serde::de::DeserializeOwned;
+use std::collections::BTreeMap;
+use std::sync::{ atomic::{ AtomicUsize, Ordering }, Mutex };
+use std::time::{ Duration, SystemTime };
+use tokio::sync::Mutex as AsyncMutex;
+use url::Url;
+
+const MAX_REDIRECTS: usize = 10;
+
+/// A cookie set by a server, or injected as if the host it is for had set it.
+#[derive(Debug, Clone)]
+struct Cookie {
+    name: String,
+    value: String,
+    domain: String,
+    host_only: bool,
+    path: String,
+    secure: bool,
+    expires: Option<SystemTime>,
+}
+
+impl Cookie {
+    /// Reads a Set-Cookie header received from `url`, `None` if it is invalid or for another domain.
+    fn parse(url: &Url, set_cookie: &str) -> Option<Cookie> {
+        let mut attributes = set_cookie.split(';');
+        let (name, value) = attributes.next()?.split_once('=')?;
+        let host = url.host_str()?.to_lowercase();
+        if name.trim().is_empty() {
+            return None;
+        }
+
+        let mut cookie = Cookie {
+            name: name.trim().to_string(),
+            value: value.trim().trim_matches('"').to_string(),
+            domain: host.clone(),
+            host_only: true,
+            path: default_path(url),
+            secure: false,
+            expires: None,
+        };
+
+        let mut max_age = None;
+        for attribute in attributes {
+            let (key, value) = match attribute.split_once('=') {
+                Some((key, value)) => (key.trim().to_lowercase(), value.trim()),
+                None => (attribute.trim().to_lowercase(), ""),
+            };
+
+            match key.as_str() {
+                "domain" => {
+                    let domain = value.trim_start_matches('.').to_lowercase();
+                    if domain.is_empty() {
+                        continue;
+                    }
+                    if host != domain && !host.ends_with(&format!(".{}", domain)) {
+                        return None;
+                    }
+                    cookie.domain = domain;
+                    cookie.host_only = false;
+                }
+                "path" if value.starts_with('/') => cookie.path = value.to_string(),
+                "secure" => cookie.secure = true,
+                "max-age" => max_age = value.parse::<i64>().ok(),
+                "expires" => cookie.expires = httpdate::parse_http_date(value).ok(),
+                _ => {}
+            }
+        }
+
+        // Max-Age wins over Expires.
+        if let Some(max_age) = max_age {
+            cookie.expires = Some(match max_age {
+                age if age <= 0 => SystemTime::UNIX_EPOCH,
+                age => SystemTime::now() + Duration::from_secs(age as u64),
+            });
+        }
+
+        Some(cookie)
+    }
+
+    fn expired(&self, now: SystemTime) -> bool {
+        self.expires.is_some_and(|expires| expires <= now)
+    }
+
+    fn matches(&self, url: &Url) -> bool {
+        let host = match url.host_str() {
+            Some(host) => host.to_lowercase(),
+            None => return false,
+        };
+
+        let domain_matches = host == self.domain || (!self.host_only && host.ends_with(&format!(".{}", self.domain)));
+
+        let path = url.path();
+        let path_matches = path == self.path
+            || (path.starts_with(&self.path) && (self.path.ends_with('/') || path[self.path.len()..].starts_with('/')));
+
+        domain_matches && path_matches && (!self.secure || url.scheme() == "https")
+    }
+}
+
+/// The directory of the URL path, the path of its cookies without a Path attribute.
+fn default_path(url: &Url) -> String {
+    match url.path().rfind('/') {
+        Some(0) | None => "/".to_string(),
+        Some(end) => url.path()[..end].to_string(),
+    }
+}
+
+/// The cookies of a spider, sent back with its later requests like a browser would.
+#[derive(Debug, Default)]
+pub struct CookieJar {
+    cookies: Mutex<Vec<Cookie>>,
+}
+
+impl CookieJar {
+    /// Sends `name=value` to the host of `url`, and to no other.
+    pub fn insert(&self, url: &Url, name: &str, value: &str) {
+        let domain = match url.host_str() {
+            Some(host) => host.to_lowercase(),
+            None => return,
+        };
+
+        self.replace(Cookie {
+            name: name.to_string(),
+            value: value.to_string(),
+            domain,
+            host_only: true,
+            path: "/".to_string(),
+            secure: false,
+            expires: None,
+        });
+    }
+
+    /// Keeps the cookies set by a response from `url`.
+    pub fn store(&self, url: &Url, headers: &HeaderMap) {
+        for set_cookie in headers.get_all(header::SET_COOKIE) {
+            match set_cookie.to_str().ok().and_then(|value| Cookie::parse(url, value)) {
+                Some(cookie) => self.replace(cookie),
+                None => log::debug!("session: ignoring cookie {:?} from {}", set_cookie, url),
+            }
+        }
+    }
+
+    /// The cookies to send to `url`, the one with the longest path winning on duplicate names.
+    pub fn cookies(&self, url: &Url) -> BTreeMap<String, String> {
+        let now = SystemTime::now();
+        let mut jar = self.cookies.lock().unwrap();
+        jar.retain(|cookie| !cookie.expired(now));
+
+        let mut matching = jar.iter().filter(|cookie| cookie.matches(url)).collect::<Vec<&Cookie>>();
+        matching.sort_by_key(|cookie| cookie.path.len());
+        matching
+            .into_iter()
+            .map(|cookie| (cookie.name.clone(), cookie.value.clone()))
+            .collect()
+    }
+
+    fn replace(&self, cookie: Cookie) {
+        let mut jar = self.cookies.lock().unwrap();
+        jar.retain(|old| !(old.name == cookie.name && old.domain == cookie.domain && old.path == cookie.path));
+
+        // An expired cookie is how a server deletes one.
+        if !cookie.expired(SystemTime::now()) {
+            jar.push(cookie);
+        }
+    }
+}
+
+/// A response read in full.
+#[derive(Debug, Clone)]
+pub struct Response {
+    /// The URL answering, after the redirects.
+    pub url: Url,
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    /// A 200 HTML page from `url`, e.g. a page source rendered by a browser.
+    pub fn html(url: &str, html: String) -> Result<Self, Error> {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CONTENT_TYPE, header::HeaderValue::from_static("text/html"));
+
+        Ok(Response {
+            url: Url::parse(url).map_err(|err| Error::Internal(format!("{} : {}", url, err)))?,
+            status: StatusCode::OK,
+            headers,
+            body: html.into_bytes(),
+        })
+    }
+
+    pub fn content_type(&self) -> Option<String> {
+        self.headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string())
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, Error> {
+        serde_json::from_slice(&self.body).map_err(|err| {
+            log::debug!("{} : {}", self.url, err);
+            Error::InvalidHttpResponse(self.url.to_string())
+        })
+    }
+}
+
+/// How a spider authenticates to its target.
+#[derive(Debug, Clone)]
+pub enum Login {
+    /// Types `fields` into the form of the `url` page that has a password input, then submits it.
+    Form { url: String, fields: Vec<(String, String)> },
+    /// A session cookie taken from a logged in browser, sent to the host of `url` only.
+    Cookie { url: String, name: String, value: String },
+    /// Sent as `Authorization: Bearer <token>`.
+    Bearer(String),
+}
+
+/// The HTTP client of a spider: its cookie jar, how it logs in and how it notices being logged out.
+pub struct Session {
+    client: Client,
+    jar: CookieJar,
+    login: Option<Login>,
+    logged_out: Option<Regex>,
+    cache: Option<HttpCache>,
+    /// Rotates the identifying headers of the requests, the client's own otherwise.
+    headers: Option<HeaderRotation>,
+    /// Counts the logins, so that the requests finding the session expired together log in once.
+    logins: AtomicUsize,
+    login_lock: AsyncMutex<()>,
+}
+
+impl Session {
+    /// A session with the client of `builder`, whose redirects it follows itself to keep their cookies.
+    pub fn new(builder: ClientBuilder) -> Result<Self, Error> {
+        let client = builder.redirect(redirect::Policy::none()).build()?;
+
+        Ok(Session {
+            client,
+            jar: CookieJar::default(),
+            login: None,
+            logged_out: None,
+            cache: None,
+            headers: None,
+            logins: AtomicUsize::new(0),
+            login_lock: AsyncMutex::new(()),
+        })
+    }
+
+    pub fn with_login(mut self, login: Login) -> Self {
+        self.login = Some(login);
+        self
+    }
+
+    /// Responses whose body matches `pattern` show a logged out session, e.g. "Please sign in".
+    pub fn with_logged_out(mut self, pattern: Regex) -> Self {
+        self.logged_out = Some(pattern);
+        self
+    }
+
+    pub fn with_headers(mut self, headers: HeaderRotation) -> Self {
+        self.headers = Some(headers).filter(|headers| headers.is_rotating());
+        self
+    }
+
+    /// Serves the responses fetched before from `cache`, and records the new ones there.
+    pub fn with_cache(mut self, cache: HttpCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// The cookies the session sends to `url`.
+    pub fn cookies(&self, url: &Url) -> BTreeMap<String, String> {
+        self.jar.cookies(url)
+    }
+
+    pub fn has_login(&self) -> bool {
+        self.login.is_some()
+    }
+
+    /// Logs in to the target, nothing to do without a login or offline.
+    pub async fn login(&self) -> Result<(), Error> {
+        if self.cache.as_ref().is_some_and(|cache| cache.is_offline()) {
+            return Ok(());
+        }
+
+        let _guard = self.login_lock.lock().await;
+        self.log_in().await
+    }
+
+    /// Fetches `request`, from the cache when it has it.
+    pub async fn fetch(&self, request: &Request) -> Result<Response, Error> {
+        let cache = match &self.cache {
+            Some(cache) => cache,
+            None => return self.fetch_live(request).await,
+        };
+
+        if let Some(response) = cache.get(request) {
+            return Ok(response);
+        }
+        if cache.is_offline() {
+            return Err(Error::Cache(format!("{} {} : not cached, offline", request.method, request.url)));
+        }
+
+        let response = self.fetch_live(request).await?;
+        if !response.status.is_server_error() {
+            if let Err(err) = cache.put(request, &response) {
+                log::warn!("{}", err);
+            }
+        }
+
+        Ok(response)
+    }
+
+    /// Fetches `request`, logging in again and retrying once if the response shows the session expired.
+    async fn fetch_live(&self, request: &Request) -> Result<Response, Error> {
+        let logins = self.logins.load(Ordering::SeqCst);
+        let response = self.send(request).await?;
+        if !self.is_expired(request, &response) {
+            return Ok(response);
+        }
+
+        if !matches!(self.login, Some(Login::Form { .. })) {
+            return Err(Error::Session(format!("{} : session expired, renew the cookie or token", request.url)));
+        }
+
+        {
+            let _guard = self.login_lock.lock().await;
+            if self.logins.load(Ordering::SeqCst) == logins {
+                log::warn!("session: expired on {}, logging in again", request.url);
+                self.log_in().await?;
+            }
+        }
+
+        let response = self.send(request).await?;
+        if self.is_expired(request, &response) {
+            return Err(Error::Session(format!("{} : still logged out after logging in again", request.url)));
+        }
+
+        Ok(response)
+    }
+
+    async fn log_in(&self) -> Result<(), Error> {
+        match &self.login {
+            None => return Ok(()),
+            Some(Login::Bearer(_)) => {}
+            Some(Login::Cookie { url, name, value }) => {
+                let url = Url::parse(url).map_err(|err| Error::Session(format!("{} : {}", url, err)))?;
+                self.jar.insert(&url, name, value);
+            }
+            Some(Login::Form { url, fields }) => {
+                let page = self.send(&Request::get(url)).await?;
+                let mut form = Document::from(page.text().as_str())
+                    .select(Name("form"))
+                    .find(has_password)
+                    .map(|node| Form::from_node(&page.url, &node))
+                    .ok_or_else(|| Error::Session(format!("{} : no login form", url)))?;
+
+                for (name, value) in fields {
+                    form.set(name, value);
+                }
+
+                let submission = form.submission(&Request::get(page.url.as_str()))?;
+                let res = self.send(&submission).await?;
+                let still_on_form = Document::from(res.text().as_str())
+                    .select(Name("form"))
+                    .any(|form| has_password(&form));
+                if self.is_expired(&submission, &res) || still_on_form {
+                    return Err(Error::Session(format!("{} : login failed [{}]", url, res.status)));
+                }
+            }
+        }
+
+        self.logins.fetch_add(1, Ordering::SeqCst);
+        log::info!("session: logged in");
+        Ok(())
+    }
+
+    /// A 401, a redirect to the login form or a body matching the logged out pattern.
+    fn is_expired(&self, request: &Request, response: &Response) -> bool {
+        let login_url = match &self.login {
+            None => return false,
+            Some(Login::Form { url, .. }) => Url::parse(url).ok(),
+            Some(_) => None,
+        };
+
+        let redirected_to_login = login_url.is_some_and(|login_url| {
+            response.url.as_str() != request.url
+                && response.url.host_str() == login_url.host_str()
+                && response.url.path() == login_url.path()
+        });
+
+        response.status == StatusCode::UNAUTHORIZED
+            || redirected_to_login
+            || self.logged_out.as_ref().is_some_and(|pattern| pattern.is_match(&response.text()))
+    }
+
+    /// Sends `request` with the cookies of the jar, following its redirects.
+    ///
+    /// The token, the cookies and the headers of the request only go to its own origin: a redirect
+    /// to another one gets the cookies the jar has for it and nothing else.
+    async fn send(&self, request: &Request) -> Result<Response, Error> {
+        let mut request = request.clone();
+        let origin = Url::parse(&request.url)
+            .map_err(|err| Error::Internal(format!("{} : {}", request.url, err)))?
+            .origin();
+
+        for _ in 0..=MAX_REDIRECTS {
+            let url = Url::parse(&request.url)
+                .map_err(|err| Error::Internal(format!("{} : {}", request.url, err)))?;
+            let same_origin = url.origin() == origin;
+
+            let mut cookies = self.jar.cookies(&url);
+            // The headers of the request win over the rotated ones, e.g. an API's `Accept`.
+            let mut headers = self.headers.as_ref().map(|headers| headers.for_url(&url)).unwrap_or_default();
+            if same_origin {
+                cookies.extend(request.cookies.clone());
+                headers.extend(request.headers.clone());
+            } else {
+                log::debug!("session: redirected to {}, not sending the credentials of {}", url, origin.ascii_serialization());
+            }
+            let mut builder = Request { cookies, headers, ..request.clone() }.build(&self.client);
+            if let Some(Login::Bearer(token)) = self.login.as_ref().filter(|_| same_origin) {
+                builder = builder.bearer_auth(token);
+            }
+
+            let res = builder.send().await?;
+            self.jar.store(&url, res.headers());
+
+            let status = res.status();
+            let location = res
+                .headers()
+                .get(header::LOCATION)
+                .and_then(|location| location.to_str().ok())
+                .and_then(|location| url.join(location).ok());
+
+            match location {
+                Some(location) if status.is_redirection() => {
+                    request = if status == StatusCode::TEMPORARY_REDIRECT || status == StatusCode::PERMANENT_REDIRECT {
+                        Request { url: location.to_string(), ..request }
+                    } else {
+                        let mut headers = request.headers;
+                        headers.remove(header::CONTENT_TYPE);
+                        Request { url: location.to_string(), method: Method::GET, headers, body: None, ..request }
+                    };
+                }
+                _ => {
+                    let headers = res.headers().clone();
+                    let body = res.bytes().await?.to_vec();
+                    return Ok(Response { url, status, headers, body });
+                }
+            }
+        }
+
+        Err(Error::InvalidHttpResponse(format!("{} : too many redirects", request.url)))
+    }
+}
+
+fn has_password(form: &Node) -> bool {
+    form.select(Attr("type", "password")).next().is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(value: &str) -> Url {
+        Url::parse(value).unwrap()
+    }
+
+    fn set_cookies(values: &[&str]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for value in values {
+            headers.append(header::SET_COOKIE, value.parse().unwrap());
+        }
+        headers
+    }
+
+    fn jar(from: &str, values: &[&str]) -> CookieJar {
+        let jar = CookieJar::default();
+        jar.store(&url(from), &set_cookies(values));
+        jar
+    }
+
+    fn names(jar: &CookieJar, to: &str) -> Vec<String> {
+        jar.cookies(&url(to)).into_keys().collect()
+    }
+
+    #[test]
+    fn host_only_cookies_stay_on_their_host() {
+        let jar = jar("http://www.example.com/", &["sid=1", "lang=en; Domain=example.com"]);
+
+        assert_eq!(names(&jar, "http://www.example.com/"), vec!["lang", "sid"]);
+        assert_eq!(names(&jar, "http://api.example.com/"), vec!["lang"]);
+        assert!(names(&jar, "http://example.org/").is_empty());
+        assert!(names(&jar, "http://notexample.com/").is_empty());
+    }
+
+    #[test]
+    fn cookies_for_other_domains_are_refused() {
+        let jar = jar("http://www.example.com/", &["sid=1; Domain=example.org", "=nameless"]);
+        assert!(names(&jar, "http://example.org/").is_empty());
+        assert!(names(&jar, "http://www.example.com/").is_empty());
+    }
+
+    #[test]
+    fn paths_and_secure_restrict_the_requests() {
+        let jar = jar(
+            "https://example.com/account/login",
+            &["default=1", "admin=1; Path=/admin", "token=1; Secure; Path=/"],
+        );
+
+        assert_eq!(names(&jar, "https://example.com/account/settings"), vec!["default", "token"]);
+        assert_eq!(names(&jar, "https://example.com/admin/users"), vec!["admin", "token"]);
+        assert!(names(&jar, "https://example.com/administrator").contains(&"token".to_string()));
+        assert!(!names(&jar, "https://example.com/administrator").contains(&"admin".to_string()));
+        assert!(names(&jar, "http://example.com/admin").iter().all(|name| name != "token"));
+    }
+
+    #[test]
+    fn longest_path_wins_on_duplicate_names() {
+        let jar = jar("http://example.com/", &["theme=light; Path=/", "theme=dark; Path=/app"]);
+        assert_eq!(jar.cookies(&url("http://example.com/app/page"))["theme"], "dark");
+        assert_eq!(jar.cookies(&url("http://example.com/"))["theme"], "light");
+    }
+
+    #[test]
+    fn expired_cookies_delete_the_stored_ones() {
+        let jar = jar("http://example.com/", &["sid=1", "old=1; Expires=Wed, 21 Oct 2015 07:28:00 GMT"]);
+        assert_eq!(names(&jar, "http://example.com/"), vec!["sid"]);
+
+        jar.store(&url("http://example.com/logout"), &set_cookies(&["sid=; Path=/; Max-Age=0"]));
+        assert!(names(&jar, "http://example.com/").is_empty());
+    }
+
+    #[test]
+    fn injected_cookies_are_bound_to_their_host() {
+        let jar = CookieJar::default();
+        jar.insert(&url("https://app.example.com/dashboard"), "session", "secret");
+
+        assert_eq!(jar.cookies(&url("https://app.example.com/other"))["session"], "secret");
+        assert!(names(&jar, "https://evil.example.net/").is_empty());
+        assert!(names(&jar, "https://sub.app.example.com/").is_empty());
+    }
+}
@@ -0,0 +1,349 @@
+use async_trait::async_trait;
+use crate::commons::output::Output;
+use crate::module::cache::HttpCache;
+use crate::module::crawler::{ HttpSettings, Spider };
+use crate::module::fetch::{ FetchMode, Fetcher };
+use crate::module::render::Action;
+use crate::module::request::{ Form, Request };
+use crate::module::error::Error;
+use crate::module::session::{ Login, Session };
+use crate::module::webdriver::WebDriverPool;
+use reqwest::{ Method, header::{ self, HeaderMap } };
+use regex::Regex;
+use serde::Serialize;
+use select::{ document::Document, predicate::Name };
+use std::collections::{ BTreeMap, BTreeSet };
+use std::sync::{ Arc, Mutex };
+use url::Url;
+
+/// Links followed from the start URL before the crawl stops, bounding the pages generated on the fly.
+pub const DEFAULT_MAX_DEPTH: usize = 10;
+
+/// Link-following spider that maps every in-scope endpoint of a web target.
+pub struct SiteMapper {
+    session: Session,
+    fetcher: Fetcher,
+    start_url: Url,
+    include_subdomains: bool,
+    max_depth: usize,
+    js_literal_regex: Regex,
+    logout_regex: Regex,
+    start_request: Option<Request>,
+    headers: HeaderMap,
+    cookies: BTreeMap<String, String>,
+    form_values: Vec<(String, String)>,
+    /// Origins of the endpoints found so far, the web services of the target.
+    services: Mutex<BTreeSet<String>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Endpoint {
+    pub url: String,
+    pub method: String,
+    pub status: u16,
+    pub content_type: Option<String>,
+    pub title: Option<String>,
+    pub parameters: Vec<String>,
+    pub forms: Vec<Form>,
+}
+
+impl SiteMapper {
+    pub fn new(settings: &HttpSettings, target: &str, include_subdomains: bool) -> Result<Self, Error> {
+        let start_url = Url::parse(target)
+            .map_err(|err| Error::InvalidSpider(format!("sitemap: {} : {}", target, err)))?;
+
+        if start_url.scheme() != "http" && start_url.scheme() != "https" {
+            return Err(Error::InvalidSpider(format!("sitemap: {} is not an HTTP(S) url", target)));
+        }
+
+        let session = Session::new(settings.client_builder())?.with_headers(settings.headers.clone());
+
+        let js_literal_regex = Regex::new(r#"["'`]((?:https?://|/)[A-Za-z0-9_\-\./:?=&%~+]+)["'`]"#)
+            .expect("spiders/sitemap: Compiling js literal regex");
+        let logout_regex = Regex::new(r"(?i)log_?out|sign_?out|log_?off")
+            .expect("spiders/sitemap: Compiling logout regex");
+
+        Ok(SiteMapper {
+            session,
+            fetcher: Fetcher::new(FetchMode::Static),
+            start_url,
+            include_subdomains,
+            max_depth: DEFAULT_MAX_DEPTH,
+            js_literal_regex,
+            logout_regex,
+            start_request: None,
+            headers: HeaderMap::new(),
+            cookies: BTreeMap::new(),
+            form_values: Vec::new(),
+            services: Mutex::new(BTreeSet::new()),
+        })
+    }
+
+    /// Starts from `request` instead of a GET of the target, e.g. to POST a search.
+    /// Its headers and cookies are then sent with every request of the crawl.
+    pub fn with_start_request(mut self, request: Request) -> Self {
+        self.headers = request.headers.clone();
+        self.headers.remove(header::CONTENT_TYPE);
+        self.cookies = request.cookies.clone();
+        self.start_request = Some(request);
+        self
+    }
+
+    /// Stops following links `max_depth` links away from the start URL.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Values typed into the matching fields of the GET forms the crawl submits.
+    pub fn with_form_values(mut self, values: Vec<(String, String)>) -> Self {
+        self.form_values = values;
+        self
+    }
+
+    /// Logs in with `login` before crawling, and again whenever the session expires.
+    pub fn with_login(mut self, login: Login) -> Self {
+        self.session = self.session.with_login(login);
+        self
+    }
+
+    /// Responses whose body matches `pattern` show that the session expired.
+    pub fn with_logged_out(mut self, pattern: Regex) -> Self {
+        self.session = self.session.with_logged_out(pattern);
+        self
+    }
+
+    pub fn with_cache(mut self, cache: HttpCache) -> Self {
+        self.fetcher = self.fetcher.with_cache(&cache);
+        self.session = self.session.with_cache(cache);
+        self
+    }
+
+    /// Renders the pages with `webdriver` as `mode` says, all of them being fetched over HTTP otherwise.
+    pub fn with_rendering(mut self, mode: FetchMode, webdriver: Arc<WebDriverPool>) -> Self {
+        self.fetcher = self.fetcher.with_mode(mode).with_webdriver(webdriver);
+        self
+    }
+
+    /// Renders the pages whose static HTML lacks one of `selectors` in auto mode.
+    pub fn with_expected(mut self, selectors: &[String]) -> Result<Self, Error> {
+        self.fetcher = self.fetcher.with_expected(selectors)?;
+        Ok(self)
+    }
+
+    /// Runs `actions` on every rendered page before reading its links.
+    pub fn with_actions(mut self, actions: Vec<Action>) -> Self {
+        self.fetcher = self.fetcher.with_actions(actions);
+        self
+    }
+
+    /// The root URL of every host and port an endpoint was found on.
+    pub fn services(&self) -> Vec<String> {
+        self.services.lock().unwrap().iter().cloned().collect()
+    }
+
+    fn follow(&self, parent: &Request, request: Request) -> Request {
+        Request {
+            headers: self.headers.clone(),
+            cookies: self.cookies.clone(),
+            depth: parent.depth + 1,
+            ..request
+        }
+    }
+
+    fn in_scope(&self, url: &Url) -> bool {
+        if url.scheme() != "http" && url.scheme() != "https" {
+            return false;
+        }
+
+        let (host, target) = match (url.host_str(), self.start_url.host_str()) {
+            (Some(host), Some(target)) => (host, target),
+            _ => return false,
+        };
+
+        if host == target {
+            return url.port_or_known_default() == self.start_url.port_or_known_default();
+        }
+
+        self.include_subdomains && host.ends_with(&format!(".{}", target))
+    }
+
+    fn resolve(&self, base: &Url, link: &str) -> Option<Url> {
+        let link = link.trim();
+        if link.is_empty() || link.starts_with('#') {
+            return None;
+        }
+
+        let mut url = base.join(link).ok()?;
+        url.set_fragment(None);
+
+        // Logging out would only make the session log in again.
+        if self.session.has_login() && self.logout_regex.is_match(url.path()) {
+            return None;
+        }
+
+        if self.in_scope(&url) {
+            Some(url)
+        } else {
+            None
+        }
+    }
+
+    fn extract_links(&self, base: &Url, document: &Document) -> BTreeSet<String> {
+        let mut links = BTreeSet::new();
+        let attributes = [
+            ("a", "href"),
+            ("form", "action"),
+            ("script", "src"),
+            ("link", "href"),
+            ("img", "src"),
+        ];
+
+        for (tag, attr) in attributes.iter() {
+            for node in document.select(Name(*tag)) {
+                if let Some(url) = node.attr(attr).and_then(|link| self.resolve(base, link)) {
+                    links.insert(url.to_string());
+                }
+            }
+        }
+
+        for script in document.select(Name("script")) {
+            links.extend(self.extract_js_links(base, &script.text()));
+        }
+
+        links
+    }
+
+    fn extract_js_links(&self, base: &Url, source: &str) -> BTreeSet<String> {
+        self.js_literal_regex
+            .captures_iter(source)
+            .filter_map(|captures| captures.get(1))
+            .filter(|literal| !literal.as_str().starts_with("//"))
+            .filter_map(|literal| self.resolve(base, literal.as_str()))
+            .map(|url| url.to_string())
+            .collect()
+    }
+}
+
+#[async_trait]
+impl Spider for SiteMapper {
+    type Item = Endpoint;
+
+    fn name(&self) -> String {
+        String::from("sitemap")
+    }
+
+    fn start_urls(&self) -> Vec<String> {
+        vec![self.start_url.to_string()]
+    }
+
+    fn start_requests(&self) -> Vec<Request> {
+        match &self.start_request {
+            Some(request) => vec![request.clone()],
+            None => vec![Request::get(self.start_url.as_str())],
+        }
+    }
+
+    async fn open(&self) -> Result<(), Error> {
+        self.session.login().await?;
+        self.fetcher.open().await
+    }
+
+    async fn scrapy(&self, request: Request) -> Result<(Vec<Self::Item>, Vec<Request>), Error> {
+        let url = request.url.clone();
+        log::info!("visiting: {}", url);
+
+        let res = self.fetcher.fetch(&self.session, &request).await?;
+        let final_url = res.url.clone();
+        let status = res.status.as_u16();
+        let content_type = res.content_type();
+        let body = res.text();
+
+        let mut endpoint = Endpoint {
+            url: url.clone(),
+            method: request.method.to_string(),
+            status,
+            content_type: content_type.clone(),
+            title: None,
+            parameters: final_url.query_pairs().map(|(key, _)| key.into_owned()).collect(),
+            forms: Vec::new(),
+        };
+
+        let mut links = BTreeSet::new();
+        let mut submissions = Vec::new();
+        if final_url.as_str() != url && self.in_scope(&final_url) {
+            links.insert(final_url.to_string());
+        }
+
+        let content_type = content_type.unwrap_or_default();
+        if content_type.contains("html") {
+            let document = Document::from(body.as_str());
+            endpoint.title = document
+                .select(Name("title"))
+                .next()
+                .map(|title| title.text().trim().to_string())
+                .filter(|title| !title.is_empty());
+            endpoint.forms = Form::all(&final_url, &document);
+            links.extend(self.extract_links(&final_url, &document));
+
+            // GET forms are submitted with their default values, like any other link.
+            for form in endpoint.forms.iter().filter(|form| form.method == "GET") {
+                let in_scope = Url::parse(&form.action).map(|action| self.in_scope(&action)).unwrap_or(false);
+                if in_scope {
+                    let mut form = form.clone();
+                    for (name, value) in &self.form_values {
+                        form.set(name, value);
+                    }
+                    submissions.push(form.submission(&request)?);
+                }
+            }
+        } else if content_type.contains("javascript") {
+            links.extend(self.extract_js_links(&final_url, &body));
+        }
+
+        if request.depth >= self.max_depth {
+            log::debug!("not following the links of {}, {} links away from the start", url, request.depth);
+            return Ok((vec![endpoint], Vec::new()));
+        }
+
+        let requests = links
+            .iter()
+            .map(|link| Request::get(link))
+            .chain(submissions)
+            .map(|follow| self.follow(&request, follow))
+            .collect();
+
+        Ok((vec![endpoint], requests))
+    }
+
+    async fn process(&self, item: Self::Item) -> Result<(), Error> {
+        if let Ok(url) = Url::parse(&item.url) {
+            self.services.lock().unwrap().insert(format!("{}/", url.origin().ascii_serialization()));
+        }
+
+        let mut line = format!(
+            "{}{} [{}] {}",
+            if item.method == Method::GET.as_str() { String::new() } else { format!("{} ", item.method) },
+            item.url,
+            item.status,
+            item.content_type.as_deref().unwrap_or("-"),
+        );
+
+        if let Some(title) = &item.title {
+            line.push_str(&format!(" \"{}\"", title));
+        }
+
+        if !item.parameters.is_empty() {
+            line.push_str(&format!(" params: {}", item.parameters.join(",")));
+        }
+
+        Output::success(&line);
+
+        for form in &item.forms {
+            let inputs = form.fields.iter().map(|(name, _)| name.as_str()).collect::<Vec<&str>>();
+            Output::info(&format!("\tform {} {} inputs: {}", form.method, form.action, inputs.join(",")));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,54 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+
+/// Subdomains discovered for a target domain, with the sources that revealed each of them.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SubdomainResults {
+    pub domain: String,
+    pub subdomains: BTreeMap<String, Vec<String>>,
+}
+
+impl SubdomainResults {
+    /// Collects names under `domain`; an empty domain (e.g. an IP target) accepts every name.
+    pub fn new(domain: &str) -> Self {
+        SubdomainResults {
+            domain: domain.trim_end_matches('.').to_lowercase(),
+            subdomains: BTreeMap::new(),
+        }
+    }
+
+    /// Guesses the registered domain of `host` from its last two labels.
+    pub fn for_host(host: &str) -> Self {
+        if host.parse::<IpAddr>().is_ok() {
+            return SubdomainResults::new("");
+        }
+
+        let labels = host.trim_end_matches('.').split('.').collect::<Vec<&str>>();
+        let domain = labels[labels.len().saturating_sub(2)..].join(".");
+
+        SubdomainResults::new(&domain)
+    }
+
+    /// Records `name` when it belongs to the domain, returns true if it was not known yet.
+    pub fn add(&mut self, name: &str, source: &str) -> bool {
+        let name = name.trim().trim_start_matches("*.").trim_end_matches('.').to_lowercase();
+
+        if name.is_empty() || name.contains('*') {
+            return false;
+        }
+
+        if !self.domain.is_empty() && name != self.domain && !name.ends_with(&format!(".{}", self.domain)) {
+            return false;
+        }
+
+        let sources = self.subdomains.entry(name).or_default();
+        let is_new = sources.is_empty();
+
+        if !sources.iter().any(|known| known == source) {
+            sources.push(source.to_string());
+        }
+
+        is_new
+    }
+}
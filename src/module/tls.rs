@@ -0,0 +1,694 @@
+use crate::commons::output::{ Format, Output };
+use crate::module::error::Error;
+use crate::module::scanner::{ Connector, ScanSettings };
+use crate::module::subdomain::SubdomainResults;
+use rustls::{
+    client::{ ServerCertVerified, ServerCertVerifier },
+    version::{ TLS12, TLS13 },
+    Certificate, ClientConfig, ProtocolVersion, ServerName, SupportedProtocolVersion,
+};
+use serde::Serialize;
+use std::{ convert::TryFrom, sync::Arc, time::{ Duration, SystemTime } };
+use tokio::{
+    io::{ AsyncReadExt, AsyncWriteExt },
+    net::TcpStream,
+    time::timeout,
+};
+use tokio_rustls::TlsConnector;
+use x509_parser::{
+    certificate::X509Certificate,
+    extensions::GeneralName,
+    objects::{ oid2sn, oid_registry },
+    prelude::FromDer,
+    public_key::PublicKey,
+    time::ASN1Time,
+};
+
+/// Certificates expiring sooner than this are flagged.
+const EXPIRY_WARNING_DAYS: i64 = 30;
+
+/// Accepts every certificate: we want to inspect what the server sends, not to trust it.
+struct AcceptAnyCertificate;
+
+impl ServerCertVerifier for AcceptAnyCertificate {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &Certificate,
+        _intermediates: &[Certificate],
+        _server_name: &ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: SystemTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CertificateInfo {
+    pub subject: String,
+    pub issuer: String,
+    pub serial: String,
+    pub sans: Vec<String>,
+    pub not_before: String,
+    pub not_after: String,
+    pub key_type: String,
+    pub key_size: usize,
+    pub signature_algorithm: String,
+    pub is_ca: bool,
+    pub self_signed: bool,
+    pub expired: bool,
+    pub issues: Vec<String>,
+}
+
+impl CertificateInfo {
+    pub fn from_der(der: &[u8]) -> Result<Self, Error> {
+        let (_, cert) = X509Certificate::from_der(der)
+            .map_err(|err| Error::Tls(format!("parsing certificate: {}", err)))?;
+
+        let sans = match cert.subject_alternative_name() {
+            Ok(Some(extension)) => extension
+                .value
+                .general_names
+                .iter()
+                .filter_map(|name| match name {
+                    GeneralName::DNSName(dns) => Some(dns.to_string()),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        let (key_type, key_size) = match cert.public_key().parsed() {
+            Ok(PublicKey::RSA(rsa)) => ("RSA", rsa_key_size(rsa.modulus)),
+            Ok(PublicKey::EC(ec)) => ("EC", ec.key_size()),
+            Ok(PublicKey::DSA(key)) => ("DSA", key.len() * 8),
+            Ok(key) => ("unknown", key.key_size()),
+            Err(_) => ("unknown", 0),
+        };
+
+        let registry = oid_registry();
+        let signature_oid = &cert.signature_algorithm.algorithm;
+        let signature_algorithm = oid2sn(signature_oid, registry)
+            .map(|name| name.to_string())
+            .unwrap_or_else(|_| signature_oid.to_id_string());
+
+        let validity = cert.validity();
+        let now = ASN1Time::now();
+        let expired = validity.not_after.timestamp() < now.timestamp();
+        let self_signed = cert.subject() == cert.issuer();
+
+        let mut info = CertificateInfo {
+            subject: cert.subject().to_string(),
+            issuer: cert.issuer().to_string(),
+            serial: cert.raw_serial_as_string(),
+            sans,
+            not_before: validity.not_before.to_string(),
+            not_after: validity.not_after.to_string(),
+            key_type: key_type.to_string(),
+            key_size,
+            signature_algorithm,
+            is_ca: cert.is_ca(),
+            self_signed,
+            expired,
+            issues: Vec::new(),
+        };
+
+        if expired {
+            info.issues.push("expired".to_string());
+        } else if validity.not_before.timestamp() > now.timestamp() {
+            info.issues.push("not yet valid".to_string());
+        } else if validity.not_after.timestamp() - now.timestamp() < EXPIRY_WARNING_DAYS * 24 * 3600 {
+            info.issues.push(format!("expires within {} days", EXPIRY_WARNING_DAYS));
+        }
+
+        if self_signed {
+            info.issues.push("self-signed".to_string());
+        }
+
+        let weak_key = match key_type {
+            "RSA" | "DSA" => key_size < 2048,
+            "EC" => key_size < 256,
+            _ => false,
+        };
+        if weak_key {
+            info.issues.push(format!("weak {} key ({} bits)", key_type, key_size));
+        }
+
+        let signature = info.signature_algorithm.to_lowercase();
+        if signature.contains("md5") || signature.contains("sha1") || signature.contains("md2") {
+            info.issues.push(format!("weak signature algorithm ({})", info.signature_algorithm));
+        }
+
+        Ok(info)
+    }
+
+    /// Whether the certificate names cover `host`, honouring single label wildcards.
+    pub fn covers(&self, host: &str) -> bool {
+        let host = host.to_lowercase();
+
+        self.sans.iter().any(|name| {
+            let name = name.to_lowercase();
+            match name.strip_prefix("*.") {
+                Some(suffix) => host
+                    .split_once('.')
+                    .map(|(_, parent)| parent == suffix)
+                    .unwrap_or(false),
+                None => name == host,
+            }
+        })
+    }
+}
+
+fn rsa_key_size(modulus: &[u8]) -> usize {
+    let modulus = match modulus.iter().position(|byte| *byte != 0) {
+        Some(start) => &modulus[start..],
+        None => return 0,
+    };
+
+    modulus.len() * 8 - modulus[0].leading_zeros() as usize
+}
+
+/// Result of probing a single TLS protocol version.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProtocolSupport {
+    pub version: String,
+    pub supported: bool,
+    pub cipher: Option<String>,
+}
+
+impl ProtocolSupport {
+    fn supported(version: &str, cipher: &str) -> Self {
+        ProtocolSupport { version: version.to_string(), supported: true, cipher: Some(cipher.to_string()) }
+    }
+
+    fn unsupported(version: &str) -> Self {
+        ProtocolSupport { version: version.to_string(), supported: false, cipher: None }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TlsReport {
+    pub host: String,
+    pub port: u16,
+    pub chain: Vec<CertificateInfo>,
+    pub hostname_matches: bool,
+    pub protocols: Vec<ProtocolSupport>,
+}
+
+impl TlsReport {
+    pub fn issues(&self) -> Vec<String> {
+        let mut issues = Vec::new();
+
+        if let Some(leaf) = self.chain.first() {
+            issues.extend(leaf.issues.iter().cloned());
+        }
+
+        if !self.hostname_matches && self.host.parse::<std::net::IpAddr>().is_err() {
+            issues.push(format!("certificate does not cover {}", self.host));
+        }
+
+        for protocol in self.protocols.iter().filter(|protocol| protocol.supported) {
+            if protocol.version.starts_with("SSL") || protocol.version == "TLSv1.0" || protocol.version == "TLSv1.1" {
+                issues.push(format!("deprecated protocol {} enabled", protocol.version));
+            }
+        }
+
+        issues
+    }
+
+    pub fn summary(&self) -> String {
+        let mut line = format!("{}:{}", self.host, self.port);
+
+        if let Some(leaf) = self.chain.first() {
+            line.push_str(&format!(
+                " subject: {} issuer: {} valid: {} - {} key: {} {} sig: {}",
+                leaf.subject,
+                leaf.issuer,
+                leaf.not_before,
+                leaf.not_after,
+                leaf.key_type,
+                leaf.key_size,
+                leaf.signature_algorithm,
+            ));
+        }
+
+        let protocols = self
+            .protocols
+            .iter()
+            .filter(|protocol| protocol.supported)
+            .map(|protocol| protocol.version.as_str())
+            .collect::<Vec<&str>>();
+        line.push_str(&format!(" protocols: {}", protocols.join(",")));
+
+        line
+    }
+
+    /// DNS names from the leaf certificate, wildcards stripped.
+    pub fn names(&self) -> Vec<String> {
+        self.chain
+            .first()
+            .map(|leaf| {
+                leaf.sans
+                    .iter()
+                    .map(|name| name.trim_start_matches("*.").to_lowercase())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+pub struct TlsProbe {
+    wait: Duration,
+    connector: Connector,
+}
+
+impl TlsProbe {
+    pub fn new(wait: Duration) -> Self {
+        TlsProbe { wait, connector: Connector::Direct }
+    }
+
+    /// Reaches the targets the way the port scan does, e.g. through its SOCKS5 pivot.
+    pub fn with_connector(mut self, connector: Connector) -> Self {
+        self.connector = connector;
+        self
+    }
+
+    /// Extracts the certificate chain of `host:port` and enumerates the protocol versions it accepts.
+    pub async fn probe(&self, host: &str, port: u16) -> Result<TlsReport, Error> {
+        let mut protocols = Vec::new();
+        let mut legacy_chain = Vec::new();
+
+        for (name, version) in LEGACY_VERSIONS.iter() {
+            let hello = timeout(self.wait, legacy_handshake(&self.connector, host, port, self.wait, *version))
+                .await
+                .ok()
+                .and_then(|res| res.map_err(|err| log::debug!("tls: {}:{} {} : {}", host, port, name, err)).ok())
+                .flatten();
+
+            if let Some(hello) = &hello {
+                if !hello.certificates.is_empty() {
+                    legacy_chain = hello.certificates.clone();
+                }
+            }
+
+            protocols.push(ProtocolSupport {
+                version: name.to_string(),
+                supported: hello.is_some(),
+                cipher: hello.map(|hello| format!("0x{:04X}", hello.cipher)),
+            });
+        }
+
+        // Offered both versions, a server settles on 1.3 when it speaks it: only 1.2 is left to try then.
+        let modern = handshake(&self.connector, host, port, self.wait, &[&TLS13, &TLS12]).await;
+        let negotiated = modern.as_ref().ok().and_then(|info| info.version);
+        let (tls12, tls13) = match (&modern, negotiated) {
+            (Ok(info), Some(ProtocolVersion::TLSv1_3)) => (
+                self.probe_modern(host, port, "TLSv1.2", &TLS12).await,
+                ProtocolSupport::supported("TLSv1.3", &info.cipher),
+            ),
+            (Ok(info), Some(ProtocolVersion::TLSv1_2)) => (
+                ProtocolSupport::supported("TLSv1.2", &info.cipher),
+                ProtocolSupport::unsupported("TLSv1.3"),
+            ),
+            _ => (ProtocolSupport::unsupported("TLSv1.2"), ProtocolSupport::unsupported("TLSv1.3")),
+        };
+        protocols.push(tls12);
+        protocols.push(tls13);
+        let modern = modern.map(|info| info.certificates);
+
+        // Servers stuck on legacy versions still hand out their chain in the clear.
+        let chain = match modern {
+            Ok(chain) => chain,
+            Err(_) if !legacy_chain.is_empty() => legacy_chain,
+            Err(err) => return Err(err),
+        };
+
+        let chain = chain
+            .iter()
+            .map(|der| CertificateInfo::from_der(der))
+            .collect::<Result<Vec<CertificateInfo>, Error>>()?;
+
+        let hostname_matches = chain.first().map(|leaf| leaf.covers(host)).unwrap_or(false);
+
+        Ok(TlsReport {
+            host: host.to_string(),
+            port,
+            chain,
+            hostname_matches,
+            protocols,
+        })
+    }
+
+    async fn probe_modern(
+        &self,
+        host: &str,
+        port: u16,
+        name: &str,
+        version: &'static SupportedProtocolVersion,
+    ) -> ProtocolSupport {
+        let cipher = handshake(&self.connector, host, port, self.wait, &[version])
+            .await
+            .map(|info| info.cipher)
+            .map_err(|err| log::debug!("tls: {}:{} {} : {}", host, port, name, err))
+            .ok();
+
+        ProtocolSupport {
+            version: name.to_string(),
+            supported: cipher.is_some(),
+            cipher,
+        }
+    }
+}
+
+struct HandshakeInfo {
+    certificates: Vec<Vec<u8>>,
+    cipher: String,
+    version: Option<ProtocolVersion>,
+}
+
+/// A connection to `host:port` through `connector`, whose connect blocks.
+async fn connect(connector: &Connector, host: &str, port: u16, wait: Duration) -> std::io::Result<TcpStream> {
+    let connector = connector.clone();
+    let host = host.to_string();
+    let stream = tokio::task::spawn_blocking(move || connector.connect(&host, port, wait)).await??;
+
+    stream.set_nonblocking(true)?;
+    TcpStream::from_std(stream)
+}
+
+async fn handshake(
+    connector: &Connector,
+    host: &str,
+    port: u16,
+    wait: Duration,
+    versions: &[&'static SupportedProtocolVersion],
+) -> Result<HandshakeInfo, Error> {
+    let config = ClientConfig::builder()
+        .with_safe_default_cipher_suites()
+        .with_safe_default_kx_groups()
+        .with_protocol_versions(versions)
+        .map_err(|err| Error::Tls(err.to_string()))?
+        .with_custom_certificate_verifier(Arc::new(AcceptAnyCertificate))
+        .with_no_client_auth();
+
+    let server_name = ServerName::try_from(host)
+        .map_err(|err| Error::Tls(format!("{} : {}", host, err)))?;
+
+    let connect = async {
+        let stream = connect(connector, host, port, wait).await?;
+        TlsConnector::from(Arc::new(config)).connect(server_name, stream).await
+    };
+
+    let stream = timeout(wait, connect)
+        .await
+        .map_err(|_| Error::Tls(format!("{}:{} : handshake timed out", host, port)))?
+        .map_err(|err| Error::Tls(format!("{}:{} : {}", host, port, err)))?;
+
+    let connection = stream.get_ref().1;
+    let certificates = connection
+        .peer_certificates()
+        .map(|chain| chain.iter().map(|cert| cert.0.clone()).collect())
+        .unwrap_or_default();
+    let cipher = connection
+        .negotiated_cipher_suite()
+        .map(|suite| format!("{:?}", suite.suite()))
+        .unwrap_or_default();
+
+    Ok(HandshakeInfo { certificates, cipher, version: connection.protocol_version() })
+}
+
+/// Performs a TLS handshake through `connector` and returns the DER encoded chain sent by the server, leaf first.
+pub async fn peer_certificates(connector: &Connector, host: &str, port: u16, wait: Duration) -> Result<Vec<Vec<u8>>, Error> {
+    handshake(connector, host, port, wait, &[&TLS13, &TLS12])
+        .await
+        .map(|info| info.certificates)
+}
+
+/// Protocol versions rustls refuses to speak, probed with a hand written ClientHello.
+const LEGACY_VERSIONS: &[(&str, u16)] = &[("SSLv3", 0x0300), ("TLSv1.0", 0x0301), ("TLSv1.1", 0x0302)];
+
+/// A broad list of pre TLS 1.2 cipher suites so that old servers find something they like.
+const LEGACY_CIPHERS: &[u16] = &[
+    0xc014, 0xc013, 0xc00a, 0xc009, 0x0039, 0x0033, 0x0035, 0x002f, 0x000a, 0x0005, 0x0004,
+    0x0016, 0x0013, 0x0009, 0x0003,
+];
+
+/// What a server answered to a legacy ClientHello.
+struct LegacyHello {
+    cipher: u16,
+    certificates: Vec<Vec<u8>>,
+}
+
+/// Sends a ClientHello offering only `version` and reads the plaintext handshake messages
+/// up to ServerHelloDone. Returns `None` when the server answers with an alert or with a
+/// different version.
+async fn legacy_handshake(
+    connector: &Connector,
+    host: &str,
+    port: u16,
+    wait: Duration,
+    version: u16,
+) -> Result<Option<LegacyHello>, Error> {
+    let mut stream = connect(connector, host, port, wait)
+        .await
+        .map_err(|err| Error::Tls(err.to_string()))?;
+
+    stream
+        .write_all(&client_hello(host, version))
+        .await
+        .map_err(|err| Error::Tls(err.to_string()))?;
+
+    let mut messages = Vec::new();
+    let mut hello: Option<LegacyHello> = None;
+
+    loop {
+        let mut header = [0u8; 5];
+        if stream.read_exact(&mut header).await.is_err() {
+            return Ok(hello);
+        }
+
+        let length = u16::from_be_bytes([header[3], header[4]]) as usize;
+        let mut record = vec![0u8; length];
+        if stream.read_exact(&mut record).await.is_err() {
+            return Ok(hello);
+        }
+
+        // 0x16 is a handshake record, anything else (usually an alert) ends the exchange.
+        if header[0] != 0x16 {
+            return Ok(hello);
+        }
+        messages.extend_from_slice(&record);
+
+        // handshake messages: type(1) length(3) body
+        while messages.len() >= 4 {
+            let length = u32::from_be_bytes([0, messages[1], messages[2], messages[3]]) as usize;
+            if messages.len() < 4 + length {
+                break;
+            }
+
+            let message = messages.drain(..4 + length).collect::<Vec<u8>>();
+            let body = &message[4..];
+
+            match message[0] {
+                0x02 => match parse_server_hello(body, version) {
+                    Some(cipher) => {
+                        hello = Some(LegacyHello {
+                            cipher,
+                            certificates: Vec::new(),
+                        })
+                    }
+                    None => return Ok(None),
+                },
+                0x0b => {
+                    if let Some(hello) = hello.as_mut() {
+                        hello.certificates = parse_certificates(body);
+                    }
+                }
+                0x0e => return Ok(hello),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Returns the selected cipher suite if the server agreed on `version`.
+fn parse_server_hello(body: &[u8], version: u16) -> Option<u16> {
+    // version(2) random(32) session id length(1)
+    if body.len() < 35 || u16::from_be_bytes([body[0], body[1]]) != version {
+        return None;
+    }
+
+    let cipher_offset = 35 + body[34] as usize;
+    if body.len() < cipher_offset + 2 {
+        return None;
+    }
+
+    Some(u16::from_be_bytes([body[cipher_offset], body[cipher_offset + 1]]))
+}
+
+fn parse_certificates(body: &[u8]) -> Vec<Vec<u8>> {
+    let mut certificates = Vec::new();
+    let mut offset = 3;
+
+    while offset + 3 <= body.len() {
+        let length = u32::from_be_bytes([0, body[offset], body[offset + 1], body[offset + 2]]) as usize;
+        offset += 3;
+
+        if offset + length > body.len() {
+            break;
+        }
+
+        certificates.push(body[offset..offset + length].to_vec());
+        offset += length;
+    }
+
+    certificates
+}
+
+fn client_hello(host: &str, version: u16) -> Vec<u8> {
+    let mut hello = Vec::new();
+    hello.extend_from_slice(&version.to_be_bytes());
+
+    let random: [u8; 32] = rand::random();
+    hello.extend_from_slice(&random);
+    hello.push(0);
+
+    hello.extend_from_slice(&((LEGACY_CIPHERS.len() * 2) as u16).to_be_bytes());
+    for cipher in LEGACY_CIPHERS {
+        hello.extend_from_slice(&cipher.to_be_bytes());
+    }
+    hello.extend_from_slice(&[1, 0]);
+
+    // SSLv3 has no extensions and IP addresses must not be sent as SNI.
+    if version > 0x0300 && host.parse::<std::net::IpAddr>().is_err() {
+        let name = host.as_bytes();
+        let mut sni = Vec::new();
+        sni.extend_from_slice(&((name.len() + 3) as u16).to_be_bytes());
+        sni.push(0);
+        sni.extend_from_slice(&(name.len() as u16).to_be_bytes());
+        sni.extend_from_slice(name);
+
+        let mut extensions = vec![0, 0];
+        extensions.extend_from_slice(&(sni.len() as u16).to_be_bytes());
+        extensions.extend_from_slice(&sni);
+
+        hello.extend_from_slice(&(extensions.len() as u16).to_be_bytes());
+        hello.extend_from_slice(&extensions);
+    }
+
+    let mut handshake = vec![0x01];
+    handshake.extend_from_slice(&(hello.len() as u32).to_be_bytes()[1..]);
+    handshake.extend_from_slice(&hello);
+
+    let mut record = vec![0x16];
+    record.extend_from_slice(&version.min(0x0301).to_be_bytes());
+    record.extend_from_slice(&(handshake.len() as u16).to_be_bytes());
+    record.extend_from_slice(&handshake);
+
+    record
+}
+
+/// Probes `ports` of `host` for TLS and feeds the certificate names into the subdomain results.
+pub async fn inspect_tls(host: &str, ports: &[u16], format: Format, report_failures: bool, scan_settings: &ScanSettings) {
+    let probe = TlsProbe::new(scan_settings.timeout).with_connector(scan_settings.connector.clone());
+    let mut subdomains = SubdomainResults::for_host(host);
+
+    for port in ports {
+        let report = match probe.probe(host, *port).await {
+            Ok(report) => report,
+            Err(err) => {
+                if report_failures {
+                    Output::error(&err.to_string());
+                }
+                continue;
+            }
+        };
+
+        Output::result(format, &report.summary(), &report);
+
+        if format == Format::Text {
+            for issue in report.issues() {
+                Output::warning(&format!("{}:{} {}", host, port, issue));
+            }
+        }
+
+        for name in report.names() {
+            if subdomains.add(&name, "tls-certificate") && format == Format::Text {
+                Output::info(&format!("subdomain found via certificate: {}", name));
+            }
+        }
+    }
+
+    if format == Format::Json && !subdomains.subdomains.is_empty() {
+        Output::result(format, "", &subdomains);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn certificate(name: &str) -> CertificateInfo {
+        let path = format!("{}/fixtures/tls/{}", env!("CARGO_MANIFEST_DIR"), name);
+        let pem = std::fs::read(&path).unwrap();
+        let der = rustls_pemfile::certs(&mut pem.as_slice()).unwrap().remove(0);
+        CertificateInfo::from_der(&der).unwrap()
+    }
+
+    #[test]
+    fn reads_the_certificate_fields() {
+        let leaf = certificate("leaf.pem");
+
+        assert_eq!(leaf.subject, "O=vxsuite tests, CN=localhost");
+        assert_eq!(leaf.issuer, "O=vxsuite tests, CN=vxsuite test CA");
+        assert_eq!(leaf.sans, vec!["localhost", "*.vx.test"]);
+        assert_eq!((leaf.key_type.as_str(), leaf.key_size), ("RSA", 2048));
+        assert_eq!(leaf.signature_algorithm, "sha256WithRSAEncryption");
+        assert!(!leaf.is_ca && !leaf.self_signed && !leaf.expired);
+        assert!(leaf.issues.is_empty(), "{:?}", leaf.issues);
+
+        assert!(certificate("ca.pem").is_ca);
+    }
+
+    #[test]
+    fn flags_expired_self_signed_and_weak_certificates() {
+        let old = certificate("expired.pem");
+
+        assert!(old.expired && old.self_signed);
+        assert_eq!(
+            old.issues,
+            vec![
+                "expired",
+                "self-signed",
+                "weak RSA key (1024 bits)",
+                "weak signature algorithm (sha1WithRSAEncryption)",
+            ]
+        );
+    }
+
+    #[test]
+    fn wildcards_cover_a_single_label() {
+        let leaf = certificate("leaf.pem");
+
+        assert!(leaf.covers("localhost"));
+        assert!(leaf.covers("www.VX.test"));
+        assert!(!leaf.covers("vx.test"));
+        assert!(!leaf.covers("a.b.vx.test"));
+    }
+
+    #[test]
+    fn parses_legacy_server_hellos() {
+        // version, random, session id of 2 bytes, cipher.
+        let mut hello = vec![0x03, 0x01];
+        hello.extend_from_slice(&[0u8; 32]);
+        hello.extend_from_slice(&[2, 0xaa, 0xbb, 0xc0, 0x14, 0]);
+
+        assert_eq!(parse_server_hello(&hello, 0x0301), Some(0xc014));
+        assert_eq!(parse_server_hello(&hello, 0x0302), None);
+        assert_eq!(parse_server_hello(&hello[..36], 0x0301), None);
+
+        let certificates = [0, 0, 9, 0, 0, 2, 1, 2, 0, 0, 1, 3];
+        assert_eq!(parse_certificates(&certificates), vec![vec![1, 2], vec![3]]);
+    }
+}
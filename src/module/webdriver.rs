@@ -0,0 +1,227 @@
+use crate::module::error::Error;
+use fantoccini::{ error::NewSessionError, wd::{ Capabilities, TimeoutConfiguration }, Client, ClientBuilder };
+use serde_json::{ json, Value };
+use std::fmt;
+use std::ops::Deref;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::{ Semaphore, SemaphorePermit };
+use url::Url;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Browser {
+    Chrome,
+    Firefox,
+}
+
+impl Browser {
+    pub fn from_name(name: &str) -> Option<Browser> {
+        match name.to_lowercase().as_str() {
+            "chrome" | "chromium" => Some(Browser::Chrome),
+            "firefox" => Some(Browser::Firefox),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Browser {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Browser::Chrome => write!(f, "chrome"),
+            Browser::Firefox => write!(f, "firefox"),
+        }
+    }
+}
+
+/// WebDriver configuration shared by the modules rendering pages in a browser.
+#[derive(Debug, Clone)]
+pub struct WebDriverSettings {
+    pub url: String,
+    pub browser: Browser,
+    pub headless: bool,
+    pub window_size: Option<(u32, u32)>,
+    /// How long a navigation may take before it is interrupted, the driver's own default otherwise.
+    pub page_load_timeout: Option<Duration>,
+    /// `http://`, `https://` or `socks5://` proxy the browser goes through.
+    pub proxy: Option<String>,
+    pub user_agent: Option<String>,
+    /// Command line arguments of the browser.
+    pub args: Vec<String>,
+}
+
+impl Default for WebDriverSettings {
+    fn default() -> Self {
+        WebDriverSettings {
+            // geckodriver and Selenium listen there, chromedriver once given `--port=4444`.
+            url: "http://localhost:4444".to_string(),
+            browser: Browser::Chrome,
+            headless: true,
+            window_size: None,
+            page_load_timeout: None,
+            proxy: None,
+            user_agent: None,
+            args: Vec::new(),
+        }
+    }
+}
+
+impl WebDriverSettings {
+    pub fn capabilities(&self) -> Result<Capabilities, Error> {
+        let mut caps = Capabilities::new();
+        caps.insert("browserName".to_string(), json!(self.browser.to_string()));
+        // Self-signed and expired certificates are the norm on internal services.
+        caps.insert("acceptInsecureCerts".to_string(), json!(true));
+
+        if let Some(proxy) = &self.proxy {
+            caps.insert("proxy".to_string(), proxy_capability(proxy)?);
+        }
+
+        let mut args = Vec::new();
+        match self.browser {
+            Browser::Chrome => {
+                if self.headless {
+                    args.extend(["--headless".to_string(), "--disable-gpu".to_string()]);
+                }
+                if let Some(user_agent) = &self.user_agent {
+                    args.push(format!("--user-agent={}", user_agent));
+                }
+                args.extend(self.args.iter().cloned());
+                caps.insert("goog:chromeOptions".to_string(), json!({ "args": args }));
+            }
+            Browser::Firefox => {
+                if self.headless {
+                    args.push("-headless".to_string());
+                }
+                args.extend(self.args.iter().cloned());
+                let mut options = json!({ "args": args });
+                if let Some(user_agent) = &self.user_agent {
+                    options["prefs"] = json!({ "general.useragent.override": user_agent });
+                }
+                caps.insert("moz:firefoxOptions".to_string(), options);
+            }
+        }
+
+        Ok(caps)
+    }
+}
+
+/// The W3C proxy capability for a proxy URL.
+fn proxy_capability(proxy: &str) -> Result<Value, Error> {
+    let url = Url::parse(proxy).map_err(|err| Error::WebDriver(format!("proxy {} : {}", proxy, err)))?;
+    let address = match (url.host_str(), url.port_or_known_default()) {
+        (Some(host), Some(port)) => format!("{}:{}", host, port),
+        _ => return Err(Error::WebDriver(format!("proxy {} has no host and port", proxy))),
+    };
+
+    match url.scheme() {
+        "http" | "https" => Ok(json!({ "proxyType": "manual", "httpProxy": address, "sslProxy": address })),
+        "socks5" | "socks5h" => Ok(json!({ "proxyType": "manual", "socksProxy": address, "socksVersion": 5 })),
+        scheme => Err(Error::WebDriver(format!("proxy {} : unsupported scheme {}", proxy, scheme))),
+    }
+}
+
+/// WebDriver sessions shared by the pages to render, at most `size` of them open at once.
+pub struct WebDriverPool {
+    settings: WebDriverSettings,
+    capabilities: Capabilities,
+    idle: Mutex<Vec<Client>>,
+    permits: Semaphore,
+}
+
+/// A session checked out of the pool, returned to it when dropped.
+pub struct PooledClient<'a> {
+    client: Option<Client>,
+    pool: &'a WebDriverPool,
+    _permit: SemaphorePermit<'a>,
+}
+
+impl WebDriverPool {
+    /// A pool opening its sessions on demand, none before the first checkout.
+    pub fn new(settings: &WebDriverSettings, size: usize) -> Result<Self, Error> {
+        Ok(WebDriverPool {
+            settings: settings.clone(),
+            capabilities: settings.capabilities()?,
+            idle: Mutex::new(Vec::new()),
+            permits: Semaphore::new(size.max(1)),
+        })
+    }
+
+    /// A live session, an idle one if any answers or else a new one, waiting while all are checked out.
+    pub async fn checkout(&self) -> Result<PooledClient<'_>, Error> {
+        let permit = self
+            .permits
+            .acquire()
+            .await
+            .map_err(|err| Error::WebDriver(err.to_string()))?;
+
+        loop {
+            let idle = self.idle.lock().unwrap().pop();
+            let client = match idle {
+                Some(client) => client,
+                None => break,
+            };
+
+            // Sessions die with their browser or on the driver timeout.
+            match client.current_url().await {
+                Ok(_) => return Ok(PooledClient { client: Some(client), pool: self, _permit: permit }),
+                Err(err) => log::warn!("webdriver: dropping dead session: {}", err),
+            }
+        }
+
+        let client = self.connect().await?;
+        Ok(PooledClient { client: Some(client), pool: self, _permit: permit })
+    }
+
+    async fn connect(&self) -> Result<Client, Error> {
+        let url = &self.settings.url;
+        log::info!("webdriver: opening a {} session on {}", self.settings.browser, url);
+
+        let client = ClientBuilder::rustls()
+            .capabilities(self.capabilities.clone())
+            .connect(url)
+            .await
+            .map_err(|err| match err {
+                NewSessionError::Failed(_) | NewSessionError::Lost(_) => Error::WebDriver(format!(
+                    "no WebDriver reachable on {} ({}), start geckodriver or chromedriver --port=4444, or pass --webdriver",
+                    url, err
+                )),
+                NewSessionError::SessionNotCreated(_) => Error::WebDriver(format!(
+                    "{} could not start {} : {}",
+                    url, self.settings.browser, err
+                )),
+                err => Error::WebDriver(format!("{} : {}", url, err)),
+            })?;
+
+        if let Some((width, height)) = self.settings.window_size {
+            client.set_window_size(width, height).await?;
+        }
+        if let Some(timeout) = self.settings.page_load_timeout {
+            client.update_timeouts(TimeoutConfiguration::new(None, Some(timeout), None)).await?;
+        }
+
+        Ok(client)
+    }
+}
+
+impl PooledClient<'_> {
+    /// Closes a session that failed instead of returning it, the next checkout opening a new one.
+    pub fn discard(mut self) {
+        self.client = None;
+    }
+}
+
+impl Deref for PooledClient<'_> {
+    type Target = Client;
+
+    fn deref(&self) -> &Client {
+        self.client.as_ref().unwrap()
+    }
+}
+
+impl Drop for PooledClient<'_> {
+    fn drop(&mut self) {
+        if let Some(client) = self.client.take() {
+            self.pool.idle.lock().unwrap().push(client);
+        }
+    }
+}
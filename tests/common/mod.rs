@@ -0,0 +1,263 @@
+//! A throwaway HTTP/1.1 server the tests point the spiders and clients at.
+
+#![allow(dead_code)]
+
+use rustls::{ Certificate, PrivateKey, ServerConfig, SupportedProtocolVersion };
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::atomic::{ AtomicUsize, Ordering };
+use std::sync::Arc;
+use tokio::io::{ AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt };
+use tokio::net::{ TcpListener, TcpStream };
+use tokio_rustls::TlsAcceptor;
+use url::Url;
+
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    pub method: String,
+    /// Path and query, as in the request line.
+    pub target: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl HttpRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn path(&self) -> &str {
+        self.target.split('?').next().unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct HttpReply {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl HttpReply {
+    pub fn new(status: u16, content_type: &str, body: impl Into<Vec<u8>>) -> Self {
+        HttpReply {
+            status,
+            headers: vec![("Content-Type".to_string(), content_type.to_string())],
+            body: body.into(),
+        }
+    }
+
+    pub fn html(body: impl Into<Vec<u8>>) -> Self {
+        HttpReply::new(200, "text/html; charset=utf-8", body)
+    }
+
+    pub fn json(body: impl Into<Vec<u8>>) -> Self {
+        HttpReply::new(200, "application/json", body)
+    }
+
+    pub fn not_found() -> Self {
+        HttpReply::new(404, "text/plain", "not found")
+    }
+
+    pub fn redirect(location: &str) -> Self {
+        HttpReply::new(302, "text/plain", "").with_header("Location", location)
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+/// Serves the answers of `handler` on a random local port until the test ends.
+pub async fn serve<F>(handler: F) -> SocketAddr
+where
+    F: Fn(&HttpRequest) -> HttpReply + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let handler = Arc::new(handler);
+
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let handler = handler.clone();
+            tokio::spawn(async move {
+                let _ = answer(stream, handler.as_ref()).await;
+            });
+        }
+    });
+
+    addr
+}
+
+/// Serves the answers of `handler` over TLS speaking `versions`, with the certificate of
+/// fixtures/tls/leaf.pem (`localhost`, `vx.test`) and its CA.
+pub async fn serve_tls<F>(versions: &[&'static SupportedProtocolVersion], handler: F) -> SocketAddr
+where
+    F: Fn(&HttpRequest) -> HttpReply + Send + Sync + 'static,
+{
+    let fixture = |name: &str| std::fs::read(Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/tls").join(name)).unwrap();
+    let chain = ["leaf.pem", "ca.pem"]
+        .iter()
+        .flat_map(|name| rustls_pemfile::certs(&mut fixture(name).as_slice()).unwrap())
+        .map(Certificate)
+        .collect::<Vec<Certificate>>();
+    let key = rustls_pemfile::pkcs8_private_keys(&mut fixture("leaf.key").as_slice()).unwrap().remove(0);
+
+    let config = ServerConfig::builder()
+        .with_safe_default_cipher_suites()
+        .with_safe_default_kx_groups()
+        .with_protocol_versions(versions)
+        .unwrap()
+        .with_no_client_auth()
+        .with_single_cert(chain, PrivateKey(key))
+        .unwrap();
+    let acceptor = TlsAcceptor::from(Arc::new(config));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let handler = Arc::new(handler);
+
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let (acceptor, handler) = (acceptor.clone(), handler.clone());
+            tokio::spawn(async move {
+                if let Ok(stream) = acceptor.accept(stream).await {
+                    let _ = answer(stream, handler.as_ref()).await;
+                }
+            });
+        }
+    });
+
+    addr
+}
+
+/// A SOCKS5 pivot relaying every CONNECT to the local port asked for, counting the connections.
+pub async fn socks5_pivot() -> (Url, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = Url::parse(&format!("socks5h://{}", listener.local_addr().unwrap())).unwrap();
+    let connections = Arc::new(AtomicUsize::new(0));
+
+    let counted = connections.clone();
+    tokio::spawn(async move {
+        while let Ok((mut client, _)) = listener.accept().await {
+            counted.fetch_add(1, Ordering::SeqCst);
+            tokio::spawn(async move {
+                let mut greeting = [0u8; 2];
+                client.read_exact(&mut greeting).await?;
+                let mut methods = vec![0u8; greeting[1] as usize];
+                client.read_exact(&mut methods).await?;
+                client.write_all(&[0x05, 0x00]).await?;
+
+                // VER CMD RSV ATYP, then the address and the port.
+                let mut request = [0u8; 4];
+                client.read_exact(&mut request).await?;
+                let length = match request[3] {
+                    0x01 => 4,
+                    0x04 => 16,
+                    _ => client.read_u8().await? as usize,
+                };
+                let mut address = vec![0u8; length];
+                client.read_exact(&mut address).await?;
+                let port = client.read_u16().await?;
+
+                let mut target = TcpStream::connect(("127.0.0.1", port)).await?;
+                client.write_all(&[0x05, 0x00, 0x00, 0x01, 0, 0, 0, 0, 0, 0]).await?;
+                tokio::io::copy_bidirectional(&mut client, &mut target).await?;
+                Ok::<(), std::io::Error>(())
+            });
+        }
+    });
+
+    (url, connections)
+}
+
+/// Serves the files of `dir`.
+pub async fn serve_dir(dir: &Path) -> SocketAddr {
+    let dir = dir.to_path_buf();
+    serve(move |request| file(&dir, request)).await
+}
+
+/// The file of `dir` at the path of `request`, `/` being `index.html`.
+pub fn file(dir: &Path, request: &HttpRequest) -> HttpReply {
+    let path = match request.path() {
+        "/" => "index.html",
+        path => path.trim_start_matches('/'),
+    };
+    let content_type = match Path::new(path).extension().and_then(|extension| extension.to_str()) {
+        Some("html") => "text/html; charset=utf-8",
+        Some("js") => "application/javascript",
+        Some("json") => "application/json",
+        _ => "text/plain",
+    };
+    match std::fs::read(dir.join(path)) {
+        Ok(body) => HttpReply::new(200, content_type, body),
+        Err(_) => HttpReply::not_found(),
+    }
+}
+
+async fn answer<S, F>(mut stream: S, handler: &F) -> std::io::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+    F: Fn(&HttpRequest) -> HttpReply,
+{
+    loop {
+        let request = match read_request(&mut stream).await? {
+            Some(request) => request,
+            None => return Ok(()),
+        };
+        let reply = handler(&request);
+
+        let mut head = format!("HTTP/1.1 {} X\r\nContent-Length: {}\r\n", reply.status, reply.body.len());
+        for (name, value) in &reply.headers {
+            head.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        head.push_str("\r\n");
+        stream.write_all(head.as_bytes()).await?;
+        stream.write_all(&reply.body).await?;
+    }
+}
+
+async fn read_request<S: AsyncRead + Unpin>(stream: &mut S) -> std::io::Result<Option<HttpRequest>> {
+    let mut data = Vec::new();
+    let mut buffer = [0u8; 4096];
+    let end = loop {
+        if let Some(end) = data.windows(4).position(|window| window == b"\r\n\r\n") {
+            break end;
+        }
+        let read = stream.read(&mut buffer).await?;
+        if read == 0 {
+            return Ok(None);
+        }
+        data.extend_from_slice(&buffer[..read]);
+    };
+
+    let head = String::from_utf8_lossy(&data[..end]).to_string();
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().unwrap_or_default().split(' ');
+    let method = request_line.next().unwrap_or_default().to_string();
+    let target = request_line.next().unwrap_or_default().to_string();
+    let headers = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .collect::<Vec<(String, String)>>();
+
+    let length = headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.parse::<usize>().ok())
+        .unwrap_or(0);
+    let mut body = data[end + 4..].to_vec();
+    while body.len() < length {
+        let read = stream.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        body.extend_from_slice(&buffer[..read]);
+    }
+
+    Ok(Some(HttpRequest { method, target, headers, body }))
+}
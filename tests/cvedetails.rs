@@ -0,0 +1,57 @@
+mod common;
+
+use common::{ serve, HttpReply };
+use std::path::Path;
+use std::sync::atomic::{ AtomicBool, Ordering };
+use std::sync::Arc;
+use std::time::Duration;
+use vxsuite::{ Crawler, CveDetails, CveStore, HttpSettings };
+
+/// Serves the recorded list as page 1 and every CVE page, page 2 answering 500 while `broken` is set.
+async fn cvedetails(broken: Arc<AtomicBool>) -> String {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/spiders/cvedetails");
+    let list = std::fs::read(dir.join("list.html")).unwrap();
+    let detail = std::fs::read(dir.join("detail.html")).unwrap();
+
+    let addr = serve(move |request| match request.path() {
+        "/vulnerability-list.php" if request.target.contains("page=1") => HttpReply::html(list.clone()),
+        "/vulnerability-list.php" if broken.load(Ordering::SeqCst) => HttpReply::new(500, "text/plain", "oops"),
+        "/vulnerability-list.php" => HttpReply::html("<html><body></body></html>"),
+        path if path.starts_with("/cve/") => HttpReply::html(detail.clone()),
+        _ => HttpReply::not_found(),
+    })
+    .await;
+
+    format!("http://{}", addr)
+}
+
+async fn crawl(base_url: &str, store: &Arc<CveStore>) {
+    let spider = CveDetails::new(&HttpSettings::default())
+        .with_base_url(base_url)
+        .with_store(store.clone())
+        .with_since("2021-01-01");
+
+    Crawler::new(Duration::ZERO, 2, 10).run(Arc::new(spider)).await.unwrap();
+}
+
+#[tokio::test]
+async fn failed_pages_keep_the_crawl_state() {
+    let path = std::env::temp_dir().join(format!("vxsuite-cvedetails-{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let store = Arc::new(CveStore::open(&path).unwrap());
+    store.record_update("cvedetails", "2022-01-01").unwrap();
+
+    let broken = Arc::new(AtomicBool::new(true));
+    let base_url = cvedetails(broken.clone()).await;
+
+    // The rows of page 1 are saved, but page 2 is missing so the next run has to start over.
+    crawl(&base_url, &store).await;
+    assert!(store.get("CVE-2021-44228").unwrap().is_some());
+    assert_eq!(store.last_update("cvedetails").unwrap().as_deref(), Some("2022-01-01"));
+
+    broken.store(false, Ordering::SeqCst);
+    crawl(&base_url, &store).await;
+    assert_eq!(store.last_update("cvedetails").unwrap().as_deref(), Some("2022-07-12"));
+
+    let _ = std::fs::remove_file(&path);
+}
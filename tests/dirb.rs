@@ -0,0 +1,49 @@
+mod common;
+
+use common::HttpReply;
+use vxsuite::{ DirBuster, DirbOptions, HttpSettings };
+
+/// Answers every unknown path with a "soft 404": a 200 page quoting the path.
+async fn soft_404_site() -> String {
+    let addr = common::serve(|request| match request.path() {
+        "/admin" => HttpReply::redirect("/admin/"),
+        "/admin/" => HttpReply::html("<html><body>Administration</body></html>"),
+        "/admin/users.php" => HttpReply::html("<html><body>users</body></html>"),
+        "/robots.txt" => HttpReply::new(200, "text/plain", "User-agent: *\nDisallow: /admin\n"),
+        path => HttpReply::html(format!("<html><body>Sorry, {} does not exist here.</body></html>", path)),
+    })
+    .await;
+
+    format!("http://{}/", addr)
+}
+
+fn wordlist() -> Vec<String> {
+    ["admin", "robots.txt", "users", "backup", "old"].iter().map(|word| word.to_string()).collect()
+}
+
+async fn run(options: DirbOptions) -> Vec<String> {
+    let root = soft_404_site().await;
+    let buster = DirBuster::new(&HttpSettings::default(), &root, wordlist(), options).unwrap();
+    let mut paths = buster
+        .run()
+        .await
+        .into_iter()
+        .map(|hit| hit.url.trim_start_matches(root.as_str()).to_string())
+        .collect::<Vec<String>>();
+    paths.sort();
+    paths
+}
+
+#[tokio::test]
+async fn calibration_drops_soft_404s() {
+    let options = DirbOptions { extensions: vec!["php".to_string()], recursive: true, ..DirbOptions::default() };
+
+    assert_eq!(run(options).await, vec!["admin", "admin/users.php", "robots.txt"]);
+}
+
+#[tokio::test]
+async fn without_calibration_soft_404s_are_reported() {
+    let options = DirbOptions { calibrate: false, ..DirbOptions::default() };
+
+    assert_eq!(run(options).await, vec!["admin", "backup", "old", "robots.txt", "users"]);
+}
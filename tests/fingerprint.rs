@@ -0,0 +1,60 @@
+mod common;
+
+use common::{ serve_tls, socks5_pivot, HttpReply, HttpRequest };
+use std::path::Path;
+use std::sync::atomic::Ordering;
+use vxsuite::{ HttpFingerprinter, HttpSettings, ProxyList };
+
+/// A PHP application on nginx behind two redirects, with its favicon in a `<link>`.
+async fn application() -> String {
+    let icon = std::fs::read(Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/fingerprint/favicon.ico")).unwrap();
+    let addr = common::serve(move |request: &HttpRequest| match request.path() {
+        "/" => HttpReply::redirect("/login"),
+        "/login" => HttpReply::redirect("/app/"),
+        "/app/" => HttpReply::html(
+            r#"<html><head><title> Admin console </title><link rel="shortcut icon" href="/static/icon.ico"></head></html>"#,
+        )
+        .with_header("Server", "nginx/1.18.0")
+        .with_header("X-Powered-By", "PHP/8.1.2")
+        .with_header("Set-Cookie", "PHPSESSID=abc; path=/"),
+        "/static/icon.ico" => HttpReply::new(200, "image/x-icon", icon.clone()),
+        _ => HttpReply::not_found(),
+    })
+    .await;
+
+    format!("http://{}/", addr)
+}
+
+#[tokio::test]
+async fn fingerprints_the_page_the_redirects_lead_to() {
+    let root = application().await;
+    let fingerprint = HttpFingerprinter::new(&HttpSettings::default()).unwrap().fingerprint(&root).await.unwrap();
+
+    assert_eq!(fingerprint.redirects, vec![root.clone(), format!("{}login", root)]);
+    assert_eq!(fingerprint.url, format!("{}app/", root));
+    assert_eq!(fingerprint.status, 200);
+    assert_eq!(fingerprint.title.as_deref(), Some("Admin console"));
+    assert_eq!(fingerprint.server.as_deref(), Some("nginx/1.18.0"));
+    assert_eq!(fingerprint.powered_by.as_deref(), Some("PHP/8.1.2"));
+    assert_eq!(fingerprint.favicon_hash, Some(-265101478));
+    assert!(fingerprint.certificate.is_none());
+
+    let technologies = fingerprint.technologies.iter().map(|technology| technology.name.as_str()).collect::<Vec<&str>>();
+    assert_eq!(technologies, vec!["nginx", "PHP"]);
+}
+
+#[tokio::test]
+async fn reads_the_certificate_through_a_socks5_proxy() {
+    let addr = serve_tls(rustls::ALL_VERSIONS, |_| HttpReply::html("<title>Portal</title>")).await;
+    let (proxy, connections) = socks5_pivot().await;
+    let settings = HttpSettings { proxies: Some(ProxyList::parse([proxy.as_str()]).unwrap()), ..HttpSettings::default() };
+
+    let url = format!("https://localhost:{}/", addr.port());
+    let fingerprint = HttpFingerprinter::new(&settings).unwrap().fingerprint(&url).await.unwrap();
+
+    assert_eq!(fingerprint.title.as_deref(), Some("Portal"));
+    let certificate = fingerprint.certificate.unwrap();
+    assert_eq!(certificate.subject, "O=vxsuite tests, CN=localhost");
+    // The page and the missing favicon over one pooled connection, then the handshake reading the certificate.
+    assert_eq!(connections.load(Ordering::SeqCst), 2);
+}
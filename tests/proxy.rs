@@ -0,0 +1,28 @@
+mod common;
+
+use common::{ serve, HttpReply };
+use vxsuite::{ HttpSettings, ProxyList };
+
+#[tokio::test]
+async fn rotating_proxies_take_the_requests_in_turn() {
+    let first = serve(|request| HttpReply::html(format!("first {}", request.target))).await;
+    let second = serve(|request| HttpReply::html(format!("second {}", request.target))).await;
+    let proxies = ProxyList::parse([format!("http://{}", first), format!("http://{}", second)]).unwrap();
+    let client = HttpSettings { proxies: Some(proxies), ..HttpSettings::default() }.client_builder().build().unwrap();
+
+    let mut answers = Vec::new();
+    for page in 1..=4 {
+        let res = client.get(format!("http://target.test/{}", page)).send().await.unwrap();
+        answers.push(res.text().await.unwrap());
+    }
+
+    assert_eq!(
+        answers,
+        vec![
+            "first http://target.test/1",
+            "second http://target.test/2",
+            "first http://target.test/3",
+            "second http://target.test/4",
+        ]
+    );
+}
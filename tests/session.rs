@@ -0,0 +1,68 @@
+mod common;
+
+use common::{ HttpReply, HttpRequest };
+use std::sync::{ Arc, Mutex };
+use vxsuite::{ HttpSettings, Login, Request, Session };
+
+/// A server recording the requests it gets, answering them with `reply`.
+async fn recording(reply: HttpReply) -> (String, Arc<Mutex<Vec<HttpRequest>>>) {
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let recorded = requests.clone();
+    let addr = common::serve(move |request: &HttpRequest| {
+        recorded.lock().unwrap().push(request.clone());
+        reply.clone()
+    })
+    .await;
+
+    (format!("http://{}", addr), requests)
+}
+
+fn session(login: Login) -> Session {
+    Session::new(HttpSettings::default().client_builder()).unwrap().with_login(login)
+}
+
+#[tokio::test]
+async fn credentials_do_not_follow_redirects_to_other_origins() {
+    // localhost rather than 127.0.0.1, so that the other server is another host too.
+    let (other, other_requests) = recording(HttpReply::html("elsewhere")).await;
+    let other = other.replace("127.0.0.1", "localhost");
+    let (target, target_requests) = recording(HttpReply::redirect(&format!("{}/landing", other))).await;
+
+    let request = Request::get(&format!("{}/start", target))
+        .with_header("X-Api-Key", "secret-key")
+        .unwrap()
+        .with_cookie("sid", "secret-cookie");
+    let response = session(Login::Bearer("secret-token".to_string())).fetch(&request).await.unwrap();
+    assert_eq!(response.text(), "elsewhere");
+
+    let sent = target_requests.lock().unwrap()[0].clone();
+    assert_eq!(sent.header("Authorization"), Some("Bearer secret-token"));
+    assert_eq!(sent.header("X-Api-Key"), Some("secret-key"));
+    assert_eq!(sent.header("Cookie"), Some("sid=secret-cookie"));
+
+    let redirected = other_requests.lock().unwrap()[0].clone();
+    assert_eq!(redirected.path(), "/landing");
+    assert_eq!(redirected.header("Authorization"), None);
+    assert_eq!(redirected.header("X-Api-Key"), None);
+    assert_eq!(redirected.header("Cookie"), None);
+}
+
+#[tokio::test]
+async fn session_cookie_is_only_sent_to_its_host() {
+    let (other, other_requests) = recording(HttpReply::html("elsewhere")).await;
+    let other = other.replace("127.0.0.1", "localhost");
+    let (target, target_requests) = recording(HttpReply::html("dashboard")).await;
+
+    let session = session(Login::Cookie {
+        url: format!("{}/", target),
+        name: "session".to_string(),
+        value: "secret".to_string(),
+    });
+    session.login().await.unwrap();
+
+    session.fetch(&Request::get(&format!("{}/dashboard", target))).await.unwrap();
+    session.fetch(&Request::get(&format!("{}/", other))).await.unwrap();
+
+    assert_eq!(target_requests.lock().unwrap()[0].header("Cookie"), Some("session=secret"));
+    assert_eq!(other_requests.lock().unwrap()[0].header("Cookie"), None);
+}
@@ -0,0 +1,120 @@
+mod common;
+
+use common::{ HttpReply, HttpRequest };
+use futures::StreamExt;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use vxsuite::{ CrawlEvent, Crawler, Endpoint, Error, HttpSettings, SiteMapper };
+
+/// Serves fixtures/site, plus a calendar whose every page links to the next one.
+async fn fixture_site() -> String {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/site");
+    let addr = common::serve(move |request: &HttpRequest| {
+        if request.path() == "/calendar" {
+            let page = request
+                .target
+                .rsplit('=')
+                .next()
+                .and_then(|page| page.parse::<usize>().ok())
+                .unwrap_or(1);
+            return HttpReply::html(format!(
+                "<html><head><title>Page {}</title></head><body><a href=\"/calendar?page={}\">next</a></body></html>",
+                page,
+                page + 1,
+            ));
+        }
+        common::file(&dir, request)
+    })
+    .await;
+
+    format!("http://{}/", addr)
+}
+
+async fn crawl(spider: SiteMapper) -> Vec<Endpoint> {
+    crawl_with(Crawler::new(Duration::from_millis(0), 4, 10), spider).await
+}
+
+async fn crawl_with(crawler: Crawler, spider: SiteMapper) -> Vec<Endpoint> {
+    let mut events = crawler
+        .stream(Arc::new(spider))
+        .await
+        .unwrap();
+
+    let mut endpoints = Vec::new();
+    while let Some(event) = events.next().await {
+        if let CrawlEvent::Item(endpoint) = event {
+            endpoints.push(endpoint);
+        }
+    }
+    endpoints.sort_by(|a, b| a.url.cmp(&b.url));
+    endpoints
+}
+
+fn find<'a>(endpoints: &'a [Endpoint], url: &str) -> &'a Endpoint {
+    endpoints
+        .iter()
+        .find(|endpoint| endpoint.url == url)
+        .unwrap_or_else(|| panic!("{} not found in {:?}", url, endpoints.iter().map(|e| &e.url).collect::<Vec<_>>()))
+}
+
+#[tokio::test]
+async fn maps_the_fixture_site() {
+    let root = fixture_site().await;
+    let spider = SiteMapper::new(&HttpSettings::default(), &root, false).unwrap().with_max_depth(3);
+    let endpoints = crawl(spider).await;
+    let urls = endpoints.iter().map(|endpoint| endpoint.url.as_str()).collect::<Vec<&str>>();
+
+    let index = find(&endpoints, &root);
+    assert_eq!(index.status, 200);
+    assert_eq!(index.title.as_deref(), Some("Fixture Shop"));
+    assert_eq!(index.forms.len(), 1);
+    assert_eq!(index.forms[0].method, "GET");
+
+    let about = find(&endpoints, &format!("{}about.html", root));
+    assert_eq!(about.title.as_deref(), Some("About us"));
+    assert_eq!(about.forms[0].method, "POST");
+
+    let books = find(&endpoints, &format!("{}products.html?category=books", root));
+    assert_eq!(books.parameters, vec!["category"]);
+    find(&endpoints, &format!("{}products.html?category=music", root));
+
+    // link, script and img tags, string literals of the script, the GET form.
+    find(&endpoints, &format!("{}static/style.css", root));
+    find(&endpoints, &format!("{}static/logo.png", root));
+    let script = find(&endpoints, &format!("{}static/app.js", root));
+    assert_eq!(script.content_type.as_deref(), Some("application/javascript"));
+    assert_eq!(find(&endpoints, &format!("{}api/v1/users", root)).status, 404);
+    find(&endpoints, &format!("{}api/v1/orders?limit=10", root));
+    find(&endpoints, &format!("{}search?q=", root));
+
+    // Out of scope, or not HTTP.
+    assert!(urls.iter().all(|url| url.starts_with(&root)), "{:?}", urls);
+    // The action of the POST form is visited, the form is not submitted.
+    assert_eq!(find(&endpoints, &format!("{}contact", root)).method, "GET");
+    assert!(endpoints.iter().all(|endpoint| endpoint.method == "GET"));
+}
+
+#[tokio::test]
+async fn stops_at_the_maximum_depth() {
+    let root = fixture_site().await;
+    let spider = SiteMapper::new(&HttpSettings::default(), &root, false).unwrap().with_max_depth(3);
+    let endpoints = crawl(spider).await;
+
+    let pages = endpoints
+        .iter()
+        .filter(|endpoint| endpoint.url.contains("/calendar"))
+        .map(|endpoint| endpoint.title.clone().unwrap_or_default())
+        .collect::<Vec<String>>();
+    // The start page is at depth 0, the first calendar page at 1.
+    assert_eq!(pages, vec!["Page 1", "Page 2", "Page 3"]);
+}
+
+#[tokio::test]
+async fn zero_concurrency_is_refused() {
+    let root = fixture_site().await;
+    let spider = SiteMapper::new(&HttpSettings::default(), &root, false).unwrap();
+    let crawl = Crawler::new(Duration::from_millis(0), 0, 0).stream(Arc::new(spider)).await;
+
+    assert!(matches!(crawl, Err(Error::Config(_))));
+}
@@ -0,0 +1,47 @@
+use std::path::Path;
+use std::sync::Arc;
+use vxsuite::{ check, CheckReport, CveDetails, Fixture, HttpSettings, QuotesSpider, WebDriverPool, WebDriverSettings };
+
+/// Checks the spider named after the fixture directory against one of its fixtures.
+async fn check_fixture(spider: &str, path: &Path, cache_dir: &Path) -> CheckReport {
+    let fixture = Fixture::load(path).unwrap();
+    let cache = fixture.cache(cache_dir).unwrap();
+    let name = path.display().to_string();
+    let http = HttpSettings::default();
+
+    match spider {
+        "cvedetails" => check(&CveDetails::new(&http).with_cache(cache), &fixture, &name).await,
+        "quotes" => {
+            let webdriver = Arc::new(WebDriverPool::new(&WebDriverSettings::default(), 1).unwrap());
+            check(&QuotesSpider::new(&http, webdriver).with_cache(cache), &fixture, &name).await
+        }
+        _ => panic!("no spider for fixtures/spiders/{}", spider),
+    }
+}
+
+#[tokio::test]
+async fn spiders_pass_their_fixtures() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/spiders");
+    let cache_dir = std::env::temp_dir().join(format!("vxsuite-spiders-{}", std::process::id()));
+
+    let mut dirs = std::fs::read_dir(&root)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.is_dir())
+        .collect::<Vec<_>>();
+    dirs.sort();
+    assert!(!dirs.is_empty());
+
+    let mut checked = 0;
+    for dir in dirs {
+        let spider = dir.file_name().unwrap().to_string_lossy().to_string();
+        for path in Fixture::find(&dir).unwrap() {
+            let report = check_fixture(&spider, &path, &cache_dir).await;
+            assert!(report.passed(), "{} : {:#?}", report.summary(), report.failures);
+            checked += 1;
+        }
+    }
+    let _ = std::fs::remove_dir_all(&cache_dir);
+
+    assert!(checked >= 3);
+}
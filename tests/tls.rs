@@ -0,0 +1,83 @@
+mod common;
+
+use common::{ serve_tls, socks5_pivot, HttpReply };
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use vxsuite::{ Connector, TlsProbe };
+
+/// A TLS server handing out the test leaf certificate and its CA, until the test ends.
+async fn tls_server() -> u16 {
+    tls_server_speaking(rustls::ALL_VERSIONS).await
+}
+
+async fn tls_server_speaking(versions: &[&'static rustls::SupportedProtocolVersion]) -> u16 {
+    serve_tls(versions, |_| HttpReply::not_found()).await.port()
+}
+
+#[tokio::test]
+async fn probes_a_local_rustls_server() {
+    let port = tls_server().await;
+    let report = TlsProbe::new(Duration::from_secs(3)).probe("localhost", port).await.unwrap();
+
+    let chain = report.chain.iter().map(|cert| cert.subject.as_str()).collect::<Vec<&str>>();
+    assert_eq!(chain, vec!["O=vxsuite tests, CN=localhost", "O=vxsuite tests, CN=vxsuite test CA"]);
+    assert!(report.hostname_matches);
+    assert_eq!(report.names(), vec!["localhost", "vx.test"]);
+
+    let supported = report
+        .protocols
+        .iter()
+        .map(|protocol| (protocol.version.as_str(), protocol.supported))
+        .collect::<Vec<(&str, bool)>>();
+    assert_eq!(
+        supported,
+        vec![("SSLv3", false), ("TLSv1.0", false), ("TLSv1.1", false), ("TLSv1.2", true), ("TLSv1.3", true)]
+    );
+    assert!(report.protocols[4].cipher.as_deref().unwrap().starts_with("TLS13_"));
+    assert!(report.issues().is_empty(), "{:?}", report.issues());
+}
+
+#[tokio::test]
+async fn reports_names_the_certificate_does_not_cover() {
+    let port = tls_server().await;
+    let report = TlsProbe::new(Duration::from_secs(3)).probe("127.0.0.1", port).await.unwrap();
+
+    // IP addresses are not checked against the DNS names.
+    assert!(!report.hostname_matches);
+    assert!(report.issues().is_empty());
+}
+
+#[tokio::test]
+async fn probes_through_the_scan_pivot() {
+    let port = tls_server().await;
+    let (proxy, connections) = socks5_pivot().await;
+
+    let report = TlsProbe::new(Duration::from_secs(3))
+        .with_connector(Connector::Socks5(proxy))
+        .probe("localhost", port)
+        .await
+        .unwrap();
+
+    assert!(report.hostname_matches);
+    assert_eq!(report.chain.len(), 2);
+    // The three legacy hellos, the TLS 1.3 handshake giving the chain, then TLS 1.2 alone.
+    assert_eq!(connections.load(Ordering::SeqCst), 5);
+}
+
+#[tokio::test]
+async fn tls12_only_servers_need_no_second_handshake() {
+    let port = tls_server_speaking(&[&rustls::version::TLS12]).await;
+    let (proxy, connections) = socks5_pivot().await;
+
+    let report = TlsProbe::new(Duration::from_secs(3))
+        .with_connector(Connector::Socks5(proxy))
+        .probe("localhost", port)
+        .await
+        .unwrap();
+
+    let supported = report.protocols.iter().map(|protocol| protocol.supported).collect::<Vec<bool>>();
+    assert_eq!(supported, vec![false, false, false, true, false]);
+    assert!(report.protocols[3].cipher.as_deref().unwrap().starts_with("TLS_ECDHE_"));
+    assert_eq!(report.chain.len(), 2);
+    assert_eq!(connections.load(Ordering::SeqCst), 4);
+}
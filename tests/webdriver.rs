@@ -0,0 +1,97 @@
+mod common;
+
+use common::{ HttpReply, HttpRequest };
+use std::sync::atomic::{ AtomicBool, AtomicUsize, Ordering };
+use std::sync::{ Arc, Mutex };
+use vxsuite::{ WebDriverPool, WebDriverSettings };
+
+/// A WebDriver answering the few commands the pool sends, its sessions dying when `alive` is cleared.
+struct StubDriver {
+    url: String,
+    sessions: Arc<AtomicUsize>,
+    alive: Arc<AtomicBool>,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+async fn stub_driver() -> StubDriver {
+    let sessions = Arc::new(AtomicUsize::new(0));
+    let alive = Arc::new(AtomicBool::new(true));
+    let requests = Arc::new(Mutex::new(Vec::new()));
+
+    let (opened, live, recorded) = (sessions.clone(), alive.clone(), requests.clone());
+    let addr = common::serve(move |request: &HttpRequest| {
+        recorded.lock().unwrap().push(format!("{} {}", request.method, request.path()));
+
+        match (request.method.as_str(), request.path()) {
+            ("POST", "/session") => {
+                let id = opened.fetch_add(1, Ordering::SeqCst) + 1;
+                live.store(true, Ordering::SeqCst);
+                HttpReply::json(format!(
+                    r#"{{"value":{{"sessionId":"session-{}","capabilities":{{"browserName":"chrome"}}}}}}"#,
+                    id
+                ))
+            }
+            ("GET", path) if path.ends_with("/url") && live.load(Ordering::SeqCst) => {
+                HttpReply::json(r#"{"value":"about:blank"}"#)
+            }
+            ("GET", path) if path.ends_with("/url") => HttpReply::new(
+                404,
+                "application/json",
+                r#"{"value":{"error":"invalid session id","message":"session deleted","stacktrace":""}}"#,
+            ),
+            _ => HttpReply::json(r#"{"value":null}"#),
+        }
+    })
+    .await;
+
+    StubDriver { url: format!("http://{}", addr), sessions, alive, requests }
+}
+
+fn pool(driver: &StubDriver, size: usize) -> WebDriverPool {
+    let settings = WebDriverSettings { url: driver.url.clone(), ..WebDriverSettings::default() };
+    WebDriverPool::new(&settings, size).unwrap()
+}
+
+#[tokio::test]
+async fn returned_sessions_are_reused() {
+    let driver = stub_driver().await;
+    let pool = pool(&driver, 2);
+
+    drop(pool.checkout().await.unwrap());
+    drop(pool.checkout().await.unwrap());
+    assert_eq!(driver.sessions.load(Ordering::SeqCst), 1);
+    // The idle session was checked before being handed out again.
+    assert!(driver.requests.lock().unwrap().contains(&"GET /session/session-1/url".to_string()));
+
+    // Sessions checked out together are distinct.
+    let first = pool.checkout().await.unwrap();
+    let second = pool.checkout().await.unwrap();
+    assert_eq!(driver.sessions.load(Ordering::SeqCst), 2);
+    drop((first, second));
+}
+
+#[tokio::test]
+async fn dead_and_discarded_sessions_are_replaced() {
+    let driver = stub_driver().await;
+    let pool = pool(&driver, 1);
+
+    drop(pool.checkout().await.unwrap());
+    driver.alive.store(false, Ordering::SeqCst);
+    drop(pool.checkout().await.unwrap());
+    assert_eq!(driver.sessions.load(Ordering::SeqCst), 2);
+
+    pool.checkout().await.unwrap().discard();
+    drop(pool.checkout().await.unwrap());
+    assert_eq!(driver.sessions.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn missing_driver_is_reported() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    drop(listener);
+
+    let pool = WebDriverPool::new(&WebDriverSettings { url, ..WebDriverSettings::default() }, 1).unwrap();
+    let err = pool.checkout().await.err().unwrap();
+    assert!(err.to_string().contains("no WebDriver reachable"), "{}", err);
+}